@@ -0,0 +1,192 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use once_cell::sync::{Lazy, OnceCell};
+
+// This module contains the private implementation details of the device
+// provider and its devices
+//
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "rstestpatterndeviceprovider",
+        gst::DebugColorFlags::empty(),
+        Some("Test Pattern Device Provider"),
+    )
+});
+
+// Configuration of one virtual device
+#[derive(Debug)]
+pub struct Preset {
+    name: &'static str,
+    width: i32,
+    height: i32,
+    fps: (i32, i32),
+    // Properties set on the created rstestpattern, in string form
+    properties: &'static [(&'static str, &'static str)],
+}
+
+static PRESETS: &[Preset] = &[
+    Preset {
+        name: "SMPTE bars 1080p30",
+        width: 1920,
+        height: 1080,
+        fps: (30, 1),
+        properties: &[("pattern", "smpte")],
+    },
+    Preset {
+        name: "moving bar 720p60",
+        width: 1280,
+        height: 720,
+        fps: (60, 1),
+        properties: &[("pattern", "bar"), ("speed", "4"), ("size", "72")],
+    },
+    Preset {
+        name: "moving bar 1080p30",
+        width: 1920,
+        height: 1080,
+        fps: (30, 1),
+        properties: &[("pattern", "bar"), ("speed", "8"), ("size", "108")],
+    },
+    Preset {
+        name: "static bar 640x480p30",
+        width: 640,
+        height: 480,
+        fps: (30, 1),
+        properties: &[("pattern", "bar"), ("speed", "0"), ("size", "48")],
+    },
+];
+
+impl Preset {
+    fn caps(&self) -> gst::Caps {
+        gst::Caps::builder("video/x-raw")
+            .field("format", gst_video::VideoFormat::Bgrx.to_str())
+            .field("width", self.width)
+            .field("height", self.height)
+            .field("framerate", gst::Fraction::new(self.fps.0, self.fps.1))
+            .build()
+    }
+
+    fn device(&'static self) -> super::TestPatternDevice {
+        let properties = gst::Structure::builder("rstestpattern-device")
+            .field("device.api", "rstestpattern")
+            .field("device.preset", self.name)
+            .build();
+
+        let device = glib::Object::new::<super::TestPatternDevice>(&[
+            ("display-name", &format!("Test pattern: {}", self.name)),
+            ("device-class", &"Video/Source"),
+            ("caps", &self.caps()),
+            ("properties", &properties),
+        ])
+        .expect("Failed to create device");
+
+        TestPatternDevice::from_instance(&device)
+            .preset
+            .set(self)
+            .expect("Device preset set twice");
+
+        device
+    }
+}
+
+#[derive(Default)]
+pub struct TestPatternDeviceProvider {}
+
+#[glib::object_subclass]
+impl ObjectSubclass for TestPatternDeviceProvider {
+    const NAME: &'static str = "TestPatternDeviceProvider";
+    type Type = super::TestPatternDeviceProvider;
+    type ParentType = gst::DeviceProvider;
+}
+
+impl ObjectImpl for TestPatternDeviceProvider {}
+
+impl GstObjectImpl for TestPatternDeviceProvider {}
+
+impl DeviceProviderImpl for TestPatternDeviceProvider {
+    fn metadata() -> Option<&'static gst::subclass::DeviceProviderMetadata> {
+        static METADATA: Lazy<gst::subclass::DeviceProviderMetadata> = Lazy::new(|| {
+            gst::subclass::DeviceProviderMetadata::new(
+                "Test pattern device provider",
+                "Source/Video",
+                "Lists virtual devices producing test patterns",
+                "Alexander Stein <alexander.stein@mailbox.org>",
+            )
+        });
+
+        Some(&*METADATA)
+    }
+
+    fn probe(&self, _provider: &Self::Type) -> Vec<gst::Device> {
+        PRESETS
+            .iter()
+            .map(|preset| preset.device().upcast())
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct TestPatternDevice {
+    preset: OnceCell<&'static Preset>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for TestPatternDevice {
+    const NAME: &'static str = "TestPatternDevice";
+    type Type = super::TestPatternDevice;
+    type ParentType = gst::Device;
+}
+
+impl ObjectImpl for TestPatternDevice {}
+
+impl GstObjectImpl for TestPatternDevice {}
+
+impl DeviceImpl for TestPatternDevice {
+    fn create_element(
+        &self,
+        _device: &Self::Type,
+        name: Option<&str>,
+    ) -> Result<gst::Element, gst::LoggableError> {
+        let preset = self
+            .preset
+            .get()
+            .ok_or_else(|| gst::loggable_error!(CAT, "Device without preset"))?;
+
+        let src = gst::ElementFactory::make("rstestpattern", None)
+            .map_err(|_| gst::loggable_error!(CAT, "Failed to create rstestpattern"))?;
+        for (property, value) in preset.properties {
+            src.set_property_from_str(property, value);
+        }
+
+        // Keep the output at the resolution and framerate the device
+        // advertises, whatever downstream would accept
+        let capsfilter = gst::ElementFactory::make("capsfilter", None)
+            .map_err(|_| gst::loggable_error!(CAT, "Failed to create capsfilter"))?;
+        capsfilter.set_property("caps", &preset.caps());
+
+        let bin = gst::Bin::new(name);
+        bin.add_many(&[&src, &capsfilter])
+            .map_err(|_| gst::loggable_error!(CAT, "Failed to add elements to bin"))?;
+        src.link(&capsfilter)
+            .map_err(|_| gst::loggable_error!(CAT, "Failed to link capsfilter"))?;
+
+        let pad = capsfilter.static_pad("src").unwrap();
+        let ghost_pad = gst::GhostPad::with_target(Some("src"), &pad)
+            .map_err(|_| gst::loggable_error!(CAT, "Failed to create ghost pad"))?;
+        bin.add_pad(&ghost_pad)
+            .map_err(|_| gst::loggable_error!(CAT, "Failed to add ghost pad"))?;
+
+        Ok(bin.upcast())
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use gst::glib;
+use gst::prelude::*;
+
+mod imp;
+
+glib::wrapper! {
+    pub struct TestPatternDeviceProvider(ObjectSubclass<imp::TestPatternDeviceProvider>) @extends gst::DeviceProvider, gst::Object;
+}
+
+glib::wrapper! {
+    pub struct TestPatternDevice(ObjectSubclass<imp::TestPatternDevice>) @extends gst::Device, gst::Object;
+}
+
+// Registers the device provider listing the virtual test pattern devices,
+// so they show up in gst::DeviceMonitor.
+pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    gst::DeviceProvider::register(
+        Some(plugin),
+        "rstestpatterndeviceprovider",
+        gst::Rank::Marginal,
+        TestPatternDeviceProvider::static_type(),
+    )
+}
@@ -9,23 +9,52 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 #![allow(clippy::non_send_fields_in_send_ty)]
 
+//! Test pattern elements for GStreamer
+//!
+//! Besides being loaded as a plugin from the GStreamer plugin path, the
+//! crate can be linked into applications directly. Calling
+//! [`plugin_register_static()`] after `gst::init()` then registers all
+//! elements, and e.g. `gst::ElementFactory::make("rstestpattern", None)`
+//! works without any plugin path configuration. Both ways go through the
+//! same `plugin_init()`.
+
 use gst::glib;
 
+mod deviceprovider;
+pub mod pattern;
 mod testpattern;
+mod testpatternanalyze;
+mod testpatternoverlay;
+mod video;
+
+mod plugin {
+    use super::plugin_init;
 
-gst::plugin_define!(
-    rstestpattern,
-    env!("CARGO_PKG_DESCRIPTION"),
-    plugin_init,
-    concat!(env!("CARGO_PKG_VERSION"), "-", env!("COMMIT_ID")),
-    "MIT/X11",
-    env!("CARGO_PKG_NAME"),
-    env!("CARGO_PKG_NAME"),
-    env!("CARGO_PKG_REPOSITORY"),
-    env!("BUILD_REL_DATE")
-);
+    gst::plugin_define!(
+        rstestpattern,
+        env!("CARGO_PKG_DESCRIPTION"),
+        plugin_init,
+        concat!(env!("CARGO_PKG_VERSION"), "-", env!("COMMIT_ID")),
+        "MIT/X11",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_REPOSITORY"),
+        env!("BUILD_REL_DATE")
+    );
+}
+
+/// Registers the plugin and all its elements with GStreamer without loading
+/// it from the plugin path. Has to be called after `gst::init()`.
+pub fn plugin_register_static() -> Result<(), glib::BoolError> {
+    plugin::plugin_register_static()
+}
 
+// Registers all elements of the plugin, used by both the dynamic plugin entry
+// point and plugin_register_static()
 fn plugin_init(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
     testpattern::register(plugin)?;
+    testpatternoverlay::register(plugin)?;
+    testpatternanalyze::register(plugin)?;
+    deviceprovider::register(plugin)?;
     Ok(())
 }
@@ -0,0 +1,124 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::f64::consts::PI;
+
+// BT.601 luma weights, the axis saturation is scaled around
+const LUMA: [f64; 3] = [0.299, 0.587, 0.114];
+// Fixed point precision of the hue/saturation matrix
+const MATRIX_SHIFT: u32 = 10;
+
+/// Adjustments applied to the rendered frame, modelled after the channels of
+/// the GstColorBalance interface
+///
+/// All values range from [`ColorBalance::MIN`] to [`ColorBalance::MAX`],
+/// 0 leaves the picture unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColorBalance {
+    /// Offset added to every component, the extremes giving black and white
+    pub brightness: i32,
+    /// Scales components around mid grey, from flat grey to doubled
+    pub contrast: i32,
+    /// Rotation of the hue, from -180 to 180 degrees
+    pub hue: i32,
+    /// Scales the distance from grey, from fully desaturated to doubled
+    pub saturation: i32,
+}
+
+impl ColorBalance {
+    pub const MIN: i32 = -1000;
+    pub const MAX: i32 = 1000;
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Applies the adjustments to a canvas of ARGB pixels, keeping alpha
+    pub(super) fn apply(&self, canvas: &mut [u32]) {
+        if self.is_identity() {
+            return;
+        }
+
+        let lut = self.lut();
+        let matrix = if self.hue != 0 || self.saturation != 0 {
+            Some(self.matrix())
+        } else {
+            None
+        };
+
+        for pixel in canvas.iter_mut() {
+            let mut rgb = [
+                lut[((*pixel >> 16) & 0xff) as usize] as i32,
+                lut[((*pixel >> 8) & 0xff) as usize] as i32,
+                lut[(*pixel & 0xff) as usize] as i32,
+            ];
+
+            if let Some(ref m) = matrix {
+                let [r, g, b] = rgb;
+                for (out, row) in rgb.iter_mut().zip(m.iter()) {
+                    let v = (row[0] * r + row[1] * g + row[2] * b + (1 << (MATRIX_SHIFT - 1)))
+                        >> MATRIX_SHIFT;
+                    *out = v.clamp(0, 255);
+                }
+            }
+
+            *pixel = (*pixel & 0xff00_0000)
+                | (rgb[0] as u32) << 16
+                | (rgb[1] as u32) << 8
+                | rgb[2] as u32;
+        }
+    }
+
+    // Brightness and contrast act on each component independently
+    fn lut(&self) -> [u8; 256] {
+        let contrast = self.contrast.clamp(Self::MIN, Self::MAX) - Self::MIN;
+        let brightness = self.brightness.clamp(Self::MIN, Self::MAX) * 255 / Self::MAX;
+
+        let mut lut = [0; 256];
+        for (v, out) in lut.iter_mut().enumerate() {
+            let v = (v as i32 - 128) * contrast / Self::MAX + 128 + brightness;
+            *out = v.clamp(0, 255) as u8;
+        }
+        lut
+    }
+
+    // Saturation scaling around the luma axis followed by a rotation around
+    // the grey axis, in fixed point
+    fn matrix(&self) -> [[i32; 3]; 3] {
+        let s = (self.saturation.clamp(Self::MIN, Self::MAX) - Self::MIN) as f64 / Self::MAX as f64;
+        let angle = self.hue.clamp(Self::MIN, Self::MAX) as f64 * PI / Self::MAX as f64;
+        let (sin, cos) = angle.sin_cos();
+
+        let mut sat = [[0.0; 3]; 3];
+        for (i, row) in sat.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = LUMA[j] * (1.0 - s) + if i == j { s } else { 0.0 };
+            }
+        }
+
+        let a = cos + (1.0 - cos) / 3.0;
+        let b = (1.0 - cos) / 3.0 - (1.0f64 / 3.0).sqrt() * sin;
+        let c = (1.0 - cos) / 3.0 + (1.0f64 / 3.0).sqrt() * sin;
+        let rot = [[a, b, c], [c, a, b], [b, c, a]];
+
+        let mut m = [[0; 3]; 3];
+        for (out_row, rot_row) in m.iter_mut().zip(rot.iter()) {
+            for (j, out) in out_row.iter_mut().enumerate() {
+                let v: f64 = rot_row
+                    .iter()
+                    .zip(sat.iter())
+                    .map(|(r, sat_row)| r * sat_row[j])
+                    .sum();
+                *out = (v * (1 << MATRIX_SHIFT) as f64).round() as i32;
+            }
+        }
+        m
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::f64::consts::PI;
+
+use super::gradient::lerp;
+
+// Duration of one cycle of the Lissajous path in seconds and the number of
+// horizontal and vertical oscillations per cycle
+const LISSAJOUS_PERIOD: f64 = 8.0;
+const LISSAJOUS_X: f64 = 3.0;
+const LISSAJOUS_Y: f64 = 2.0;
+
+/// Center of a ball of `radius` on its Lissajous path at `time`
+/// nanoseconds, always keeping the ball inside of the frame
+pub(super) fn lissajous(width: usize, height: usize, radius: f64, time: u64) -> (f64, f64) {
+    let phase = 2.0 * PI * (time as f64 / 1_000_000_000.0) / LISSAJOUS_PERIOD;
+    let range_x = (width as f64 - 2.0 * radius).max(0.0);
+    let range_y = (height as f64 - 2.0 * radius).max(0.0);
+
+    (
+        radius + range_x * 0.5 * (1.0 + (LISSAJOUS_X * phase + PI / 2.0).sin()),
+        radius + range_y * 0.5 * (1.0 + (LISSAJOUS_Y * phase).sin()),
+    )
+}
+
+/// Fills all pixels whose centers are inside of the circle at `center` with
+/// `color`, blended over them with `opacity` out of 255
+pub(super) fn disc(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    center: (f64, f64),
+    radius: f64,
+    color: u32,
+    opacity: u32,
+) {
+    let (cx, cy) = center;
+    let clip = |v: f64, max: usize| (v.max(0.0) as usize).min(max);
+    let (x0, x1) = (clip(cx - radius, width), clip((cx + radius).ceil(), width));
+    let (y0, y1) = (
+        clip(cy - radius, height),
+        clip((cy + radius).ceil(), height),
+    );
+
+    for y in y0..y1 {
+        let dy = y as f64 + 0.5 - cy;
+        for x in x0..x1 {
+            let dx = x as f64 + 0.5 - cx;
+            if dx * dx + dy * dy <= radius * radius {
+                let pixel = &mut canvas[y * width + x];
+                *pixel = lerp(*pixel, color, opacity, 255);
+            }
+        }
+    }
+}
+
+/// Ball radius actually used in a `width` x `height` frame, so that the
+/// ball always fits
+pub(super) fn fitted_radius(radius: u32, width: usize, height: usize) -> f64 {
+    (radius as f64).min(width.min(height) as f64 / 2.0)
+}
+
+/// Ball falling under gravity and bouncing off the bottom of the frame
+///
+/// The motion between two bounces is computed exactly, so the trajectory
+/// doesn't depend on the size of the time steps. Once the bounces get lower
+/// than a pixel the ball is dropped from the top again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BouncingBall {
+    /// Distance of the lowest point of the ball from the bottom in pixels
+    pub height: f64,
+    /// Upwards velocity in pixels per second
+    pub velocity: f64,
+}
+
+impl Default for BouncingBall {
+    /// Ball at the top of the frame, at rest
+    fn default() -> Self {
+        BouncingBall {
+            height: f64::INFINITY,
+            velocity: 0.0,
+        }
+    }
+}
+
+impl BouncingBall {
+    /// Advances the simulation by `dt` seconds for a ball of `radius` in a
+    /// `width` x `height` frame. `gravity` is in pixels per second squared,
+    /// `elasticity` the fraction of the velocity kept on every bounce.
+    pub fn advance(
+        &mut self,
+        dt: f64,
+        gravity: f64,
+        elasticity: f64,
+        (width, height): (usize, usize),
+        radius: u32,
+    ) {
+        let top = height as f64 - 2.0 * fitted_radius(radius, width, height);
+        let gravity = gravity.max(0.0);
+        let mut dt = dt.max(0.0);
+
+        self.height = self.height.min(top);
+        // Bounded, a very elastic ball in a tiny frame bounces a lot
+        for _ in 0..1000 {
+            let (y, v) = (self.height, self.velocity);
+            let hit = if gravity > 0.0 {
+                (v + (v * v + 2.0 * gravity * y).sqrt()) / gravity
+            } else if v < 0.0 {
+                -y / v
+            } else {
+                f64::INFINITY
+            };
+
+            if hit > dt {
+                self.height = (y + v * dt - gravity * dt * dt / 2.0).clamp(0.0, top);
+                self.velocity = v - gravity * dt;
+                return;
+            }
+
+            dt -= hit;
+            self.height = 0.0;
+            self.velocity = -(v - gravity * hit) * elasticity;
+            if self.velocity * self.velocity <= 2.0 * gravity {
+                *self = BouncingBall {
+                    height: top,
+                    velocity: 0.0,
+                };
+            }
+        }
+    }
+
+    /// Center of the ball
+    pub(super) fn center(&self, width: usize, height: usize, radius: f64) -> (f64, f64) {
+        let top = height as f64 - 2.0 * radius;
+        (
+            width as f64 / 2.0,
+            height as f64 - radius - self.height.clamp(0.0, top.max(0.0)),
+        )
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::color::flatten;
+use super::gradient::lerp;
+
+// Color bar patterns. All bar edges are computed as fractions of the frame
+// size rounded down, so they land on the same pixels for every renderer.
+
+// Black with 7.5% setup, the reference the PLUGE levels are relative to
+const SETUP_BLACK: u32 = 0xff131313;
+
+// SMPTE EG 1 bars: seven 75% bars, the reverse blue strip and the bottom
+// section with -I, white, +Q and the PLUGE. Column edges in 84ths of the
+// width.
+const SMPTE_TOP: [(u32, usize); 7] = [
+    (0xffbfbfbf, 12),
+    (0xffbfbf00, 24),
+    (0xff00bfbf, 36),
+    (0xff00bf00, 48),
+    (0xffbf00bf, 60),
+    (0xffbf0000, 72),
+    (0xff0000bf, 84),
+];
+const SMPTE_STRIP: [(u32, usize); 7] = [
+    (0xff0000bf, 12),
+    (SETUP_BLACK, 24),
+    (0xffbf00bf, 36),
+    (SETUP_BLACK, 48),
+    (0xff00bfbf, 60),
+    (SETUP_BLACK, 72),
+    (0xffbfbfbf, 84),
+];
+const SMPTE_BOTTOM: [(u32, usize); 8] = [
+    (0xff00214c, 15),
+    (0xffffffff, 30),
+    (0xff32006a, 45),
+    (SETUP_BLACK, 60),
+    // PLUGE: 3.5%, 7.5% and 11.5%
+    (0xff090909, 64),
+    (SETUP_BLACK, 68),
+    (0xff1d1d1d, 72),
+    (SETUP_BLACK, 84),
+];
+
+// SMPTE RP 219 bars, column edges in 56ths of the width: 1/8 side panels
+// and seven 3/28 wide bars in between. Levels are percentages of the full
+// RGB range, so the +2% and +4% PLUGE steps end up exactly at the right
+// studio range luma after conversion. The -2% step can't be represented in
+// RGB and is clipped to black.
+const RP219_TOP: [(u32, usize); 9] = [
+    (0xff666666, 7),
+    (0xffbfbfbf, 13),
+    (0xffbfbf00, 19),
+    (0xff00bfbf, 25),
+    (0xff00bf00, 31),
+    (0xffbf00bf, 37),
+    (0xffbf0000, 43),
+    (0xff0000bf, 49),
+    (0xff666666, 56),
+];
+const RP219_SECOND: [(u32, usize); 3] = [(0xff00ffff, 7), (0xffbfbfbf, 49), (0xff0000ff, 56)];
+// The luma ramp is drawn over the black between the edges 13 and 43
+const RP219_THIRD: [(u32, usize); 4] = [
+    (0xffffff00, 7),
+    (0xff000000, 43),
+    (0xffffffff, 49),
+    (0xffff0000, 56),
+];
+const RP219_RAMP: (usize, usize) = (13, 43);
+const RP219_BOTTOM: [(u32, usize); 11] = [
+    (0xff262626, 7),
+    (0xff000000, 16),
+    (0xffffffff, 28),
+    (0xff000000, 33),
+    // PLUGE: -2%, 0%, +2%, 0%, +4%
+    (0xff000000, 35),
+    (0xff000000, 37),
+    (0xff050505, 39),
+    (0xff000000, 41),
+    (0xff0a0a0a, 43),
+    (0xff000000, 49),
+    (0xff262626, 56),
+];
+
+/// Draws SMPTE color bars into a `width` x `height` canvas
+pub(super) fn smpte(canvas: &mut [u32], width: usize, height: usize) {
+    let strip = height * 2 / 3;
+    let bottom = height * 3 / 4;
+
+    band(canvas, width, 0..strip, &SMPTE_TOP, 84);
+    band(canvas, width, strip..bottom, &SMPTE_STRIP, 84);
+    band(canvas, width, bottom..height, &SMPTE_BOTTOM, 84);
+}
+
+/// Draws SMPTE RP 219 HD color bars with the luma ramp and the PLUGE
+pub(super) fn rp219(canvas: &mut [u32], width: usize, height: usize) {
+    let second = height * 7 / 12;
+    let third = height * 8 / 12;
+    let bottom = height * 9 / 12;
+
+    band(canvas, width, 0..second, &RP219_TOP, 56);
+    band(canvas, width, second..third, &RP219_SECOND, 56);
+    band(canvas, width, third..bottom, &RP219_THIRD, 56);
+    band(canvas, width, bottom..height, &RP219_BOTTOM, 56);
+
+    let start = RP219_RAMP.0 * width / 56;
+    let end = RP219_RAMP.1 * width / 56;
+    let steps = (end - start).saturating_sub(1).max(1);
+    for row in canvas[third * width..bottom * width].chunks_exact_mut(width) {
+        for (x, pixel) in row[start..end].iter_mut().enumerate() {
+            let l = (x * 255 / steps) as u32;
+            *pixel = 0xff000000 | l << 16 | l << 8 | l;
+        }
+    }
+}
+
+// Fills the lines `rows` with vertical bars. Each bar is given by its color
+// and the position of its right edge in `denom`ths of the width.
+fn band(
+    canvas: &mut [u32],
+    width: usize,
+    rows: std::ops::Range<usize>,
+    bars: &[(u32, usize)],
+    denom: usize,
+) {
+    for row in canvas[rows.start * width..rows.end * width].chunks_exact_mut(width) {
+        let mut start = 0;
+        for &(color, edge) in bars {
+            let end = edge * width / denom;
+            row[start..end].fill(color);
+            start = end;
+        }
+    }
+}
+
+/// Draws the eight EBU color bars, white at 100% and the colored bars at
+/// `level` (0-255). Every bar is `width / 8` pixels wide, the remaining
+/// pixels are added to the last one.
+pub(super) fn ebu(canvas: &mut [u32], width: usize, level: u8) {
+    let l = level as u32;
+    let colors = [
+        0xffffffff,
+        0xff000000 | l << 16 | l << 8,
+        0xff000000 | l << 8 | l,
+        0xff000000 | l << 8,
+        0xff000000 | l << 16 | l,
+        0xff000000 | l << 16,
+        0xff000000 | l,
+        0xff000000,
+    ];
+    let bar = width / 8;
+
+    for row in canvas.chunks_exact_mut(width) {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = match bar {
+                0 => colors[7],
+                _ => colors[(x / bar).min(7)],
+            };
+        }
+    }
+}
+
+/// Direction the bar pattern scrolls in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// Horizontal bars moving down
+    Vertical,
+    /// Vertical bars moving right
+    Horizontal,
+}
+
+/// Draws `count` horizontal bars, each `size` lines high and `spacing`
+/// lines apart, the first one starting at line `offset`. A single bar is
+/// cut off at the bottom, multiple bars wrap around to the top
+/// independently. `color` gives the color of every bar, with `blend` it's
+/// composed over the canvas instead of replacing it.
+pub(super) fn horizontal_bars(
+    canvas: &mut [u32],
+    width: usize,
+    (offset, size): (u32, u32),
+    count: u32,
+    spacing: u32,
+    color: impl Fn(usize) -> u32,
+    blend: bool,
+) {
+    let height = canvas.len() / width.max(1);
+    if height == 0 {
+        return;
+    }
+
+    if count <= 1 {
+        let start = (offset as usize).min(height);
+        let end = (offset as usize).saturating_add(size as usize).min(height);
+        paint(&mut canvas[start * width..end * width], color(0), blend);
+        return;
+    }
+
+    let pitch = size as u64 + spacing as u64;
+    for bar in 0..count as usize {
+        let start = (offset as u64 + bar as u64 * pitch) % height as u64;
+        let color = color(bar);
+        for line in start..start + (size as u64).min(height as u64) {
+            let line = (line % height as u64) as usize;
+            paint(&mut canvas[line * width..(line + 1) * width], color, blend);
+        }
+    }
+}
+
+/// Draws `count` vertical bars like [`horizontal_bars`] draws horizontal
+/// ones, `offset`, `size` and `spacing` are in columns
+pub(super) fn vertical_bars(
+    canvas: &mut [u32],
+    width: usize,
+    (offset, size): (u32, u32),
+    count: u32,
+    spacing: u32,
+    color: impl Fn(usize) -> u32,
+    blend: bool,
+) {
+    if width == 0 {
+        return;
+    }
+
+    if count <= 1 {
+        let start = (offset as usize).min(width);
+        let end = (offset as usize).saturating_add(size as usize).min(width);
+        for row in canvas.chunks_exact_mut(width) {
+            paint(&mut row[start..end], color(0), blend);
+        }
+        return;
+    }
+
+    let pitch = size as u64 + spacing as u64;
+    let size = (size as usize).min(width);
+    for bar in 0..count as usize {
+        let start = ((offset as u64 + bar as u64 * pitch) % width as u64) as usize;
+        // Split into the part up to the right edge and the wrapped one
+        let end = (start + size).min(width);
+        let wrapped = start + size - end;
+        let color = color(bar);
+        for row in canvas.chunks_exact_mut(width) {
+            paint(&mut row[start..end], color, blend);
+            paint(&mut row[..wrapped], color, blend);
+        }
+    }
+}
+
+/// Draws bars like [`horizontal_bars`], but with the top and bottom edges
+/// blended into the canvas over `softness` lines. The bars start at the
+/// fractional line `offset`, so the blend follows sub-pixel motion.
+pub(super) fn soft_horizontal_bars(
+    canvas: &mut [u32],
+    width: usize,
+    (offset, size): (f64, u32),
+    (count, spacing): (u32, u32),
+    softness: u32,
+    color: impl Fn(usize) -> u32,
+    blend: bool,
+) {
+    let height = canvas.len() / width.max(1);
+    let mix = |line: usize, bar, weight| {
+        let color = color(bar);
+        for pixel in canvas[line * width..(line + 1) * width].iter_mut() {
+            *pixel = soften(*pixel, color, weight, blend);
+        }
+    };
+    soft_bars(height, (offset, size), (count, spacing), softness, mix);
+}
+
+/// Draws bars like [`vertical_bars`] with soft left and right edges, see
+/// [`soft_horizontal_bars`]
+pub(super) fn soft_vertical_bars(
+    canvas: &mut [u32],
+    width: usize,
+    (offset, size): (f64, u32),
+    (count, spacing): (u32, u32),
+    softness: u32,
+    color: impl Fn(usize) -> u32,
+    blend: bool,
+) {
+    let mix = |column: usize, bar, weight| {
+        let color = color(bar);
+        for row in canvas.chunks_exact_mut(width) {
+            row[column] = soften(row[column], color, weight, blend);
+        }
+    };
+    soft_bars(width, (offset, size), (count, spacing), softness, mix);
+}
+
+// Fills `pixels` with `color`, or composes it over them with `blend`
+fn paint(pixels: &mut [u32], color: u32, blend: bool) {
+    if blend {
+        for pixel in pixels.iter_mut() {
+            *pixel = flatten(color, *pixel);
+        }
+    } else {
+        pixels.fill(color);
+    }
+}
+
+// Pixel covered by a soft edged bar by `weight` (0-256)
+fn soften(pixel: u32, color: u32, weight: u32, blend: bool) -> u32 {
+    let color = if blend { flatten(color, pixel) } else { color };
+    lerp(pixel, color, weight, 256)
+}
+
+// Calls `blend` with the weight (0-256) every line along an axis `length`
+// lines long is covered by each bar with. The edges ramp up linearly over
+// `softness` lines centered on the edge. Like with the hard edged bars a
+// single bar is cut off at the end, multiple ones wrap around.
+fn soft_bars(
+    length: usize,
+    (offset, size): (f64, u32),
+    (count, spacing): (u32, u32),
+    softness: u32,
+    mut blend: impl FnMut(usize, usize, u32),
+) {
+    if length == 0 {
+        return;
+    }
+
+    let softness = softness.max(1) as f64;
+    let pitch = size as f64 + spacing as f64;
+    for bar in 0..count.max(1) as usize {
+        let top = offset + bar as f64 * pitch - softness / 2.0;
+        let bottom = offset + bar as f64 * pitch + size as f64 + softness / 2.0;
+        let first = top.floor() as i64;
+        let last = (bottom.ceil() as i64).min(first + length as i64);
+
+        for line in first..last {
+            let center = line as f64 + 0.5;
+            let coverage = ((center - top) / softness)
+                .min((bottom - center) / softness)
+                .clamp(0.0, 1.0);
+            let weight = (coverage * 256.0).round() as u32;
+            let line = if count > 1 {
+                line.rem_euclid(length as i64) as usize
+            } else if line >= 0 && line < length as i64 {
+                line as usize
+            } else {
+                continue;
+            };
+            if weight > 0 {
+                blend(line, bar, weight);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Format, FrameLayout, Pattern, PatternParams, Renderer};
+    use super::*;
+
+    // Renders a pattern as BGRx and returns the pixels as ARGB
+    fn render(pattern: Pattern, width: u32, height: u32) -> Vec<u32> {
+        let layout = FrameLayout::new(Format::Bgrx, width, height);
+        let mut out = vec![0; layout.size()];
+        let params = PatternParams {
+            pattern,
+            ..PatternParams::default()
+        };
+        Renderer::new().render(0, &params, &layout, &mut out);
+
+        out.chunks_exact(4)
+            .map(|p| 0xff000000 | (p[2] as u32) << 16 | (p[1] as u32) << 8 | p[0] as u32)
+            .collect()
+    }
+
+    #[test]
+    fn smpte_bar_centers() {
+        for &(width, height) in &[(1280, 720), (333, 241)] {
+            let frame = render(Pattern::Smpte, width, height);
+            let (width, height) = (width as usize, height as usize);
+            let at = |x: usize, y: usize| frame[y * width + x];
+
+            for (band, y) in [(&SMPTE_TOP, height / 3), (&SMPTE_STRIP, height * 17 / 24)] {
+                for (i, &(color, _)) in band.iter().enumerate() {
+                    let x = (12 * i + 6) * width / 84;
+                    assert_eq!(at(x, y), color, "{}x{} at {},{}", width, height, x, y);
+                }
+            }
+
+            let y = height * 7 / 8;
+            let mut start = 0;
+            for &(color, edge) in &SMPTE_BOTTOM {
+                let x = (start + edge) * width / 168;
+                assert_eq!(at(x, y), color, "{}x{} at {},{}", width, height, x, y);
+                start = edge;
+            }
+        }
+    }
+
+    #[test]
+    fn smpte_edges_on_pixel_boundaries() {
+        let frame = render(Pattern::Smpte, 840, 480);
+        let row = &frame[..840];
+
+        for i in 0..7 {
+            assert!(row[i * 120..(i + 1) * 120]
+                .iter()
+                .all(|&pixel| pixel == SMPTE_TOP[i].0));
+        }
+    }
+
+    #[test]
+    fn rp219_pluge_levels() {
+        let (width, height) = (1920, 1080);
+        let layout = FrameLayout::new(Format::Nv12, width, height);
+        let mut out = vec![0; layout.size()];
+        let params = PatternParams {
+            pattern: Pattern::Rp219,
+            ..PatternParams::default()
+        };
+        Renderer::new().render(0, &params, &layout, &mut out);
+
+        let frame = render(Pattern::Rp219, width, height);
+        let y = 1000;
+        // -2% is clipped to black, +2% and +4% are 20 and 25 in studio range
+        for &(start, end, argb, luma) in &[
+            (33, 35, 0xff000000, 16),
+            (35, 37, 0xff000000, 16),
+            (37, 39, 0xff050505, 20),
+            (39, 41, 0xff000000, 16),
+            (41, 43, 0xff0a0a0a, 25),
+        ] {
+            for x in start * 1920 / 56..end * 1920 / 56 {
+                assert_eq!(frame[y * 1920 + x], argb, "BGRx at {}", x);
+                assert_eq!(out[y * 1920 + x], luma, "NV12 at {}", x);
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Draws a checkerboard of `size` pixel squares, starting with `first` in
+/// the top left corner and alternating with `second`. The board is moved
+/// down by `offset` lines.
+pub(super) fn checkerboard(
+    canvas: &mut [u32],
+    width: usize,
+    size: u32,
+    offset: u32,
+    first: u32,
+    second: u32,
+) {
+    let size = size.max(1) as usize;
+    let period = 2 * size;
+    let offset = offset as usize % period;
+
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        let odd_row = (y + period - offset) % period >= size;
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let odd = odd_row != ((x / size) % 2 == 1);
+            *pixel = if odd { second } else { first };
+        }
+    }
+}
+
+/// Draws a checkerboard of single red and blue pixels, red in the top left
+/// corner
+pub(super) fn chroma_checker(canvas: &mut [u32], width: usize) {
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = if (x ^ y) & 1 == 0 {
+                0xffff0000
+            } else {
+                0xff0000ff
+            };
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Draws concentric rings of `ring_width` pixels around the frame center,
+/// alternating between `color` and the existing canvas content and starting
+/// with `color` in the center. The rings are moved outwards by `phase`
+/// pixels, which repeats after two rings.
+pub(super) fn concentric(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    ring_width: u32,
+    phase: u32,
+    color: u32,
+) {
+    let ring = ring_width.max(1) as i64;
+    let phase = (phase as i64) % (2 * ring);
+
+    // Squared inner radii of all rings in doubled coordinates, so pixel
+    // centers are integers. Counting starts two rings inside the center, so
+    // the parity of every ring stays the same for all phases.
+    let max = ((width * width + height * height) as f64).sqrt() as i64 + 2;
+    let bounds: Vec<i64> = (0..)
+        .map(|k| phase - 2 * ring + k * ring)
+        .take_while(|&r| r <= max)
+        .map(|r| (2 * r.max(0)).pow(2))
+        .collect();
+
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        let dy = 2 * y as i64 + 1 - height as i64;
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let dx = 2 * x as i64 + 1 - width as i64;
+            let k = bounds.partition_point(|&b| b <= dx * dx + dy * dy);
+            if k & 1 == 1 {
+                *pixel = color;
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Square matrix code of 16 x 16 modules: a white quiet ring, a black finder
+// ring and 12 x 12 data modules inside. The data modules hold the payload
+// bytes row by row, most significant bit first, black for a set bit, and end
+// with alternating padding bits.
+const MODULES: usize = 16;
+const DATA_MODULES: usize = MODULES - 4;
+const PAYLOAD_LEN: usize = 17;
+const PADDING: u8 = 0b1010_1010;
+
+const BLACK: u32 = 0xff000000;
+const WHITE: u32 = 0xffffffff;
+
+/// Timestamp and frame number of a frame as carried by its matrix code
+///
+/// The code encodes both as big-endian `u64`s followed by a parity byte, the
+/// XOR of all others, so damaged reads can be rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameCode {
+    /// Buffer PTS in nanoseconds
+    pub time: u64,
+    /// Buffer offset
+    pub frame: u64,
+}
+
+impl FrameCode {
+    fn payload(&self) -> [u8; PAYLOAD_LEN] {
+        let mut payload = [0; PAYLOAD_LEN];
+        payload[..8].copy_from_slice(&self.time.to_be_bytes());
+        payload[8..16].copy_from_slice(&self.frame.to_be_bytes());
+        payload[16] = payload[..16].iter().fold(0, |parity, byte| parity ^ byte);
+        payload
+    }
+
+    // Whether the data module at data row and column is black
+    fn bit(payload: &[u8; PAYLOAD_LEN], row: usize, column: usize) -> bool {
+        let index = row * DATA_MODULES + column;
+        let byte = payload.get(index / 8).copied().unwrap_or(PADDING);
+        byte & (0x80 >> (index % 8)) != 0
+    }
+
+    /// Draws the code as a `size` x `size` square with its top left corner
+    /// at `position`, clipped at the canvas edges. Every module is
+    /// `size / 16` pixels large, at least one.
+    pub(super) fn draw(
+        &self,
+        canvas: &mut [u32],
+        width: usize,
+        height: usize,
+        position: (u32, u32),
+        size: u32,
+    ) {
+        let module = (size as usize / MODULES).max(1);
+        let payload = self.payload();
+        let (x0, y0) = (position.0 as usize, position.1 as usize);
+
+        for my in 0..MODULES {
+            for mx in 0..MODULES {
+                let ring = mx.min(my).min(MODULES - 1 - mx).min(MODULES - 1 - my);
+                let black = match ring {
+                    0 => false,
+                    1 => true,
+                    _ => Self::bit(&payload, my - 2, mx - 2),
+                };
+                let color = if black { BLACK } else { WHITE };
+
+                let x = (x0 + mx * module).min(width);
+                let end = (x + module).min(width);
+                for y in y0 + my * module..(y0 + (my + 1) * module).min(height) {
+                    canvas[y * width + x..y * width + end].fill(color);
+                }
+            }
+        }
+    }
+
+    /// Reads a code from 8 bit luma samples
+    ///
+    /// `luma` starts at the top left corner of the `size` x `size` square the
+    /// code was drawn into and has `stride` bytes per line. Every module is
+    /// sampled at its center and compared against the mean of the darkest
+    /// and brightest sample. Returns `None` if the finder or quiet ring is
+    /// damaged or the parity doesn't match.
+    pub fn decode(luma: &[u8], stride: usize, size: usize) -> Option<FrameCode> {
+        let module = (size / MODULES).max(1);
+        let sample = |mx: usize, my: usize| {
+            let (x, y) = (mx * module + module / 2, my * module + module / 2);
+            luma.get(y * stride + x).copied()
+        };
+
+        let mut samples = [[0u8; MODULES]; MODULES];
+        for (my, line) in samples.iter_mut().enumerate() {
+            for (mx, value) in line.iter_mut().enumerate() {
+                *value = sample(mx, my)?;
+            }
+        }
+        let min = samples.iter().flatten().min().copied()?;
+        let max = samples.iter().flatten().max().copied()?;
+        let threshold = (min as u16 + max as u16).div_ceil(2) as u8;
+        let black = |mx: usize, my: usize| samples[my][mx] < threshold;
+
+        let mut payload = [0u8; PAYLOAD_LEN];
+        for my in 0..MODULES {
+            for mx in 0..MODULES {
+                let ring = mx.min(my).min(MODULES - 1 - mx).min(MODULES - 1 - my);
+                match ring {
+                    0 if black(mx, my) => return None,
+                    1 if !black(mx, my) => return None,
+                    0 | 1 => (),
+                    _ => {
+                        let index = (my - 2) * DATA_MODULES + mx - 2;
+                        if index < PAYLOAD_LEN * 8 && black(mx, my) {
+                            payload[index / 8] |= 0x80 >> (index % 8);
+                        }
+                    }
+                }
+            }
+        }
+
+        let parity = payload[..16].iter().fold(0, |parity, byte| parity ^ byte);
+        if parity != payload[16] {
+            return None;
+        }
+
+        let mut time = [0; 8];
+        let mut frame = [0; 8];
+        time.copy_from_slice(&payload[..8]);
+        frame.copy_from_slice(&payload[8..16]);
+
+        Some(FrameCode {
+            time: u64::from_be_bytes(time),
+            frame: u64::from_be_bytes(frame),
+        })
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::gradient::lerp;
+
+/// Full frame colors of the color cycle pattern in order: white, black,
+/// red, green, blue, cyan, magenta and yellow
+pub(super) const CYCLE: [u32; 8] = [
+    0xffffffff, 0xff000000, 0xffff0000, 0xff00ff00, 0xff0000ff, 0xff00ffff, 0xffff00ff, 0xffffff00,
+];
+
+/// Fully saturated color of the color vectors pattern in frame
+/// `frame_index`, its hue advancing by `step` degrees per frame from red.
+/// If `quantize` is set the hue is rounded to the nearest of the six
+/// primaries and secondaries.
+pub fn vector_color(frame_index: u64, step: f64, quantize: bool) -> u32 {
+    let mut hue = (frame_index as f64 * step).rem_euclid(360.0);
+    if quantize {
+        hue = (hue / 60.0).round() % 6.0 * 60.0;
+    }
+
+    from_hsv(hue, 1.0, 1.0)
+}
+
+// Colors accepted by name in parse_color()
+const NAMED: [(&str, u32); 11] = [
+    ("white", 0xffffffff),
+    ("black", 0xff000000),
+    ("red", 0xffff0000),
+    ("green", 0xff00ff00),
+    ("blue", 0xff0000ff),
+    ("cyan", 0xff00ffff),
+    ("magenta", 0xffff00ff),
+    ("yellow", 0xffffff00),
+    ("gray", 0xff808080),
+    ("grey", 0xff808080),
+    ("transparent", 0x00000000),
+];
+
+/// Parses `#RRGGBB`, `#AARRGGBB` or a color name like `white` or
+/// `transparent` into a big-endian ARGB color
+pub fn parse_color(s: &str) -> Result<u32, String> {
+    let s = s.trim();
+    let hex = match s.strip_prefix('#') {
+        Some(hex) => hex,
+        None => {
+            return NAMED
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(s))
+                .map(|&(_, color)| color)
+                .ok_or_else(|| format!("unknown color {}", s))
+        }
+    };
+
+    // from_str_radix() would also take a sign
+    let color = Some(hex)
+        .filter(|hex| hex.bytes().all(|c| c.is_ascii_hexdigit()))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+    match (hex.len(), color) {
+        (6, Some(color)) => Ok(0xff000000 | color),
+        (8, Some(color)) => Ok(color),
+        _ => Err(format!(
+            "invalid color {}, expected #RRGGBB or #AARRGGBB",
+            s
+        )),
+    }
+}
+
+/// Composes `color` over `background` by the alpha of `color`, giving an
+/// opaque color for formats without an alpha channel
+pub fn flatten(color: u32, background: u32) -> u32 {
+    let a = color >> 24;
+    (0..3).fold(0xff000000, |flat, channel| {
+        let shift = channel * 8;
+        let src = color >> shift & 0xff;
+        let dst = background >> shift & 0xff;
+        let c = (src * a + dst * (255 - a) + 127) / 255;
+        flat | c << shift
+    })
+}
+
+/// Color of a fade from `from` to `to` and back, taking `period`
+/// nanoseconds each way, at `time` nanoseconds. Every channel, alpha
+/// included, is interpolated linearly. `from` if `period` is 0.
+pub fn fade_color(from: u32, to: u32, time: u64, period: u64) -> u32 {
+    if period == 0 {
+        return from;
+    }
+
+    let cycle = period.saturating_mul(2);
+    let pos = time % cycle;
+    let pos = if pos > period { cycle - pos } else { pos };
+    // Scaled down so the position fits into lerp(), still far finer than
+    // the 8 bit channels
+    let pos = (pos as u128 * 0x10000 / period as u128) as u32;
+    lerp(from, to, pos, 0x10000)
+}
+
+/// Splits an ARGB color into hue in degrees, saturation and value (0-1)
+pub(super) fn to_hsv(color: u32) -> (f64, f64, f64) {
+    let [_, r, g, b] = color.to_be_bytes();
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+
+    let hue = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+    (hue, saturation, max)
+}
+
+/// Opaque ARGB color from hue in degrees, saturation and value (0-1)
+pub(super) fn from_hsv(hue: f64, saturation: f64, value: f64) -> u32 {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let (saturation, value) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let channel = |c: f64| ((c + m) * 255.0).round() as u32;
+
+    0xff000000 | channel(r) << 16 | channel(g) << 8 | channel(b)
+}
+
+/// Rotates the hue of an ARGB color by `degrees`, keeping its alpha,
+/// saturation and value
+pub(super) fn rotate_hue(color: u32, degrees: f64) -> u32 {
+    let (hue, saturation, value) = to_hsv(color);
+    (color & 0xff000000) | (from_hsv(hue + degrees, saturation, value) & 0xffffff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_color("#ff8000"), Ok(0xffff8000));
+        assert_eq!(parse_color("#80FF8000"), Ok(0x80ff8000));
+        assert_eq!(parse_color(" Red "), Ok(0xffff0000));
+        assert_eq!(parse_color("transparent"), Ok(0x00000000));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for s in &["", "#", "ff8000", "#ff800", "#+f8000", "#ff8000000", "nope"] {
+            assert!(parse_color(s).is_err(), "{:?}", s);
+        }
+    }
+}
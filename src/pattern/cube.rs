@@ -0,0 +1,152 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Corners of the cube, and the pairs of them its edges connect
+const CORNERS: [[f64; 3]; 8] = [
+    [-1.0, -1.0, -1.0],
+    [1.0, -1.0, -1.0],
+    [1.0, 1.0, -1.0],
+    [-1.0, 1.0, -1.0],
+    [-1.0, -1.0, 1.0],
+    [1.0, -1.0, 1.0],
+    [1.0, 1.0, 1.0],
+    [-1.0, 1.0, 1.0],
+];
+const EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (1, 2),
+    (2, 3),
+    (3, 0),
+    (4, 5),
+    (5, 6),
+    (6, 7),
+    (7, 4),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+// Distance of the viewer from the center of the cube, in half edges
+const DISTANCE: f64 = 4.0;
+
+/// Draws the edges of a cube in `color`, rotated by `rotation` degrees
+/// about the vertical and by half of it about the horizontal axis. The cube
+/// is centered and sized relative to the smaller side of the frame;
+/// `pixel_aspect` is the width of a pixel relative to its height, so the
+/// cube keeps its proportions on non-square pixels.
+pub(super) fn cube(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    rotation: f64,
+    (par_n, par_d): (u32, u32),
+    color: u32,
+) {
+    let pixel_aspect = match (par_n, par_d) {
+        (n, d) if n > 0 && d > 0 => n as f64 / d as f64,
+        _ => 1.0,
+    };
+    // Half the smaller side if the nearest corner is right in front of the
+    // viewer
+    let scale = (width as f64 * pixel_aspect).min(height as f64) / 2.0 * (DISTANCE - 3f64.sqrt())
+        / (3f64.sqrt() * DISTANCE);
+    let (sin_y, cos_y) = rotation.to_radians().sin_cos();
+    let (sin_x, cos_x) = (rotation / 2.0).to_radians().sin_cos();
+
+    let projected = CORNERS.map(|[x, y, z]| {
+        let (x, z) = (x * cos_y + z * sin_y, z * cos_y - x * sin_y);
+        let (y, z) = (y * cos_x - z * sin_x, z * cos_x + y * sin_x);
+        let f = scale * DISTANCE / (DISTANCE + z);
+        (
+            width as f64 / 2.0 + x * f / pixel_aspect,
+            height as f64 / 2.0 - y * f,
+        )
+    });
+
+    for &(a, b) in EDGES.iter() {
+        if let Some((from, to)) = clip(projected[a], projected[b], width, height) {
+            line(canvas, width, from, to, color);
+        }
+    }
+}
+
+// Cuts the line from `a` to `b` to the part inside the frame (Liang-Barsky)
+// and rounds it to pixels, `None` if it lies completely outside
+fn clip(
+    (x0, y0): (f64, f64),
+    (x1, y1): (f64, f64),
+    width: usize,
+    height: usize,
+) -> Option<((usize, usize), (usize, usize))> {
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let (max_x, max_y) = (width as f64 - 0.5, height as f64 - 0.5);
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+    for &(p, q) in [
+        (-dx, x0 + 0.5),
+        (dx, max_x - x0),
+        (-dy, y0 + 0.5),
+        (dy, max_y - y0),
+    ]
+    .iter()
+    {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+
+    let pixel = |t: f64| {
+        let x = (x0 + t * dx).round().clamp(0.0, width as f64 - 1.0);
+        let y = (y0 + t * dy).round().clamp(0.0, height as f64 - 1.0);
+        (x as usize, y as usize)
+    };
+    Some((pixel(t0), pixel(t1)))
+}
+
+// Bresenham line between two pixels inside the canvas
+fn line(
+    canvas: &mut [u32],
+    width: usize,
+    (x0, y0): (usize, usize),
+    (x1, y1): (usize, usize),
+    color: u32,
+) {
+    let (mut x, mut y) = (x0 as i64, y0 as i64);
+    let (dx, dy) = ((x1 as i64 - x).abs(), -(y1 as i64 - y).abs());
+    let (step_x, step_y) = (
+        if x < x1 as i64 { 1 } else { -1 },
+        if y < y1 as i64 { 1 } else { -1 },
+    );
+    let mut error = dx + dy;
+
+    loop {
+        canvas[y as usize * width + x as usize] = color;
+        if (x, y) == (x1 as i64, y1 as i64) {
+            break;
+        }
+        let e2 = 2 * error;
+        if e2 >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if e2 <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Whether the frame at `time` nanoseconds is the flash frame of the A/V
+/// sync pattern, the first frame at or after a multiple of `period`
+/// nanoseconds
+///
+/// Frame timestamps are expected to be the frame number scaled by the
+/// `framerate` and rounded down, as the element produces them. With a
+/// variable framerate only frames exactly at a multiple flash.
+pub fn is_flash_frame(time: u64, (numer, denom): (u32, u32), period: u64) -> bool {
+    if period == 0 {
+        return false;
+    }
+    let boundary = (time as u128).div_ceil(period as u128) * period as u128;
+    if boundary == time as u128 {
+        return true;
+    }
+    if numer == 0 || denom == 0 {
+        return false;
+    }
+
+    // Recover the frame number to get the exact start of the next frame
+    let frame_ns = denom as u128 * 1_000_000_000;
+    let frame = (time as u128 * numer as u128).div_ceil(frame_ns);
+    let next = (frame + 1) * frame_ns / numer as u128;
+
+    boundary < next
+}
+
+/// Draws the white flash rectangle, half the frame size and centered, on
+/// black
+pub(super) fn flash(canvas: &mut [u32], width: usize, height: usize, on: bool) {
+    canvas.fill(0xff000000);
+    if !on {
+        return;
+    }
+
+    let (left, top) = (width / 4, height / 4);
+    for row in canvas[top * width..(height - top) * width].chunks_exact_mut(width) {
+        row[left..width - left].fill(0xffffffff);
+    }
+}
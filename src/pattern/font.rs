@@ -0,0 +1,311 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Built-in 8x16 bitmap font for burned-in labels. Every glyph is one byte
+// per line, the most significant bit is the leftmost pixel.
+
+const GLYPH_WIDTH: usize = 8;
+const GLYPH_HEIGHT: usize = 16;
+
+#[rustfmt::skip]
+const DIGITS: [[u8; GLYPH_HEIGHT]; 10] = [
+    // 0
+    [
+        0b00000000,
+        0b00000000,
+        0b00111100,
+        0b01100110,
+        0b11000011,
+        0b11000111,
+        0b11001111,
+        0b11011011,
+        0b11110011,
+        0b11100011,
+        0b11000011,
+        0b11000011,
+        0b01100110,
+        0b00111100,
+        0b00000000,
+        0b00000000,
+    ],
+    // 1
+    [
+        0b00000000,
+        0b00000000,
+        0b00011000,
+        0b00111000,
+        0b01111000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b01111110,
+        0b00000000,
+        0b00000000,
+    ],
+    // 2
+    [
+        0b00000000,
+        0b00000000,
+        0b00111100,
+        0b01100110,
+        0b11000011,
+        0b00000011,
+        0b00000110,
+        0b00001100,
+        0b00011000,
+        0b00110000,
+        0b01100000,
+        0b11000000,
+        0b11000011,
+        0b11111111,
+        0b00000000,
+        0b00000000,
+    ],
+    // 3
+    [
+        0b00000000,
+        0b00000000,
+        0b01111100,
+        0b11000110,
+        0b00000011,
+        0b00000011,
+        0b00000110,
+        0b00111100,
+        0b00000110,
+        0b00000011,
+        0b00000011,
+        0b00000011,
+        0b11000110,
+        0b01111100,
+        0b00000000,
+        0b00000000,
+    ],
+    // 4
+    [
+        0b00000000,
+        0b00000000,
+        0b00000110,
+        0b00001110,
+        0b00011110,
+        0b00110110,
+        0b01100110,
+        0b11000110,
+        0b11000110,
+        0b11111111,
+        0b00000110,
+        0b00000110,
+        0b00000110,
+        0b00001111,
+        0b00000000,
+        0b00000000,
+    ],
+    // 5
+    [
+        0b00000000,
+        0b00000000,
+        0b11111110,
+        0b11000000,
+        0b11000000,
+        0b11000000,
+        0b11111100,
+        0b00000110,
+        0b00000011,
+        0b00000011,
+        0b00000011,
+        0b11000011,
+        0b01100110,
+        0b00111100,
+        0b00000000,
+        0b00000000,
+    ],
+    // 6
+    [
+        0b00000000,
+        0b00000000,
+        0b00011100,
+        0b00110000,
+        0b01100000,
+        0b11000000,
+        0b11111100,
+        0b11100110,
+        0b11000011,
+        0b11000011,
+        0b11000011,
+        0b11000011,
+        0b01100110,
+        0b00111100,
+        0b00000000,
+        0b00000000,
+    ],
+    // 7
+    [
+        0b00000000,
+        0b00000000,
+        0b11111111,
+        0b11000011,
+        0b00000011,
+        0b00000110,
+        0b00001100,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00011000,
+        0b00000000,
+        0b00000000,
+    ],
+    // 8
+    [
+        0b00000000,
+        0b00000000,
+        0b00111100,
+        0b01100110,
+        0b11000011,
+        0b11000011,
+        0b01100110,
+        0b00111100,
+        0b01100110,
+        0b11000011,
+        0b11000011,
+        0b11000011,
+        0b01100110,
+        0b00111100,
+        0b00000000,
+        0b00000000,
+    ],
+    // 9
+    [
+        0b00000000,
+        0b00000000,
+        0b00111100,
+        0b01100110,
+        0b11000011,
+        0b11000011,
+        0b11000011,
+        0b01100111,
+        0b00111111,
+        0b00000011,
+        0b00000011,
+        0b00000110,
+        0b00001100,
+        0b01110000,
+        0b00000000,
+        0b00000000,
+    ],
+];
+
+#[rustfmt::skip]
+const COLON: [u8; GLYPH_HEIGHT] = [
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00011000,
+    0b00011000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00011000,
+    0b00011000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+    0b00000000,
+];
+
+/// Corner of the frame a label is placed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Where a label is placed in the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Anchor {
+    Corner(Corner),
+    /// Horizontally centered at the top edge
+    Top,
+}
+
+fn glyph(c: char) -> Option<&'static [u8; GLYPH_HEIGHT]> {
+    match c {
+        '0'..='9' => Some(&DIGITS[c as usize - '0' as usize]),
+        ':' => Some(&COLON),
+        _ => None,
+    }
+}
+
+/// Integer scale factor of the font for a frame `height` lines high, so
+/// labels stay readable in large frames
+pub(super) fn scale(height: usize) -> usize {
+    (height / 240).max(1)
+}
+
+/// Draws `text` in `color` on a `background` box at `anchor` in the
+/// canvas. The box leaves one scaled font pixel around the text and is
+/// clipped at the frame edges. Characters without a glyph are left blank.
+pub(super) fn label(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    anchor: Anchor,
+    text: &str,
+    color: u32,
+    background: u32,
+) {
+    let scale = scale(height);
+    let box_width = ((text.chars().count() * GLYPH_WIDTH + 2) * scale).min(width);
+    let box_height = ((GLYPH_HEIGHT + 2) * scale).min(height);
+    let (x0, y0) = match anchor {
+        Anchor::Corner(Corner::TopLeft) => (0, 0),
+        Anchor::Corner(Corner::TopRight) => (width - box_width, 0),
+        Anchor::Corner(Corner::BottomLeft) => (0, height - box_height),
+        Anchor::Corner(Corner::BottomRight) => (width - box_width, height - box_height),
+        Anchor::Top => ((width - box_width) / 2, 0),
+    };
+
+    for row in canvas[y0 * width..(y0 + box_height) * width].chunks_exact_mut(width) {
+        row[x0..x0 + box_width].fill(background);
+    }
+
+    for (i, c) in text.chars().enumerate() {
+        let glyph = match glyph(c) {
+            Some(glyph) => glyph,
+            None => continue,
+        };
+        let left = x0 + (1 + i * GLYPH_WIDTH) * scale;
+        for (line, bits) in glyph.iter().enumerate() {
+            for bit in 0..GLYPH_WIDTH {
+                if bits & (0x80 >> bit) == 0 {
+                    continue;
+                }
+                let (x, y) = (left + bit * scale, y0 + (1 + line) * scale);
+                for y in y..(y + scale).min(y0 + box_height) {
+                    let row = &mut canvas[y * width..(y + 1) * width];
+                    let end = (x + scale).min(x0 + box_width);
+                    if x < end {
+                        row[x..end].fill(color);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Interpolates every channel of two ARGB colors, `from` at `pos` 0 and `to`
+/// at `pos` `max`, rounding to the nearest value
+pub(super) fn lerp(from: u32, to: u32, pos: u32, max: u32) -> u32 {
+    if max == 0 {
+        return from;
+    }
+
+    let (pos, max) = (pos.min(max) as u64, max as u64);
+    (0..4).fold(0, |color, channel| {
+        let shift = channel * 8;
+        let a = (from >> shift & 0xff) as u64;
+        let b = (to >> shift & 0xff) as u64;
+        let c = (a * (max - pos) + b * pos + max / 2) / max;
+        color | (c as u32) << shift
+    })
+}
+
+/// Draws a gradient from `from` in the first column to `to` in the last one,
+/// moved right by `offset` columns and wrapping around
+pub(super) fn horizontal(canvas: &mut [u32], width: usize, offset: u32, from: u32, to: u32) {
+    if width == 0 {
+        return;
+    }
+
+    let offset = offset as usize % width;
+    let max = (width - 1) as u32;
+    let mut row = Vec::with_capacity(width);
+    row.extend((0..width).map(|x| lerp(from, to, ((x + width - offset) % width) as u32, max)));
+
+    for line in canvas.chunks_exact_mut(width) {
+        line.copy_from_slice(&row);
+    }
+}
+
+/// Draws a gradient from `from` in the first line to `to` in the last one,
+/// moved down by `offset` lines and wrapping around
+pub(super) fn vertical(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    offset: u32,
+    from: u32,
+    to: u32,
+) {
+    if height == 0 {
+        return;
+    }
+
+    let offset = offset as usize % height;
+    let max = (height - 1) as u32;
+    for (y, line) in canvas.chunks_exact_mut(width).enumerate() {
+        line.fill(lerp(from, to, ((y + height - offset) % height) as u32, max));
+    }
+}
+
+// Fixed point precision of positions on an angled gradient
+const SCALE: f64 = 65536.0;
+
+// Direction of a gradient `angle` degrees clockwise from pointing right as
+// (cos, sin), exact for multiples of 90 degrees
+fn axis(angle: f64) -> (f64, f64) {
+    let angle = angle.rem_euclid(360.0);
+    if angle == 0.0 {
+        (1.0, 0.0)
+    } else if angle == 90.0 {
+        (0.0, 1.0)
+    } else if angle == 180.0 {
+        (-1.0, 0.0)
+    } else if angle == 270.0 {
+        (0.0, -1.0)
+    } else {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        (cos, sin)
+    }
+}
+
+// Projections of all columns and lines onto the gradient axis, with the
+// smallest sum of both being 0, and the largest sum
+fn projections(width: usize, height: usize, angle: f64) -> (Vec<f64>, Vec<f64>, f64) {
+    let (cos, sin) = axis(angle);
+    let mut columns: Vec<f64> = (0..width).map(|x| x as f64 * cos).collect();
+    let mut lines: Vec<f64> = (0..height).map(|y| y as f64 * sin).collect();
+
+    for values in [&mut columns, &mut lines] {
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        values.iter_mut().for_each(|v| *v -= min);
+    }
+    let max = |values: &[f64]| values.iter().copied().fold(0.0, f64::max);
+    let range = max(&columns) + max(&lines);
+
+    (columns, lines, range)
+}
+
+/// Distance in pixels after which an angled gradient repeats while
+/// scrolling
+pub(super) fn angled_period(width: usize, height: usize, angle: f64) -> u32 {
+    let (_, _, range) = projections(width, height, angle);
+    (range + 1.0).ceil() as u32
+}
+
+/// Draws a gradient from `from` to `to` along the direction `angle` degrees
+/// clockwise from pointing right, moved along it by `offset` pixels and
+/// wrapping around. For multiples of 90 degrees this is the same as the
+/// horizontal and vertical gradients.
+pub(super) fn angled(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    angle: f64,
+    offset: u32,
+    from: u32,
+    to: u32,
+) {
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let (columns, lines, range) = projections(width, height, angle);
+    let period = (range + 1.0).ceil();
+    let offset = offset as f64 % period;
+    let max = (range * SCALE).round() as u32;
+
+    for (line, &line_pos) in canvas.chunks_exact_mut(width).zip(lines.iter()) {
+        for (pixel, &column_pos) in line.iter_mut().zip(columns.iter()) {
+            let pos = (line_pos + column_pos - offset).rem_euclid(period);
+            *pixel = lerp(from, to, (pos * SCALE).round() as u32, max);
+        }
+    }
+}
+
+/// Draws `steps` equally wide gray steps from black to white, with step `i`
+/// at the code value `round(i * 255 / (steps - 1))`. The steps are columns
+/// from left to right, or lines from top to bottom if `rows` is set.
+pub(super) fn staircase(canvas: &mut [u32], width: usize, height: usize, steps: u32, rows: bool) {
+    let steps = steps.max(2) as usize;
+    let step = |pos: usize, length: usize| {
+        let i = pos * steps / length;
+        lerp(0xff000000, 0xffffffff, i as u32, steps as u32 - 1)
+    };
+
+    if rows {
+        for (y, line) in canvas.chunks_exact_mut(width).enumerate() {
+            line.fill(step(y, height));
+        }
+    } else {
+        let row: Vec<u32> = (0..width).map(|x| step(x, width)).collect();
+        for line in canvas.chunks_exact_mut(width) {
+            line.copy_from_slice(&row);
+        }
+    }
+}
+
+// 2x2 Bayer matrix, thresholds in quarters
+const BAYER: [[f64; 2]; 2] = [[0.0, 2.0], [3.0, 1.0]];
+
+/// Draws a gray ramp from black in the first column to white in the last
+/// one. The level is computed in floating point and, if `dither` is set and
+/// the canvas is wider than the 256 code values, quantized with ordered 2x2
+/// dithering instead of rounding.
+pub(super) fn ramp(canvas: &mut [u32], width: usize, dither: bool) {
+    let max = width.saturating_sub(1).max(1) as f64;
+    let dither = dither && width > 256;
+
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let level = x as f64 * 255.0 / max;
+            let level = if dither {
+                (level + (BAYER[y & 1][x & 1] + 0.5) / 4.0).floor()
+            } else {
+                level.round()
+            };
+            *pixel = 0xff000000 | (level.min(255.0) as u32 * 0x010101);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(width: usize, offset: u32, from: u32, to: u32) -> Vec<u32> {
+        let mut canvas = vec![0; width * 2];
+        horizontal(&mut canvas, width, offset, from, to);
+        assert_eq!(canvas[..width], canvas[width..]);
+        canvas.truncate(width);
+        canvas
+    }
+
+    #[test]
+    fn horizontal_first_middle_last() {
+        for &(width, from, to, middle) in &[
+            (101, 0xff000000, 0xffffffff, 0xff808080),
+            (5, 0xff102030, 0xff3060f0, 0xff204090),
+            (5, 0xff3060f0, 0xff102030, 0xff204090),
+        ] {
+            let row = row(width, 0, from, to);
+            assert_eq!(
+                (row[0], row[width / 2], row[width - 1]),
+                (from, middle, to),
+                "{:08x} to {:08x} over {}",
+                from,
+                to,
+                width
+            );
+        }
+    }
+
+    #[test]
+    fn horizontal_monotonic() {
+        for &width in &[3, 256, 1000] {
+            let row = row(width, 0, 0xff000000, 0xffffffff);
+            assert!(row.windows(2).all(|pair| pair[0] <= pair[1]), "{}", width);
+        }
+    }
+
+    #[test]
+    fn horizontal_scrolls_with_wraparound() {
+        let row = row(5, 6, 0xff102030, 0xff3060f0);
+        assert_eq!(
+            (row[0], row[1], row[3]),
+            (0xff3060f0, 0xff102030, 0xff204090)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::Rng;
+
+// Precomputed noise planes, each frame uses one of them at a random offset
+const PLANES: usize = 4;
+// Fixed point scale of the noise values, their standard deviation is 1.0
+const ONE: i32 = 256;
+
+/// Gray film grain noise at a reduced resolution
+#[derive(Debug, Clone)]
+pub(super) struct Grain {
+    seed: u64,
+    width: usize,
+    height: usize,
+    planes: Vec<Vec<i16>>,
+}
+
+impl Grain {
+    /// Noise planes for a `width` x `height` frame with grain `size` pixels
+    /// wide
+    pub(super) fn new(seed: u64, width: usize, height: usize, size: u32) -> Self {
+        let size = size.max(1) as usize;
+        let (width, height) = (reduced(width, size), reduced(height, size));
+        let mut rng = Rng::new(seed);
+
+        // Sum of four uniform bytes, close enough to a gaussian. Its
+        // standard deviation is 255 * sqrt(4 / 12).
+        let planes = (0..PLANES)
+            .map(|_| {
+                (0..width * height)
+                    .map(|_| {
+                        let sum: i32 = rng.next_u32().to_le_bytes().iter().map(|&b| b as i32).sum();
+                        ((sum - 510) * ONE * 100 / 14722) as i16
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Grain {
+            seed,
+            width,
+            height,
+            planes,
+        }
+    }
+
+    /// Whether the planes were built by [`Grain::new`] with these arguments
+    pub(super) fn matches(&self, seed: u64, width: usize, height: usize, size: u32) -> bool {
+        let size = size.max(1) as usize;
+        self.seed == seed
+            && self.width == reduced(width, size)
+            && self.height == reduced(height, size)
+    }
+
+    /// Fills the canvas with `color` plus grain with a standard deviation
+    /// of `intensity` levels, different for every frame
+    pub(super) fn draw(
+        &self,
+        canvas: &mut [u32],
+        width: usize,
+        frame_index: u64,
+        size: u32,
+        intensity: u32,
+        color: u32,
+    ) {
+        let size = size.max(1) as usize;
+        let mut rng = Rng::new(self.seed ^ frame_index.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let plane = &self.planes[rng.below(PLANES as u64) as usize];
+        let x0 = rng.below(self.width as u64) as usize;
+        let y0 = rng.below(self.height as u64) as usize;
+
+        let [a, r, g, b] = color.to_be_bytes();
+        let intensity = intensity.min(255) as i32;
+        let channel = |c: u8, n: i32| (c as i32 + n).clamp(0, 255) as u32;
+        for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+            let line = (y / size + y0) % self.height;
+            let values = &plane[line * self.width..(line + 1) * self.width];
+            let mut x = x0;
+            for pixels in row.chunks_mut(size) {
+                let n = values[x] as i32 * intensity / ONE;
+                let pixel =
+                    (a as u32) << 24 | channel(r, n) << 16 | channel(g, n) << 8 | channel(b, n);
+                pixels.fill(pixel);
+                x += 1;
+                if x == self.width {
+                    x = 0;
+                }
+            }
+        }
+    }
+}
+
+// Number of grains along an axis `length` pixels long
+fn reduced(length: usize, size: usize) -> usize {
+    length.div_ceil(size).max(1)
+}
@@ -0,0 +1,41 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Draws a crosshatch of horizontal and vertical `line_width` pixel lines in
+/// `color`, one every `spacing` pixels starting at pixel 0 on both axes.
+/// Lines at least as wide as the spacing cover the whole canvas.
+pub(super) fn crosshatch(
+    canvas: &mut [u32],
+    width: usize,
+    spacing: u32,
+    line_width: u32,
+    color: u32,
+) {
+    let spacing = spacing.max(1) as usize;
+    let line_width = line_width as usize;
+    if line_width >= spacing {
+        canvas.fill(color);
+        return;
+    }
+    if line_width == 0 {
+        return;
+    }
+
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        if y % spacing < line_width {
+            row.fill(color);
+            continue;
+        }
+        for line in row.chunks_mut(spacing) {
+            let end = line_width.min(line.len());
+            line[..end].fill(color);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Temporal order of the two fields of an interlaced frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldOrder {
+    /// The field on the even lines, starting with line 0, is shown first
+    TopFirst,
+    BottomFirst,
+}
+
+const BLOCK: u32 = 0xff808080;
+
+/// Draws the field identification pattern: even lines in `even`, odd lines
+/// in `odd` and a gray block moving down one line per field, wrapping
+/// around at the bottom.
+///
+/// For interlaced frames every field only gets the lines of the block at
+/// its own point in time, the first field in `field_order` at field
+/// `2 * frame_index`, the second one a line further down. Deinterlacing
+/// with the wrong field order moves the block back and forth. Progressive
+/// frames show the block as the first field would.
+pub(super) fn field_identification(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    frame_index: u64,
+    field_order: Option<FieldOrder>,
+    (even, odd): (u32, u32),
+) {
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        row.fill(if y & 1 == 0 { even } else { odd });
+    }
+
+    let (block_width, block_height) = ((width / 8).max(1), (height / 8).max(1));
+    let left = (width - block_width) / 2;
+    let range = (height - block_height + 1) as u64;
+    let top = |field: u64| (field % range) as usize;
+
+    let field = 2 * frame_index;
+    let fields = match field_order {
+        None => vec![(top(field), None)],
+        Some(FieldOrder::TopFirst) => vec![(top(field), Some(0)), (top(field + 1), Some(1))],
+        Some(FieldOrder::BottomFirst) => vec![(top(field), Some(1)), (top(field + 1), Some(0))],
+    };
+
+    for (top, parity) in fields {
+        for y in top..top + block_height {
+            if parity.unwrap_or(y & 1) == y & 1 {
+                canvas[y * width + left..y * width + left + block_width].fill(BLOCK);
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Line width in pixels of every band, from left to right
+const WIDTHS: [usize; 8] = [8, 7, 6, 5, 4, 3, 2, 1];
+
+/// Draws bands of line pairs getting finer from left to right, down to
+/// 1 pixel lines in the last band. The lines are vertical in the upper half
+/// and horizontal in the lower one.
+///
+/// Every band covers roughly an eighth of the width, shortened to a whole
+/// number of line pairs so that no band ends with a partial line. Lines
+/// start with `first`, pixels after the last pair of a band are `second`.
+pub(super) fn line_pairs(canvas: &mut [u32], width: usize, height: usize, first: u32, second: u32) {
+    let middle = height / 2;
+    let mut bands = Vec::with_capacity(WIDTHS.len());
+    let mut start = 0;
+    for (i, &line) in WIDTHS.iter().enumerate() {
+        let nominal = (i + 1) * width / WIDTHS.len();
+        let pairs = (nominal.saturating_sub(start) / (2 * line)).max(1);
+        let end = (start + pairs * 2 * line).min(width);
+        bands.push((start, end, line));
+        start = end;
+    }
+
+    let mut vertical = vec![second; width];
+    for &(start, end, line) in &bands {
+        for (x, pixel) in vertical[start..end].iter_mut().enumerate() {
+            if (x / line) & 1 == 0 {
+                *pixel = first;
+            }
+        }
+    }
+
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        if y < middle {
+            row.copy_from_slice(&vertical);
+            continue;
+        }
+
+        row.fill(second);
+        for &(start, end, line) in &bands {
+            if ((y - middle) / line) & 1 == 0 {
+                row[start..end].fill(first);
+            }
+        }
+    }
+}
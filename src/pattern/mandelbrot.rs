@@ -0,0 +1,81 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::gradient::lerp;
+
+// Point in the seahorse valley the view zooms into
+const CENTER: (f64, f64) = (-0.743_643_887_037_151, 0.131_825_904_205_330);
+// Width of the unzoomed view in the complex plane
+const VIEW_WIDTH: f64 = 3.0;
+// The zoom doubles every 8 seconds in 32 steps, so frames between two steps
+// are the same
+const STEPS_PER_DOUBLING: u64 = 32;
+const STEP_DURATION: u64 = 8_000_000_000 / STEPS_PER_DOUBLING;
+// Zooming starts over before doubles run out of precision
+const MAX_DOUBLINGS: u64 = 36;
+
+/// Zoom level at stream `time` in nanoseconds
+pub(super) fn zoom_step(time: u64) -> u64 {
+    time / STEP_DURATION % (MAX_DOUBLINGS * STEPS_PER_DOUBLING)
+}
+
+/// Iterations until every pixel of a `width` x `height` view at zoom level
+/// `step` escapes, `iterations` for pixels inside the set
+pub(super) fn escape_counts(width: usize, height: usize, step: u64, iterations: u32) -> Vec<u32> {
+    let zoom = (step as f64 / STEPS_PER_DOUBLING as f64).exp2();
+    let scale = VIEW_WIDTH / zoom / width.max(1) as f64;
+
+    let mut counts = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let ci = CENTER.1 + (y as f64 - height as f64 / 2.0) * scale;
+        for x in 0..width {
+            let cr = CENTER.0 + (x as f64 - width as f64 / 2.0) * scale;
+            counts.push(escape(cr, ci, iterations));
+        }
+    }
+    counts
+}
+
+fn escape(cr: f64, ci: f64, iterations: u32) -> u32 {
+    // Inside the main cardioid or the period 2 bulb, never escapes
+    let q = (cr - 0.25) * (cr - 0.25) + ci * ci;
+    if q * (q + (cr - 0.25)) <= 0.25 * ci * ci || (cr + 1.0) * (cr + 1.0) + ci * ci <= 0.0625 {
+        return iterations;
+    }
+
+    let (mut zr, mut zi) = (0.0f64, 0.0f64);
+    for n in 0..iterations {
+        let (zr2, zi2) = (zr * zr, zi * zi);
+        if zr2 + zi2 > 4.0 {
+            return n;
+        }
+        zi = 2.0 * zr * zi + ci;
+        zr = zr2 - zi2 + cr;
+    }
+    iterations
+}
+
+/// Maps escape counts to colors, `from` for pixels escaping right away or
+/// inside the set and up to `to` for the ones escaping last. The square
+/// root of the count is used, so the few iterations most pixels need are
+/// still told apart.
+pub(super) fn colorize(canvas: &mut [u32], counts: &[u32], iterations: u32, from: u32, to: u32) {
+    let palette: Vec<u32> = (0..iterations)
+        .map(|count| {
+            let pos = ((count as f64 / iterations as f64).sqrt() * 255.0).round() as u32;
+            lerp(from, to, pos, 255)
+        })
+        .chain(std::iter::once(from))
+        .collect();
+
+    for (pixel, &count) in canvas.iter_mut().zip(counts) {
+        *pixel = palette[count.min(iterations) as usize];
+    }
+}
@@ -0,0 +1,1874 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Pattern rendering independent of GStreamer.
+//!
+//! This module contains everything needed to produce the frames of the
+//! test pattern source and the test pattern overlay. It only works on plain
+//! byte slices, so it can be used without initializing GStreamer, e.g. to
+//! recreate the expected content of a frame:
+//!
+//! ```ignore
+//! use gstrstestpattern::pattern::{Format, FrameLayout, PatternParams, Renderer};
+//!
+//! let layout = FrameLayout::new(Format::Bgrx, 320, 240);
+//! let mut out = vec![0; layout.size()];
+//! Renderer::new().render(0, &PatternParams::default(), &layout, &mut out);
+//! ```
+//!
+//! Rendering happens in two steps: the pattern is drawn into an ARGB canvas
+//! the size of the frame, which is then converted into the output format.
+
+use std::mem;
+use std::sync::Arc;
+
+mod balance;
+mod ball;
+mod bars;
+mod checkers;
+mod circles;
+mod code;
+mod color;
+mod cube;
+mod flash;
+mod font;
+mod gradient;
+mod grain;
+mod grid;
+mod interlace;
+mod linepairs;
+mod mandelbrot;
+mod noise;
+mod objects;
+mod picture;
+mod pixel;
+mod plasma;
+mod rain;
+mod rng;
+mod safearea;
+mod spiral;
+mod star;
+mod stripe;
+mod swatches;
+mod testcard;
+mod text;
+mod timecode;
+mod verify;
+mod zoneplate;
+
+pub use balance::ColorBalance;
+pub use ball::BouncingBall;
+pub use bars::Orientation;
+pub use code::FrameCode;
+pub use color::{fade_color, flatten, parse_color, vector_color};
+pub use flash::is_flash_frame;
+pub use font::Corner;
+pub use interlace::FieldOrder;
+pub use objects::{Motion, Object, Shape};
+pub use picture::Image;
+pub use pixel::{moving_edge, moving_pixel};
+pub use rain::Particle;
+pub use rng::Rng;
+pub use stripe::decode_timestamp_stripe;
+pub use swatches::COLOR_CHECKER;
+pub use timecode::timecode;
+pub use verify::{frame_digest, max_difference};
+pub use zoneplate::ZonePlate;
+
+/// Memory layouts the renderer knows how to write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One plane, 4 bytes per pixel in B, G, R, x order
+    Bgrx,
+    /// One plane, 4 bytes per pixel in B, G, R, A order, with the alpha not
+    /// premultiplied
+    Bgra,
+    /// 8 bit luma plane followed by an interleaved CbCr plane,
+    /// subsampled by 2 in both directions
+    Nv12,
+}
+
+impl Format {
+    /// Number of planes of a frame in this format
+    pub fn n_planes(self) -> usize {
+        match self {
+            Format::Bgrx | Format::Bgra => 1,
+            Format::Nv12 => 2,
+        }
+    }
+}
+
+/// Position of a single plane inside a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaneLayout {
+    /// Byte offset of the first line from the start of the frame
+    pub offset: usize,
+    /// Bytes from the start of one line to the start of the next
+    pub stride: usize,
+}
+
+/// Geometry and memory layout of a frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameLayout {
+    pub format: Format,
+    pub width: u32,
+    pub height: u32,
+    /// One entry per plane of `format`, in ascending offset order
+    pub planes: Vec<PlaneLayout>,
+}
+
+impl FrameLayout {
+    /// Creates the layout GStreamer uses by default for the given format,
+    /// with every stride padded to a multiple of 4 bytes.
+    pub fn new(format: Format, width: u32, height: u32) -> Self {
+        let mut layout = FrameLayout {
+            format,
+            width,
+            height,
+            planes: Vec::with_capacity(format.n_planes()),
+        };
+
+        let mut offset = 0;
+        for plane in 0..format.n_planes() {
+            let stride = (layout.row_size(plane) + 3) & !3;
+            layout.planes.push(PlaneLayout { offset, stride });
+            offset += stride * layout.plane_height(plane);
+        }
+
+        layout
+    }
+
+    /// Number of lines of a plane
+    pub fn plane_height(&self, plane: usize) -> usize {
+        match (self.format, plane) {
+            (Format::Nv12, 1) => (self.height as usize).div_ceil(2),
+            _ => self.height as usize,
+        }
+    }
+
+    /// Number of bytes of a plane line holding pixel data, excluding any
+    /// stride padding
+    pub fn row_size(&self, plane: usize) -> usize {
+        let width = self.width as usize;
+        match (self.format, plane) {
+            (Format::Bgrx, _) | (Format::Bgra, _) => width * 4,
+            (Format::Nv12, 0) => width,
+            (Format::Nv12, _) => width.div_ceil(2) * 2,
+        }
+    }
+
+    /// Number of bytes a frame with this layout occupies
+    pub fn size(&self) -> usize {
+        self.planes
+            .iter()
+            .enumerate()
+            .map(|(idx, plane)| plane.offset + plane.stride * self.plane_height(idx))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Sub-rectangle of a frame the pattern is confined to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// The whole frame of `layout`
+    pub fn full(layout: &FrameLayout) -> Self {
+        Viewport {
+            x: 0,
+            y: 0,
+            width: layout.width,
+            height: layout.height,
+        }
+    }
+
+    /// Clamps the viewport into the frame of `layout`. For subsampled
+    /// formats it is aligned to the chroma blocks, rounding position and
+    /// size down to even values unless it reaches the frame edge.
+    pub fn fit(&self, layout: &FrameLayout) -> Self {
+        let x = self.x.min(layout.width);
+        let y = self.y.min(layout.height);
+        let mut fitted = Viewport {
+            x,
+            y,
+            width: self.width.min(layout.width - x),
+            height: self.height.min(layout.height - y),
+        };
+
+        if layout.format == Format::Nv12 {
+            fitted.x &= !1;
+            fitted.y &= !1;
+            if fitted.x + fitted.width < layout.width {
+                fitted.width &= !1;
+            }
+            if fitted.y + fitted.height < layout.height {
+                fitted.height &= !1;
+            }
+        }
+
+        fitted
+    }
+}
+
+/// Picture drawn into the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// Horizontal bars of the foreground color, `size` lines high with the
+    /// first one at line `offset`
+    Bar,
+    /// SMPTE 75% color bars
+    Smpte,
+    /// EBU 100/0/75/0 color bars
+    Ebu75,
+    /// EBU 100/0/100/0 color bars
+    Ebu100,
+    /// SMPTE RP 219 HD color bars
+    Rp219,
+    /// Squares of the foreground and background color, moving down by
+    /// `offset` lines
+    Checkers,
+    /// Gradient from the background color on the left to the foreground
+    /// color on the right, moving right by `offset` columns
+    HorizontalGradient,
+    /// Gradient from the background color at the top to the foreground
+    /// color at the bottom, moving down by `offset` lines
+    VerticalGradient,
+    /// Gradient from the background to the foreground color along
+    /// `gradient_angle`, moving along it by `offset` pixels
+    AngledGradient,
+    /// Random gray pixels, different for every `noise_seed`
+    Snow,
+    /// Smooth animated noise between the background and foreground color
+    ValueNoise,
+    /// Ball of the foreground color moving on a Lissajous path over time
+    Ball,
+    /// Ball of the foreground color falling and bouncing as simulated by
+    /// `bouncing_ball`
+    BouncingBall,
+    /// Streaks of the foreground color falling down at `particles`
+    Rain,
+    /// Gray sine zone plate as described by `zone_plate`
+    ZonePlate,
+    /// Siemens star of `spokes` foreground colored sectors
+    SiemensStar,
+    /// Whole frame alternating between the foreground and the background
+    /// color every `blink_interval` frames, starting with the foreground
+    Blink,
+    /// Crosshatch of `line_width` wide foreground colored lines every
+    /// `grid_spacing` pixels
+    Grid,
+    /// Concentric rings of the foreground and background color, each
+    /// `ring_width` pixels wide, moving outwards by `offset` pixels
+    Circles,
+    /// `steps` gray steps from black to white
+    Staircase,
+    /// Grid of the [`COLOR_CHECKER`] reference colors
+    ColorChecker,
+    /// Smooth gray ramp from black on the left to white on the right
+    Ramp,
+    /// Line pairs of the foreground and background color getting finer from
+    /// left to right, vertical in the upper and horizontal in the lower half
+    LinePairs,
+    /// Action and title safe rectangles, corner markers and a center cross
+    /// of the foreground color on gray
+    SafeArea,
+    /// Single pixel of the foreground color at the [`moving_pixel`]
+    /// position of the frame
+    MovingPixel,
+    /// Whole frame in white, black, red, green, blue, cyan, magenta and
+    /// yellow, each held for `hold_frames` frames
+    ColorCycle,
+    /// Even lines in the foreground and odd lines in the background color
+    /// with a block moving down one line per field
+    FieldIdentification,
+    /// Checkerboard of single red and blue pixels
+    ChromaChecker,
+    /// Foreground color left and background color right of a vertical edge
+    /// at the [`moving_edge`] position of the frame
+    MovingEdge,
+    /// Plasma of moving sines in colors between the background and the
+    /// foreground color, animated over `time`
+    Plasma,
+    /// Mandelbrot set zooming in over `time`, colored from the background
+    /// to the foreground color by escape time
+    Mandelbrot,
+    /// Spiral of `arms` foreground colored arms, rotating by
+    /// `rotation_speed` over `time`
+    Spiral,
+    /// `box_width` x `box_height` rectangle of the foreground color moving
+    /// by `speed_x` and `speed_y` per frame, bouncing off the frame edges
+    MovingBox,
+    /// `image` tiled over the frame, moving down by `offset` lines
+    Image,
+    /// Black, for content drawn by the application over the rendered frame
+    Custom,
+    /// Single fully saturated color, see [`vector_color`]
+    ColorVectors,
+    /// Foreground color with film grain, `grain_intensity` levels strong
+    /// and `grain_size` pixels wide, different in every frame
+    Grain,
+    /// Static composite monitor test card in the spirit of the PM5544
+    TestCard,
+    /// Black with a white rectangle in every [`is_flash_frame`] for A/V
+    /// sync measurements
+    Flash,
+    /// `pattern_left` in the left and `pattern_right` in the right half,
+    /// the left one gets the extra column of odd widths
+    Split,
+    /// Wireframe cube of the foreground color, rotating by `rotation_speed`
+    Cube,
+}
+
+/// Everything that determines the content of a frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternParams {
+    pub pattern: Pattern,
+    /// Pattern cross-fading into `pattern` and how far the fade got
+    /// (0-256)
+    pub transition: Option<(Pattern, u32)>,
+    /// Pattern drawn over the background color before `pattern`, which is
+    /// composed over it. It stays in place while `pattern` moves.
+    pub background_pattern: Option<Pattern>,
+    /// Big-endian ARGB color of the bar
+    pub foreground_color: u32,
+    /// Big-endian ARGB color everything else is filled with. If `None` the
+    /// pattern is blended over the existing frame content instead.
+    pub background_color: Option<u32>,
+    /// First line covered by the bar, relative to `margin_top`
+    pub offset: u32,
+    /// Number of lines covered by the bar
+    pub size: u32,
+    /// Lines the edges of the bar are blended into the background over, 0
+    /// for hard edges
+    pub softness: u32,
+    /// Sub-pixel part of the bar position in -0.5..0.5, added to `offset`
+    /// for soft edges
+    pub offset_fraction: f64,
+    /// Lines at the top and the bottom the bar never enters, it scrolls and
+    /// wraps around in between
+    pub margin_top: u32,
+    pub margin_bottom: u32,
+    /// With [`Orientation::Horizontal`] `offset` and `size` are columns
+    pub orientation: Orientation,
+    /// What happens at the end of the scroll period, with
+    /// [`Motion::Bounce`] `offset` is a position in the
+    /// [`motion_period`](Self::motion_period) and folded before drawing
+    pub wrap_mode: Motion,
+    /// Pixels the moving edge moves per frame
+    pub speed: i32,
+    /// Degrees per second the hue of the bar is rotated by over `time`,
+    /// starting with the foreground color
+    pub hue_speed: f64,
+    /// Degrees the hue of the color vectors pattern advances per frame
+    pub hue_step: f64,
+    pub hue_quantize: bool,
+    pub grain_intensity: u32,
+    pub grain_size: u32,
+    /// Nanoseconds between the flashes of the flash pattern
+    pub flash_period: u64,
+    /// Number of bars, all but the first follow `bar_spacing` lines below
+    /// the previous one
+    pub bar_count: u32,
+    pub bar_spacing: u32,
+    /// Colors of the bars in order, the foreground color for bars without
+    /// an entry
+    pub bar_colors: Vec<u32>,
+    /// Edge length of the checkerboard squares
+    pub checker_size: u32,
+    /// Direction of the angled gradient in degrees, clockwise from pointing
+    /// right
+    pub gradient_angle: f64,
+    /// Seed of the random noise, should change every frame
+    pub noise_seed: u64,
+    /// Seed of random but reproducible patterns like the value noise
+    pub seed: u64,
+    /// Number of octaves summed up by the value noise
+    pub octaves: u32,
+    /// Radius of the ball in pixels
+    pub ball_radius: u32,
+    /// State of the bouncing ball simulation
+    pub bouncing_ball: BouncingBall,
+    pub pattern_left: Pattern,
+    pub pattern_right: Pattern,
+    /// State of the bouncing balls in the left and right half of the split
+    /// pattern
+    pub split_balls: [BouncingBall; 2],
+    /// Centers of the ball in previous frames, oldest first, drawn fading
+    /// out behind it
+    pub trail: Vec<(f64, f64)>,
+    /// Positions of the rain streaks
+    pub particles: Vec<Particle>,
+    /// Number of lines covered by a rain streak
+    pub streak_length: u32,
+    pub zone_plate: ZonePlate,
+    /// Number of foreground colored sectors of the Siemens star
+    pub spokes: u32,
+    /// Diameter of the Siemens star relative to the shorter frame side
+    pub star_radius: f64,
+    /// Number of frames between two color changes of the blink pattern
+    pub blink_interval: u32,
+    /// Distance between two lines of the grid in pixels
+    pub grid_spacing: u32,
+    /// Width of the grid lines in pixels
+    pub line_width: u32,
+    /// Width of one ring of the concentric circles in pixels
+    pub ring_width: u32,
+    /// Number of gray steps of the staircase, at least 2
+    pub steps: u32,
+    /// Arrange the staircase steps as lines instead of columns
+    pub step_rows: bool,
+    /// Dither the ramp instead of rounding it to 8 bits
+    pub dither: bool,
+    /// Fraction of the width and height inside the action safe rectangle
+    pub action_safe: f64,
+    /// Fraction of the width and height inside the title safe rectangle
+    pub title_safe: f64,
+    /// Number of frames every color of the color cycle is shown
+    pub hold_frames: u32,
+    /// Maximum number of iterations per pixel of the Mandelbrot set
+    pub iterations: u32,
+    /// Number of arms of the spiral
+    pub arms: u32,
+    /// Degrees per second the spiral rotates by clockwise, and the cube
+    /// about its vertical axis
+    pub rotation_speed: f64,
+    pub box_width: u32,
+    pub box_height: u32,
+    /// Pixels the box moves right and down per frame
+    pub speed_x: i32,
+    pub speed_y: i32,
+    /// Shared between frames, it is only decoded once per stream
+    pub image: Option<Arc<Image>>,
+    /// Corner the frame index is burned into on top of the pattern, if any
+    pub frame_counter: Option<Corner>,
+    /// Burn the [`timecode`] of `time` into the top of the frame
+    pub show_timecode: bool,
+    /// Drawn on top of the pattern unless empty
+    pub text: String,
+    /// Top left corner of the text box
+    pub text_position: (u32, u32),
+    /// Size of a text font pixel in pixels
+    pub text_scale: u32,
+    /// Edge length of the square [`FrameCode`] of `time` and the frame index
+    /// drawn on top of the pattern, 0 to draw none
+    pub code_size: u32,
+    /// Top left corner of the frame code
+    pub code_position: (u32, u32),
+    /// Number of lines at the top covered by a stripe of blocks encoding
+    /// `time` and the frame index, see [`decode_timestamp_stripe`], 0 for
+    /// none
+    pub stripe_lines: u32,
+    /// Frame index and time in nanoseconds of the frame in the stream, shown
+    /// by the frame counter, timecode and codes. `None` if they are the
+    /// frame index and `time` the pattern is drawn for, which differ while
+    /// the motion is held or looped.
+    pub stream_position: Option<(u64, u64)>,
+    /// Draw the border, labels and codes into the frame. Without, they can
+    /// be rendered on their own with [`Renderer::render_overlay`].
+    pub overlays_in_frame: bool,
+    /// Order of the fields of interlaced frames, `None` for progressive ones
+    pub field_order: Option<FieldOrder>,
+    /// Frame rate of the stream as numerator and denominator, 0/1 if
+    /// variable
+    pub framerate: (u32, u32),
+    /// Pixel aspect ratio of the frame, the width of a pixel relative to its
+    /// height
+    pub pixel_aspect: (u32, u32),
+    /// Stream time of the frame in nanoseconds, animated patterns are a
+    /// function of it
+    pub time: u64,
+    /// Adjustments applied to the whole rendered pattern
+    pub balance: ColorBalance,
+    /// Invert the colors of the pattern to mark a discontinuity, labels and
+    /// codes stay as they are so they can still be read
+    pub invert: bool,
+    /// Thickness of a border along the frame edges, drawn below the labels,
+    /// 0 for none
+    pub border_width: u32,
+    /// Big-endian ARGB color of the border
+    pub border_color: u32,
+    /// Flip the rendered frame left to right, labels included
+    pub mirror_x: bool,
+    /// Flip the rendered frame top to bottom, labels included
+    pub mirror_y: bool,
+    /// Drawn in order instead of the pattern if not empty
+    pub objects: Vec<Object>,
+    /// Area the pattern is drawn into, and the coordinates all geometry
+    /// is relative to. The whole frame if `None`.
+    pub viewport: Option<Viewport>,
+    /// Fill the frame outside of the viewport with the background color,
+    /// otherwise it is left untouched
+    pub viewport_clear: bool,
+}
+
+impl Default for PatternParams {
+    fn default() -> Self {
+        PatternParams {
+            pattern: Pattern::Bar,
+            transition: None,
+            background_pattern: None,
+            foreground_color: 0xffffffff,
+            background_color: Some(0xff000000),
+            offset: 0,
+            size: 50,
+            softness: 0,
+            offset_fraction: 0.0,
+            margin_top: 0,
+            margin_bottom: 0,
+            orientation: Orientation::Vertical,
+            wrap_mode: Motion::Wrap,
+            speed: 1,
+            hue_speed: 0.0,
+            hue_step: 1.0,
+            hue_quantize: false,
+            grain_intensity: 16,
+            grain_size: 1,
+            flash_period: 2_000_000_000,
+            bar_count: 1,
+            bar_spacing: 50,
+            bar_colors: Vec::new(),
+            checker_size: 8,
+            gradient_angle: 0.0,
+            noise_seed: 0,
+            seed: 0,
+            octaves: 4,
+            ball_radius: 20,
+            bouncing_ball: BouncingBall::default(),
+            pattern_left: Pattern::Snow,
+            pattern_right: Pattern::HorizontalGradient,
+            split_balls: [BouncingBall::default(); 2],
+            trail: Vec::new(),
+            particles: Vec::new(),
+            streak_length: 12,
+            zone_plate: ZonePlate {
+                kx2: 20,
+                ky2: 20,
+                ..ZonePlate::default()
+            },
+            spokes: 36,
+            star_radius: 0.9,
+            blink_interval: 1,
+            grid_spacing: 64,
+            line_width: 1,
+            ring_width: 16,
+            steps: 16,
+            step_rows: false,
+            dither: false,
+            action_safe: 0.9,
+            title_safe: 0.8,
+            hold_frames: 30,
+            iterations: 256,
+            arms: 4,
+            rotation_speed: 90.0,
+            box_width: 64,
+            box_height: 48,
+            speed_x: 3,
+            speed_y: 2,
+            image: None,
+            frame_counter: None,
+            show_timecode: false,
+            text: String::new(),
+            text_position: (0, 0),
+            text_scale: 2,
+            code_size: 0,
+            code_position: (0, 0),
+            stripe_lines: 0,
+            stream_position: None,
+            overlays_in_frame: true,
+            field_order: None,
+            framerate: (0, 1),
+            pixel_aspect: (1, 1),
+            time: 0,
+            balance: ColorBalance::default(),
+            invert: false,
+            border_width: 0,
+            border_color: 0xffffffff,
+            mirror_x: false,
+            mirror_y: false,
+            objects: Vec::new(),
+            viewport: None,
+            viewport_clear: true,
+        }
+    }
+}
+
+impl PatternParams {
+    /// Center of the ball in a `width` x `height` frame, if the pattern has
+    /// one
+    pub fn ball_center(&self, width: usize, height: usize) -> Option<(f64, f64)> {
+        let radius = ball::fitted_radius(self.ball_radius, width, height);
+        match self.pattern {
+            Pattern::Ball => Some(ball::lissajous(width, height, radius, self.time)),
+            Pattern::BouncingBall => Some(self.bouncing_ball.center(width, height, radius)),
+            _ => None,
+        }
+    }
+
+    /// Distance after which `offset` wraps around for a `width` x `height`
+    /// pattern, so scrolling continues seamlessly
+    pub fn scroll_period(&self, width: u32, height: u32) -> u32 {
+        let period = match self.pattern {
+            Pattern::Checkers => self.checker_size.max(1).saturating_mul(2),
+            Pattern::Circles => self.ring_width.max(1).saturating_mul(2),
+            Pattern::Image => match self.image {
+                Some(ref image) => image.height() as u32,
+                None => height,
+            },
+            Pattern::Bar if self.orientation == Orientation::Horizontal => width,
+            Pattern::Bar => height
+                .saturating_sub(self.margin_top)
+                .saturating_sub(self.margin_bottom),
+            Pattern::HorizontalGradient => width,
+            Pattern::AngledGradient => {
+                gradient::angled_period(width as usize, height as usize, self.gradient_angle)
+            }
+            _ => height,
+        };
+
+        period.max(1)
+    }
+
+    // Distance a bouncing pattern travels in one direction, the bar stops
+    // when its last line reaches the edge
+    fn bounce_range(&self, width: u32, height: u32) -> u32 {
+        let period = self.scroll_period(width, height);
+        match self.pattern {
+            Pattern::Bar => period.saturating_sub(self.size),
+            _ => period,
+        }
+    }
+
+    /// Distance after which the motion repeats, the scroll period when
+    /// wrapping and there and back again when bouncing
+    pub fn motion_period(&self, width: u32, height: u32) -> u32 {
+        match self.wrap_mode {
+            Motion::Wrap => self.scroll_period(width, height),
+            Motion::Bounce => self.bounce_range(width, height).saturating_mul(2).max(1),
+        }
+    }
+
+    /// Offset drawn `shift` pixels away from `offset`, wrapping around the
+    /// scroll period or, when bouncing, staying in the distance the pattern
+    /// bounces in
+    pub fn shift_offset(&self, offset: u32, shift: i64, width: u32, height: u32) -> u32 {
+        let offset = offset as i64 + shift;
+        match self.wrap_mode {
+            Motion::Wrap => offset.rem_euclid(self.scroll_period(width, height) as i64) as u32,
+            Motion::Bounce => offset.clamp(0, self.bounce_range(width, height) as i64) as u32,
+        }
+    }
+
+    /// Offset of a pattern swinging `amplitude` pixels around the middle of
+    /// the distance it can bounce in, once every `period` nanoseconds, at
+    /// `time`, and the part of a pixel it was rounded by. The offset is
+    /// clamped so the bar never leaves the frame, with a `period` of 0 the
+    /// pattern stays in the middle.
+    pub fn oscillate(
+        &self,
+        time: u64,
+        (amplitude, period): (u32, u64),
+        width: u32,
+        height: u32,
+    ) -> (u32, f64) {
+        let range = self.bounce_range(width, height) as f64;
+        let swing = if period > 0 {
+            let phase = (time % period) as f64 / period as f64;
+            amplitude as f64 * (2.0 * std::f64::consts::PI * phase).sin()
+        } else {
+            0.0
+        };
+        let position = (range / 2.0 + swing).clamp(0.0, range);
+
+        (position.round() as u32, position - position.round())
+    }
+
+    /// Offset to draw at `position` in the motion period. A bouncing pattern
+    /// turns at the edges, moving back by however far it overshot them.
+    pub fn fold_offset(&self, position: u32, width: u32, height: u32) -> u32 {
+        match self.wrap_mode {
+            Motion::Wrap => position,
+            Motion::Bounce => {
+                let range = self.bounce_range(width, height);
+                let position = position % range.saturating_mul(2).max(1);
+                if position > range {
+                    range - (position - range)
+                } else {
+                    position
+                }
+            }
+        }
+    }
+}
+
+/// Renders pattern frames into memory described by a [`FrameLayout`]
+///
+/// The renderer keeps its scratch memory between frames, so one instance
+/// should be reused for a whole stream.
+#[derive(Debug, Default)]
+pub struct Renderer {
+    canvas: Vec<u32>,
+    width: usize,
+    height: usize,
+    // Each cache is built by prepare() or on first use, and again whenever
+    // what it depends on changes
+    value_noise: Option<noise::ValueNoise>,
+    // One for each half of the split pattern, so their caches are kept
+    // separately
+    split: Option<Box<[Renderer; 2]>>,
+    // The test card of the last frame size, it doesn't change otherwise
+    test_card: Option<((usize, usize), Vec<u32>)>,
+    grain: Option<grain::Grain>,
+    sine: Vec<u8>,
+    // Static patterns rendered for the parameters they depend on
+    star: Option<(StarKey, Vec<u32>)>,
+    // Escape counts of the last Mandelbrot zoom level, reused by all frames
+    // until the zoom changes
+    mandelbrot: Option<(MandelbrotKey, Vec<u32>)>,
+    // Built for the size of the canvas
+    polar: Option<spiral::PolarMap>,
+    // Draws the pattern faded out during a transition, with caches of its
+    // own
+    transition: Option<Box<Renderer>>,
+    // Canvas of render_overlay()
+    overlay: Vec<u32>,
+}
+
+/// Border, labels and codes of a frame rendered on their own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlay {
+    /// Position of the top left corner in the frame
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// BGRA pixels with the alpha not premultiplied, `width * 4` bytes per
+    /// line
+    pub data: Vec<u8>,
+}
+
+// Everything the Siemens star depends on, the radius as bits to compare it
+type StarKey = (usize, usize, u32, u64, u32, u32);
+// Size, zoom level and iterations of Mandelbrot escape counts
+type MandelbrotKey = (usize, usize, u64, u32);
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders frame number `frame_index` of the pattern into `out`, which
+    /// holds a whole frame laid out as described by `layout`.
+    ///
+    /// Panics if `out` is smaller than `layout.size()`.
+    pub fn render(
+        &mut self,
+        frame_index: u64,
+        params: &PatternParams,
+        layout: &FrameLayout,
+        out: &mut [u8],
+    ) {
+        assert!(out.len() >= layout.size(), "frame memory too small");
+
+        let mut planes = Vec::with_capacity(layout.planes.len());
+        let mut rest = out;
+        let mut consumed = 0;
+        for (idx, plane) in layout.planes.iter().enumerate() {
+            let len = plane.stride * layout.plane_height(idx);
+            let (_, tail) = mem::take(&mut rest).split_at_mut(plane.offset - consumed);
+            let (data, tail) = tail.split_at_mut(len);
+            planes.push(data);
+            rest = tail;
+            consumed = plane.offset + len;
+        }
+
+        self.render_planes(frame_index, params, layout, &mut planes);
+    }
+
+    /// Same as [`Renderer::render`], but with every plane in its own slice.
+    /// The plane offsets in `layout` are ignored.
+    pub fn render_planes(
+        &mut self,
+        frame_index: u64,
+        params: &PatternParams,
+        layout: &FrameLayout,
+        planes: &mut [&mut [u8]],
+    ) {
+        assert_eq!(planes.len(), layout.format.n_planes());
+
+        let full = Viewport::full(layout);
+        let viewport = params
+            .viewport
+            .map_or(full, |viewport| viewport.fit(layout));
+
+        if viewport != full && params.viewport_clear {
+            if let Some(color) = params.background_color {
+                for (idx, data) in planes.iter_mut().enumerate() {
+                    fill_plane(layout, idx, data, color);
+                }
+            }
+        }
+        if viewport.width == 0 || viewport.height == 0 {
+            return;
+        }
+
+        self.resize(viewport.width as usize, viewport.height as usize);
+        self.draw(frame_index, params);
+
+        let blend = params.background_color.is_none();
+        let origin = (viewport.x as usize, viewport.y as usize);
+        let mirror = (params.mirror_x, params.mirror_y);
+        for (idx, data) in planes.iter_mut().enumerate() {
+            self.pack(layout, idx, data, blend, origin, mirror);
+        }
+    }
+
+    /// Renders only the border, labels and codes of frame number
+    /// `frame_index` on a transparent background, cropped to the part they
+    /// cover. They are placed in the viewport like in [`Renderer::render`],
+    /// but not mirrored. Returns `None` if nothing is drawn.
+    pub fn render_overlay(
+        &mut self,
+        frame_index: u64,
+        params: &PatternParams,
+        layout: &FrameLayout,
+    ) -> Option<Overlay> {
+        let viewport = params
+            .viewport
+            .map_or(Viewport::full(layout), |viewport| viewport.fit(layout));
+        let (width, height) = (viewport.width as usize, viewport.height as usize);
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let mut canvas = mem::take(&mut self.overlay);
+        canvas.clear();
+        canvas.resize(width * height, 0);
+        draw_overlays(&mut canvas, width, frame_index, params);
+
+        let covered = |pixel: &u32| pixel >> 24 != 0;
+        let rows = canvas
+            .chunks_exact(width)
+            .enumerate()
+            .filter(|(_, row)| row.iter().any(covered))
+            .map(|(y, _)| y);
+        let (top, bottom) = rows.fold((height, 0), |(top, bottom), y| {
+            (top.min(y), bottom.max(y + 1))
+        });
+        let columns =
+            (0..width).filter(|&x| (top..bottom).any(|y| covered(&canvas[y * width + x])));
+        let (left, right) = columns.fold((width, 0), |(left, right), x| {
+            (left.min(x), right.max(x + 1))
+        });
+
+        let overlay = if top < bottom {
+            let mut data = Vec::with_capacity((right - left) * (bottom - top) * 4);
+            for row in canvas[top * width..bottom * width].chunks_exact(width) {
+                for &pixel in &row[left..right] {
+                    data.extend_from_slice(&pixel.to_le_bytes());
+                }
+            }
+            Some(Overlay {
+                x: viewport.x + left as u32,
+                y: viewport.y + top as u32,
+                width: (right - left) as u32,
+                height: (bottom - top) as u32,
+                data,
+            })
+        } else {
+            None
+        };
+        self.overlay = canvas;
+
+        overlay
+    }
+
+    /// Builds the tables and static images the pattern and the background
+    /// pattern of `params` are drawn from for frames laid out as described
+    /// by `layout`, so the first frame doesn't take longer than the others.
+    /// Rendering builds them itself once they don't match anymore.
+    pub fn prepare(&mut self, params: &PatternParams, layout: &FrameLayout) {
+        let viewport = params
+            .viewport
+            .map_or(Viewport::full(layout), |viewport| viewport.fit(layout));
+        if viewport.width == 0 || viewport.height == 0 {
+            return;
+        }
+
+        self.resize(viewport.width as usize, viewport.height as usize);
+        if let Some(pattern) = params.background_pattern {
+            self.prepare_pattern(pattern, params);
+        }
+        self.prepare_pattern(params.pattern, params);
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        if self.width != width || self.height != height {
+            self.width = width;
+            self.height = height;
+            self.canvas = vec![0; width * height];
+        }
+    }
+
+    fn draw(&mut self, frame_index: u64, params: &PatternParams) {
+        // Fully transparent pixels are left untouched when blending
+        self.canvas.fill(params.background_color.unwrap_or(0));
+
+        if params.objects.is_empty() {
+            self.draw_layers(frame_index, params);
+            if let Some((pattern, progress)) = params.transition {
+                self.fade_from(frame_index, params, pattern, progress);
+            }
+        }
+        for object in &params.objects {
+            object.draw(&mut self.canvas, self.width, self.height, frame_index);
+        }
+
+        params.balance.apply(&mut self.canvas);
+        if params.invert {
+            for pixel in self.canvas.iter_mut() {
+                *pixel ^= 0x00ffffff;
+            }
+        }
+        if params.overlays_in_frame {
+            draw_overlays(&mut self.canvas, self.width, frame_index, params);
+        }
+    }
+
+    // Blends `pattern` drawn with the same parameters into the canvas, by
+    // the weight that's left of it `progress` (0-256) into the transition
+    fn fade_from(
+        &mut self,
+        frame_index: u64,
+        params: &PatternParams,
+        pattern: Pattern,
+        progress: u32,
+    ) {
+        let mut from = params.clone();
+        from.pattern = pattern;
+        from.transition = None;
+
+        let renderer = self.transition.get_or_insert_with(Default::default);
+        renderer.resize(self.width, self.height);
+        renderer.canvas.fill(params.background_color.unwrap_or(0));
+        renderer.draw_layers(frame_index, &from);
+        for (pixel, &old) in self.canvas.iter_mut().zip(renderer.canvas.iter()) {
+            *pixel = gradient::lerp(old, *pixel, progress, 256);
+        }
+    }
+
+    // Draws the background pattern, if any, and the pattern over it
+    fn draw_layers(&mut self, frame_index: u64, params: &PatternParams) {
+        if let Some(pattern) = params.background_pattern {
+            let mut background = params.clone();
+            background.pattern = pattern;
+            background.offset = 0;
+            background.offset_fraction = 0.0;
+            self.draw_pattern(frame_index, &background);
+        }
+        self.draw_pattern(frame_index, params);
+    }
+
+    // Builds the tables and static images `pattern` is drawn from for the
+    // size of the canvas, unless they match already
+    fn prepare_pattern(&mut self, pattern: Pattern, params: &PatternParams) {
+        let (width, height) = (self.width, self.height);
+        match pattern {
+            Pattern::ValueNoise
+                if self.value_noise.as_ref().map(noise::ValueNoise::seed) != Some(params.seed) =>
+            {
+                self.value_noise = Some(noise::ValueNoise::new(params.seed));
+            }
+            Pattern::ZonePlate | Pattern::Plasma if self.sine.is_empty() => {
+                self.sine = zoneplate::sine_table();
+            }
+            Pattern::SiemensStar => {
+                let background = params.background_color.unwrap_or(0);
+                let key = (
+                    width,
+                    height,
+                    params.spokes,
+                    params.star_radius.to_bits(),
+                    params.foreground_color,
+                    background,
+                );
+                if self.star.as_ref().map(|(star_key, _)| *star_key) != Some(key) {
+                    let mut star = vec![0; width * height];
+                    star::siemens_star(
+                        &mut star,
+                        width,
+                        height,
+                        params.spokes,
+                        params.star_radius,
+                        params.foreground_color,
+                        background,
+                    );
+                    self.star = Some((key, star));
+                }
+            }
+            Pattern::Spiral
+                if self.polar.as_ref().map(|polar| polar.size()) != Some((width, height)) =>
+            {
+                self.polar = Some(spiral::PolarMap::new(width, height));
+            }
+            Pattern::TestCard
+                if self.test_card.as_ref().map(|(size, _)| *size) != Some((width, height)) =>
+            {
+                let mut card = vec![0; width * height];
+                testcard::test_card(&mut card, width, height);
+                self.test_card = Some(((width, height), card));
+            }
+            Pattern::Grain => {
+                let (seed, size) = (params.seed, params.grain_size);
+                if !matches!(self.grain, Some(ref grain) if grain.matches(seed, width, height, size))
+                {
+                    self.grain = Some(grain::Grain::new(seed, width, height, size));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Draws the pattern itself over the background
+    fn draw_pattern(&mut self, frame_index: u64, params: &PatternParams) {
+        self.prepare_pattern(params.pattern, params);
+        match params.pattern {
+            Pattern::Bar => {
+                let degrees = params.hue_speed * params.time as f64 / 1_000_000_000.0;
+                let color = |bar: usize| {
+                    let color = params
+                        .bar_colors
+                        .get(bar)
+                        .copied()
+                        .unwrap_or(params.foreground_color);
+                    match degrees {
+                        degrees if degrees != 0.0 => color::rotate_hue(color, degrees),
+                        _ => color,
+                    }
+                };
+                let top = (params.margin_top as usize).min(self.height);
+                let bottom = self
+                    .height
+                    .saturating_sub(params.margin_bottom as usize)
+                    .max(top);
+                let canvas = &mut self.canvas[top * self.width..bottom * self.width];
+                let blend = params.background_pattern.is_some();
+                if params.softness > 0 {
+                    let bars = match params.orientation {
+                        Orientation::Vertical => bars::soft_horizontal_bars,
+                        Orientation::Horizontal => bars::soft_vertical_bars,
+                    };
+                    bars(
+                        canvas,
+                        self.width,
+                        (params.offset as f64 + params.offset_fraction, params.size),
+                        (params.bar_count, params.bar_spacing),
+                        params.softness,
+                        color,
+                        blend,
+                    );
+                } else {
+                    let bars = match params.orientation {
+                        Orientation::Vertical => bars::horizontal_bars,
+                        Orientation::Horizontal => bars::vertical_bars,
+                    };
+                    bars(
+                        canvas,
+                        self.width,
+                        (params.offset, params.size),
+                        params.bar_count,
+                        params.bar_spacing,
+                        color,
+                        blend,
+                    );
+                }
+            }
+            Pattern::Smpte => bars::smpte(&mut self.canvas, self.width, self.height),
+            Pattern::Ebu75 => bars::ebu(&mut self.canvas, self.width, 0xbf),
+            Pattern::Ebu100 => bars::ebu(&mut self.canvas, self.width, 0xff),
+            Pattern::Rp219 => bars::rp219(&mut self.canvas, self.width, self.height),
+            Pattern::Checkers => checkers::checkerboard(
+                &mut self.canvas,
+                self.width,
+                params.checker_size,
+                params.offset,
+                params.foreground_color,
+                params.background_color.unwrap_or(0),
+            ),
+            Pattern::HorizontalGradient => gradient::horizontal(
+                &mut self.canvas,
+                self.width,
+                params.offset,
+                params.background_color.unwrap_or(0),
+                params.foreground_color,
+            ),
+            Pattern::VerticalGradient => gradient::vertical(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                params.offset,
+                params.background_color.unwrap_or(0),
+                params.foreground_color,
+            ),
+            Pattern::AngledGradient => gradient::angled(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                params.gradient_angle,
+                params.offset,
+                params.background_color.unwrap_or(0),
+                params.foreground_color,
+            ),
+            Pattern::Snow => noise::snow(&mut self.canvas, params.noise_seed),
+            Pattern::ValueNoise => {
+                if let Some(ref value_noise) = self.value_noise {
+                    value_noise.draw(
+                        &mut self.canvas,
+                        self.width,
+                        params.octaves,
+                        params.time,
+                        params.background_color.unwrap_or(0),
+                        params.foreground_color,
+                    );
+                }
+            }
+            Pattern::Ball | Pattern::BouncingBall => {
+                let radius = ball::fitted_radius(params.ball_radius, self.width, self.height);
+                // Older positions fade out more
+                let n = params.trail.len() as u32;
+                for (i, &center) in params.trail.iter().enumerate() {
+                    let opacity = 255 * (i as u32 + 1) / (n + 1);
+                    ball::disc(
+                        &mut self.canvas,
+                        self.width,
+                        self.height,
+                        center,
+                        radius,
+                        params.foreground_color,
+                        opacity,
+                    );
+                }
+                if let Some(center) = params.ball_center(self.width, self.height) {
+                    ball::disc(
+                        &mut self.canvas,
+                        self.width,
+                        self.height,
+                        center,
+                        radius,
+                        params.foreground_color,
+                        255,
+                    );
+                }
+            }
+            Pattern::Rain => rain::draw(
+                &mut self.canvas,
+                self.width,
+                &params.particles,
+                params.streak_length,
+                params.foreground_color,
+            ),
+            Pattern::ZonePlate => {
+                params.zone_plate.draw(
+                    &mut self.canvas,
+                    self.width,
+                    self.height,
+                    params.time,
+                    params.framerate,
+                    &self.sine,
+                );
+            }
+            Pattern::SiemensStar => {
+                if let Some((_, ref star)) = self.star {
+                    self.canvas.copy_from_slice(star);
+                }
+            }
+            Pattern::Blink => {
+                if (frame_index / params.blink_interval.max(1) as u64) & 1 == 0 {
+                    self.canvas.fill(params.foreground_color);
+                }
+            }
+            Pattern::Grid => grid::crosshatch(
+                &mut self.canvas,
+                self.width,
+                params.grid_spacing,
+                params.line_width,
+                params.foreground_color,
+            ),
+            Pattern::Circles => circles::concentric(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                params.ring_width,
+                params.offset,
+                params.foreground_color,
+            ),
+            Pattern::Staircase => gradient::staircase(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                params.steps,
+                params.step_rows,
+            ),
+            Pattern::ColorChecker => {
+                swatches::color_checker(&mut self.canvas, self.width, self.height)
+            }
+            Pattern::Ramp => gradient::ramp(&mut self.canvas, self.width, params.dither),
+            Pattern::LinePairs => linepairs::line_pairs(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                params.foreground_color,
+                params.background_color.unwrap_or(0),
+            ),
+            Pattern::SafeArea => safearea::safe_area(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                params.action_safe,
+                params.title_safe,
+                params.foreground_color,
+            ),
+            Pattern::MovingPixel => {
+                let (x, y) = moving_pixel(frame_index, self.width, self.height);
+                self.canvas[y * self.width + x] = params.foreground_color;
+            }
+            Pattern::ColorCycle => {
+                let step = frame_index / params.hold_frames.max(1) as u64;
+                self.canvas
+                    .fill(color::CYCLE[(step % color::CYCLE.len() as u64) as usize]);
+            }
+            Pattern::FieldIdentification => interlace::field_identification(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                frame_index,
+                params.field_order,
+                (
+                    params.foreground_color,
+                    params.background_color.unwrap_or(0),
+                ),
+            ),
+            Pattern::ChromaChecker => checkers::chroma_checker(&mut self.canvas, self.width),
+            Pattern::MovingEdge => {
+                let edge = moving_edge(frame_index, params.speed, self.width);
+                for row in self.canvas.chunks_exact_mut(self.width) {
+                    row[..edge].fill(params.foreground_color);
+                }
+            }
+            Pattern::Plasma => {
+                plasma::plasma(
+                    &mut self.canvas,
+                    self.width,
+                    self.height,
+                    params.time,
+                    &self.sine,
+                    (
+                        params.background_color.unwrap_or(0),
+                        params.foreground_color,
+                    ),
+                );
+            }
+            Pattern::Mandelbrot => {
+                let iterations = params.iterations.max(1);
+                let step = mandelbrot::zoom_step(params.time);
+                let key = (self.width, self.height, step, iterations);
+                if self.mandelbrot.as_ref().map(|(counts_key, _)| *counts_key) != Some(key) {
+                    let counts =
+                        mandelbrot::escape_counts(self.width, self.height, step, iterations);
+                    self.mandelbrot = Some((key, counts));
+                }
+                if let Some((_, ref counts)) = self.mandelbrot {
+                    mandelbrot::colorize(
+                        &mut self.canvas,
+                        counts,
+                        iterations,
+                        params.background_color.unwrap_or(0),
+                        params.foreground_color,
+                    );
+                }
+            }
+            Pattern::Spiral => {
+                let rotation = params.rotation_speed * params.time as f64 / 1_000_000_000.0;
+                if let Some(ref polar) = self.polar {
+                    polar.spiral(
+                        &mut self.canvas,
+                        params.arms,
+                        rotation,
+                        params.foreground_color,
+                    );
+                }
+            }
+            Pattern::MovingBox => {
+                // Bouncing objects larger than the frame on an axis stay
+                // at 0 on it
+                let object = Object {
+                    shape: Shape::Rect,
+                    width: params.box_width,
+                    height: params.box_height,
+                    color: params.foreground_color,
+                    velocity_x: params.speed_x,
+                    velocity_y: params.speed_y,
+                    motion: Motion::Bounce,
+                    ..Object::default()
+                };
+                object.draw(&mut self.canvas, self.width, self.height, frame_index);
+            }
+            Pattern::Custom => self.canvas.fill(0xff000000),
+            Pattern::Cube => cube::cube(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                params.rotation_speed * params.time as f64 / 1_000_000_000.0,
+                params.pixel_aspect,
+                params.foreground_color,
+            ),
+            Pattern::Split => {
+                let left = self.width.div_ceil(2);
+                let halves = [
+                    (params.pattern_left, 0, left),
+                    (params.pattern_right, left, self.width - left),
+                ];
+                let renderers = self.split.get_or_insert_with(Default::default);
+                for (side, (renderer, &(pattern, x0, width))) in
+                    renderers.iter_mut().zip(halves.iter()).enumerate()
+                {
+                    if pattern == Pattern::Split || width == 0 {
+                        continue;
+                    }
+
+                    // Both halves get their own noise
+                    let mut half = params.clone();
+                    half.pattern = pattern;
+                    half.bouncing_ball = params.split_balls[side];
+                    if side == 1 {
+                        half.noise_seed = Rng::new(params.noise_seed).next_u64();
+                    }
+
+                    renderer.resize(width, self.height);
+                    renderer.canvas.fill(params.background_color.unwrap_or(0));
+                    renderer.draw_pattern(frame_index, &half);
+                    let rows = self.canvas.chunks_exact_mut(self.width);
+                    for (row, src) in rows.zip(renderer.canvas.chunks_exact(width)) {
+                        row[x0..x0 + width].copy_from_slice(src);
+                    }
+                }
+            }
+            Pattern::Flash => flash::flash(
+                &mut self.canvas,
+                self.width,
+                self.height,
+                is_flash_frame(params.time, params.framerate, params.flash_period),
+            ),
+            Pattern::TestCard => {
+                if let Some((_, ref card)) = self.test_card {
+                    self.canvas.copy_from_slice(card);
+                }
+            }
+            Pattern::Grain => {
+                if let Some(ref grain) = self.grain {
+                    grain.draw(
+                        &mut self.canvas,
+                        self.width,
+                        frame_index,
+                        params.grain_size,
+                        params.grain_intensity,
+                        params.foreground_color,
+                    );
+                }
+            }
+            Pattern::ColorVectors => self.canvas.fill(color::vector_color(
+                frame_index,
+                params.hue_step,
+                params.hue_quantize,
+            )),
+            Pattern::Image => {
+                if let Some(ref image) = params.image {
+                    picture::scroll(&mut self.canvas, self.width, image, params.offset);
+                }
+            }
+        }
+    }
+
+    // Converts the canvas into one plane of the output format, with the top
+    // left corner of the canvas at `origin` in the frame. The axes set in
+    // `mirror` are flipped by reading the canvas backwards.
+    fn pack(
+        &self,
+        layout: &FrameLayout,
+        plane: usize,
+        data: &mut [u8],
+        blend: bool,
+        origin: (usize, usize),
+        (mirror_x, mirror_y): (bool, bool),
+    ) {
+        let stride = layout.planes[plane].stride;
+        let (width, height) = (self.width, self.height);
+        let (x0, y0) = origin;
+        // Canvas coordinates of a pixel in the output
+        let src_x = |x: usize| if mirror_x { width - 1 - x } else { x };
+        let src_y = |y: usize| if mirror_y { height - 1 - y } else { y };
+        let row = |y: usize| &self.canvas[src_y(y) * width..(src_y(y) + 1) * width];
+
+        match (layout.format, plane) {
+            (Format::Bgrx, _) | (Format::Bgra, _) => {
+                let has_alpha = layout.format == Format::Bgra;
+                let lines = data.chunks_mut(stride).skip(y0).take(height);
+                for (y, line) in lines.enumerate() {
+                    let (row, line) = (row(y), &mut line[x0 * 4..(x0 + width) * 4]);
+                    for (x, out_p) in line.chunks_exact_mut(4).enumerate() {
+                        let color = row[src_x(x)];
+                        let a = alpha(color, blend);
+                        if a == 0 {
+                            continue;
+                        }
+                        out_p[0] = mix(out_p[0], color as u8, a);
+                        out_p[1] = mix(out_p[1], (color >> 8) as u8, a);
+                        out_p[2] = mix(out_p[2], (color >> 16) as u8, a);
+                        if has_alpha {
+                            out_p[3] = if blend {
+                                mix(out_p[3], 0xff, a)
+                            } else {
+                                (color >> 24) as u8
+                            };
+                        } else if !blend {
+                            out_p[3] = 0xff;
+                        }
+                    }
+                }
+            }
+            (Format::Nv12, 0) => {
+                let lines = data.chunks_mut(stride).skip(y0).take(height);
+                for (y, line) in lines.enumerate() {
+                    let row = row(y);
+                    for (x, out_p) in line[x0..x0 + width].iter_mut().enumerate() {
+                        let color = row[src_x(x)];
+                        let a = alpha(color, blend);
+                        if a == 0 {
+                            continue;
+                        }
+                        *out_p = mix(*out_p, argb_to_ycbcr(color).0, a);
+                    }
+                }
+            }
+            (Format::Nv12, _) => {
+                // The viewport is aligned to the chroma blocks
+                let chroma_width = width.div_ceil(2);
+                let chroma_height = height.div_ceil(2);
+                for (y, line) in data
+                    .chunks_mut(stride)
+                    .skip(y0 / 2)
+                    .take(chroma_height)
+                    .enumerate()
+                {
+                    let line = &mut line[x0 / 2 * 2..(x0 / 2 + chroma_width) * 2];
+                    for (x, out_p) in line.chunks_exact_mut(2).enumerate() {
+                        // Average the pixels of the 2x2 block, weighted by
+                        // their alpha
+                        let (mut sum_a, mut sum_cb, mut sum_cr, mut n) = (0, 0, 0, 0);
+                        for yy in 2 * y..(2 * y + 2).min(height) {
+                            for xx in 2 * x..(2 * x + 2).min(width) {
+                                let color = row(yy)[src_x(xx)];
+                                let a = alpha(color, blend);
+                                let (_, cb, cr) = argb_to_ycbcr(color);
+                                sum_a += a;
+                                sum_cb += cb as u32 * a;
+                                sum_cr += cr as u32 * a;
+                                n += 1;
+                            }
+                        }
+                        if sum_a == 0 {
+                            continue;
+                        }
+                        let total = 255 * n;
+                        out_p[0] = ((out_p[0] as u32 * (total - sum_a) + sum_cb + total / 2)
+                            / total) as u8;
+                        out_p[1] = ((out_p[1] as u32 * (total - sum_a) + sum_cr + total / 2)
+                            / total) as u8;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Fills the picture area of a plane with a solid color
+fn fill_plane(layout: &FrameLayout, plane: usize, data: &mut [u8], color: u32) {
+    let (y, cb, cr) = argb_to_ycbcr(color);
+    let [a, r, g, b] = color.to_be_bytes();
+    let (pixel, n) = match (layout.format, plane) {
+        (Format::Bgrx, _) => ([b, g, r, 0xff], 4),
+        (Format::Bgra, _) => ([b, g, r, a], 4),
+        (Format::Nv12, 0) => ([y, 0, 0, 0], 1),
+        (Format::Nv12, _) => ([cb, cr, 0, 0], 2),
+    };
+
+    let stride = layout.planes[plane].stride;
+    let row_size = layout.row_size(plane);
+    for line in data.chunks_mut(stride).take(layout.plane_height(plane)) {
+        for out_p in line[..row_size].chunks_exact_mut(n) {
+            out_p.copy_from_slice(&pixel[..n]);
+        }
+    }
+}
+
+// Draws the layers on top of the pattern: the border, the labels and the
+// codes
+fn draw_overlays(canvas: &mut [u32], width: usize, frame_index: u64, params: &PatternParams) {
+    let height = canvas.len() / width.max(1);
+    if params.border_width > 0 {
+        safearea::border(
+            canvas,
+            width,
+            height,
+            params.border_width as usize,
+            params.border_color,
+        );
+    }
+
+    // Labels are drawn last to stay readable, and on an opaque box when
+    // blending
+    let label_background = params.background_color.unwrap_or(0xff000000);
+    let (stream_frame, stream_time) = params.stream_position.unwrap_or((frame_index, params.time));
+    if let Some(corner) = params.frame_counter {
+        font::label(
+            canvas,
+            width,
+            height,
+            font::Anchor::Corner(corner),
+            &stream_frame.to_string(),
+            params.foreground_color,
+            label_background,
+        );
+    }
+    if params.show_timecode {
+        font::label(
+            canvas,
+            width,
+            height,
+            font::Anchor::Top,
+            &timecode(stream_time, params.framerate),
+            params.foreground_color,
+            label_background,
+        );
+    }
+    if !params.text.is_empty() {
+        text::text(
+            canvas,
+            width,
+            height,
+            params.text_position,
+            params.text_scale,
+            &params.text,
+            (params.foreground_color, label_background),
+        );
+    }
+    if params.code_size > 0 {
+        let code = FrameCode {
+            time: stream_time,
+            frame: stream_frame,
+        };
+        code.draw(
+            canvas,
+            width,
+            height,
+            params.code_position,
+            params.code_size,
+        );
+    }
+    if params.stripe_lines > 0 {
+        stripe::draw(
+            canvas,
+            width,
+            params.stripe_lines,
+            stream_time,
+            stream_frame,
+        );
+    }
+}
+
+// Opacity a canvas pixel is written with. Without blending the canvas is
+// written as is.
+fn alpha(color: u32, blend: bool) -> u32 {
+    if blend {
+        color >> 24
+    } else {
+        255
+    }
+}
+
+// Blends `src` over `dst` with opacity `a` (0-255)
+fn mix(dst: u8, src: u8, a: u32) -> u8 {
+    ((src as u32 * a + dst as u32 * (255 - a) + 127) / 255) as u8
+}
+
+/// Converts a big-endian ARGB color to limited range BT.601 Y'CbCr
+pub fn argb_to_ycbcr(color: u32) -> (u8, u8, u8) {
+    let r = ((color >> 16) & 0xff) as i32;
+    let g = ((color >> 8) & 0xff) as i32;
+    let b = (color & 0xff) as i32;
+
+    let y = ((66 * r + 129 * g + 25 * b + 128) >> 8) + 16;
+    let cb = ((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128;
+    let cr = ((112 * r - 94 * g - 18 * b + 128) >> 8) + 128;
+
+    (y as u8, cb as u8, cr as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(offset: u32, size: u32) -> PatternParams {
+        PatternParams {
+            offset,
+            size,
+            ..PatternParams::default()
+        }
+    }
+
+    #[test]
+    fn bgrx_layout() {
+        let layout = FrameLayout::new(Format::Bgrx, 5, 3);
+        assert_eq!(
+            layout.planes,
+            vec![PlaneLayout {
+                offset: 0,
+                stride: 20
+            }]
+        );
+        assert_eq!(layout.size(), 60);
+    }
+
+    #[test]
+    fn nv12_layout() {
+        let layout = FrameLayout::new(Format::Nv12, 5, 3);
+        assert_eq!(layout.plane_height(0), 3);
+        assert_eq!(layout.plane_height(1), 2);
+        assert_eq!(layout.row_size(0), 5);
+        assert_eq!(layout.row_size(1), 6);
+        assert_eq!(
+            layout.planes,
+            vec![
+                PlaneLayout {
+                    offset: 0,
+                    stride: 8
+                },
+                PlaneLayout {
+                    offset: 24,
+                    stride: 8
+                },
+            ]
+        );
+        assert_eq!(layout.size(), 40);
+    }
+
+    #[test]
+    fn bar_bgrx() {
+        let layout = FrameLayout::new(Format::Bgrx, 4, 4);
+        let mut out = vec![0x55; layout.size()];
+        Renderer::new().render(0, &bar(1, 2), &layout, &mut out);
+
+        for (line, row) in out.chunks(16).enumerate() {
+            let expected = if line == 1 || line == 2 { 0xff } else { 0x00 };
+            for pixel in row.chunks(4) {
+                assert_eq!(&pixel[..3], &[expected; 3], "line {}", line);
+            }
+        }
+    }
+
+    #[test]
+    fn bar_nv12() {
+        let layout = FrameLayout::new(Format::Nv12, 4, 4);
+        let mut out = vec![0x55; layout.size()];
+        Renderer::new().render(0, &bar(2, 2), &layout, &mut out);
+
+        let luma = &out[..16];
+        for (line, row) in luma.chunks(4).enumerate() {
+            let expected = if line >= 2 { 235 } else { 16 };
+            assert_eq!(row, &[expected; 4], "line {}", line);
+        }
+        assert!(out[16..].iter().all(|&c| c == 128));
+    }
+
+    #[test]
+    fn bar_clipped_at_bottom() {
+        let layout = FrameLayout::new(Format::Bgrx, 1, 4);
+        let mut out = vec![0; layout.size()];
+        Renderer::new().render(0, &bar(3, 2), &layout, &mut out);
+
+        let lines: Vec<u8> = out.chunks(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(lines, vec![0x00, 0x00, 0x00, 0xff]);
+    }
+
+    #[test]
+    fn bar_confined_to_viewport() {
+        let layout = FrameLayout::new(Format::Bgrx, 160, 140);
+        let viewport = Viewport {
+            x: 40,
+            y: 30,
+            width: 100,
+            height: 100,
+        };
+        let render = |viewport_clear: bool| {
+            let params = PatternParams {
+                background_color: Some(0xff808080),
+                viewport: Some(viewport),
+                viewport_clear,
+                ..bar(20, 10)
+            };
+            let mut out = vec![0x55; layout.size()];
+            Renderer::new().render(0, &params, &layout, &mut out);
+            out
+        };
+
+        for viewport_clear in [true, false] {
+            let out = render(viewport_clear);
+            for (y, row) in out.chunks(160 * 4).enumerate() {
+                for (x, pixel) in row.chunks(4).enumerate() {
+                    let inside = (40..140).contains(&x) && (30..130).contains(&y);
+                    let expected = if !inside {
+                        if viewport_clear {
+                            0x80
+                        } else {
+                            0x55
+                        }
+                    } else if (50..60).contains(&y) {
+                        0xff
+                    } else {
+                        0x80
+                    };
+                    assert_eq!(
+                        &pixel[..3],
+                        &[expected; 3],
+                        "pixel {}x{}, clear {}",
+                        x,
+                        y,
+                        viewport_clear
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stripe_shows_stream_position() {
+        let layout = FrameLayout::new(Format::Bgrx, 276, 2);
+        let mut out = vec![0; layout.size()];
+        let params = PatternParams {
+            stripe_lines: 1,
+            time: 1_000,
+            stream_position: Some((42, 5_000)),
+            ..PatternParams::default()
+        };
+        // Drawn for a held motion position
+        Renderer::new().render(7, &params, &layout, &mut out);
+
+        let luma = out.chunks(4).map(|pixel| pixel[1]).collect::<Vec<_>>();
+        assert_eq!(decode_timestamp_stripe(&luma, 276, 276), Some((5_000, 42)));
+    }
+
+    #[test]
+    fn prepared_caches_match_lazy_ones() {
+        let layout = FrameLayout::new(Format::Bgrx, 48, 32);
+        let params = PatternParams {
+            pattern: Pattern::SiemensStar,
+            background_pattern: Some(Pattern::TestCard),
+            ..PatternParams::default()
+        };
+        let mut prepared = Renderer::new();
+        prepared.prepare(&params, &layout);
+        assert!(prepared.star.is_some());
+        assert!(prepared.test_card.is_some());
+        assert!(prepared.value_noise.is_none());
+
+        let mut out = vec![0; layout.size()];
+        prepared.render(0, &params, &layout, &mut out);
+        let mut lazy = vec![0; layout.size()];
+        Renderer::new().render(0, &params, &layout, &mut lazy);
+        assert_eq!(out, lazy);
+    }
+
+    #[test]
+    fn zone_plate_follows_time() {
+        let layout = FrameLayout::new(Format::Bgrx, 32, 8);
+        let render = |frame_index: u64, time: u64| {
+            let params = PatternParams {
+                pattern: Pattern::ZonePlate,
+                zone_plate: ZonePlate {
+                    kx: 3,
+                    kt: 64,
+                    ..ZonePlate::default()
+                },
+                time,
+                framerate: (30, 1),
+                ..PatternParams::default()
+            };
+            let mut out = vec![0; layout.size()];
+            Renderer::new().render(frame_index, &params, &layout, &mut out);
+            out
+        };
+
+        // Only the timestamp counts, not how many frames were rendered
+        assert_eq!(render(0, 33_333_333), render(7, 33_333_333));
+        assert_ne!(render(0, 0), render(0, 33_333_333));
+        // A full period after 4 frames of 64/256
+        assert_eq!(render(0, 0), render(0, 133_333_333));
+    }
+
+    #[test]
+    fn overlays_rendered_separately() {
+        let layout = FrameLayout::new(Format::Bgrx, 64, 48);
+        let mut params = PatternParams {
+            text: "A".into(),
+            text_position: (10, 20),
+            text_scale: 2,
+            ..PatternParams::default()
+        };
+        let mut renderer = Renderer::new();
+        let mut blended = vec![0; layout.size()];
+        renderer.render(0, &params, &layout, &mut blended);
+
+        params.overlays_in_frame = false;
+        let mut plain = vec![0; layout.size()];
+        renderer.render(0, &params, &layout, &mut plain);
+        assert_ne!(blended, plain);
+
+        let overlay = renderer.render_overlay(0, &params, &layout).unwrap();
+        assert!(overlay.x >= 10 && overlay.y >= 20);
+        assert!(overlay.x + overlay.width <= 64 && overlay.y + overlay.height <= 48);
+        assert_eq!(
+            overlay.data.len(),
+            (overlay.width * overlay.height * 4) as usize
+        );
+
+        // Blending the overlay on top gives the frame with the text drawn in
+        for (row, line) in overlay
+            .data
+            .chunks_exact(overlay.width as usize * 4)
+            .enumerate()
+        {
+            for (column, pixel) in line.chunks_exact(4).enumerate() {
+                let offset = ((overlay.y as usize + row) * 64 + overlay.x as usize + column) * 4;
+                if pixel[3] == 255 {
+                    plain[offset..offset + 3].copy_from_slice(&pixel[..3]);
+                }
+            }
+        }
+        assert_eq!(blended, plain);
+
+        params.text.clear();
+        assert_eq!(renderer.render_overlay(0, &params, &layout), None);
+    }
+}
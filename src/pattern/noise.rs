@@ -0,0 +1,140 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::Rng;
+
+/// Fills the canvas with uniformly distributed gray values, generated from
+/// `seed`
+pub(super) fn snow(canvas: &mut [u32], seed: u64) {
+    let mut rng = Rng::new(seed);
+
+    // Every random number is split into the values of 8 pixels
+    for pixels in canvas.chunks_mut(8) {
+        let bytes = rng.next_u64().to_le_bytes();
+        for (pixel, &value) in pixels.iter_mut().zip(bytes.iter()) {
+            *pixel = 0xff000000 | (value as u32 * 0x010101);
+        }
+    }
+}
+
+// Width of a lattice cell of the lowest octave in pixels, and lattice
+// cells passed per second
+const CELL_SIZE: f32 = 64.0;
+const CELLS_PER_SECOND: f32 = 0.5;
+
+/// Lattice tables of a smooth value noise field in space and time
+#[derive(Debug, Clone)]
+pub(super) struct ValueNoise {
+    seed: u64,
+    // Permutation of 0..256, repeated to avoid wrapping indices
+    perm: [u8; 512],
+    values: [f32; 256],
+}
+
+impl ValueNoise {
+    pub(super) fn new(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let mut perm = [0; 512];
+        let mut values = [0.0; 256];
+
+        for (i, value) in values.iter_mut().enumerate() {
+            perm[i] = i as u8;
+            *value = rng.next_f64() as f32;
+        }
+        for i in (1..256).rev() {
+            perm.swap(i, rng.below(i as u64 + 1) as usize);
+        }
+        let (low, high) = perm.split_at_mut(256);
+        high.copy_from_slice(low);
+
+        ValueNoise { seed, perm, values }
+    }
+
+    pub(super) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Draws the noise field at `time` nanoseconds, summing `octaves`
+    /// octaves, as colors between `from` and `to`
+    pub(super) fn draw(
+        &self,
+        canvas: &mut [u32],
+        width: usize,
+        octaves: u32,
+        time: u64,
+        from: u32,
+        to: u32,
+    ) {
+        let p = &self.perm;
+        let octaves = octaves.clamp(1, 16) as i32;
+        let t = (time as f64 / 1_000_000_000.0) as f32 * CELLS_PER_SECOND;
+        let total: f32 = (0..octaves).map(|o| 0.5f32.powi(o)).sum();
+        let palette: Vec<u32> = (0..256)
+            .map(|level| super::gradient::lerp(from, to, level, 255))
+            .collect();
+
+        // Lattice cell and interpolation weight of every column per octave
+        let columns: Vec<Vec<(usize, f32)>> = (0..octaves)
+            .map(|o| {
+                let frequency = 2f32.powi(o) / CELL_SIZE;
+                (0..width).map(|x| cell(x as f32 * frequency)).collect()
+            })
+            .collect();
+
+        let mut sum = vec![0.0; width];
+        for (y, line) in canvas.chunks_exact_mut(width).enumerate() {
+            sum.fill(0.0);
+            for (o, columns) in columns.iter().enumerate() {
+                let frequency = 2f32.powi(o as i32);
+                let (y0, fy) = cell(y as f32 * frequency / CELL_SIZE);
+                let (t0, ft) = cell(t * frequency);
+
+                // The hash of the time and line parts is the same for the
+                // whole line
+                let row = |t: usize, y: usize| p[p[t & 255] as usize + (y & 255)] as usize;
+                let rows = [
+                    row(t0, y0),
+                    row(t0, y0 + 1),
+                    row(t0 + 1, y0),
+                    row(t0 + 1, y0 + 1),
+                ];
+                let value = |row: usize, x: usize| self.values[p[row + (x & 255)] as usize];
+
+                for (s, &(x0, fx)) in sum.iter_mut().zip(columns.iter()) {
+                    let plane = |top, bottom| {
+                        let a = mix(value(top, x0), value(top, x0 + 1), fx);
+                        let b = mix(value(bottom, x0), value(bottom, x0 + 1), fx);
+                        mix(a, b, fy)
+                    };
+                    *s += mix(plane(rows[0], rows[1]), plane(rows[2], rows[3]), ft) / frequency;
+                }
+            }
+
+            for (pixel, &value) in line.iter_mut().zip(sum.iter()) {
+                *pixel = palette[(value / total * 255.0).round() as usize & 255];
+            }
+        }
+    }
+}
+
+// Lattice cell of a non-negative coordinate and the smoothed position
+// inside of it
+fn cell(pos: f32) -> (usize, f32) {
+    let start = pos.floor();
+    (start as usize, smooth(pos - start))
+}
+
+fn smooth(f: f32) -> f32 {
+    f * f * (3.0 - 2.0 * f)
+}
+
+fn mix(a: f32, b: f32, f: f32) -> f32 {
+    a + (b - a) * f
+}
@@ -0,0 +1,189 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::str::FromStr;
+
+/// Outline of a moving [`Object`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// Spans the whole frame width, `height` lines high
+    Bar,
+    /// `width` x `height` rectangle
+    Rect,
+    /// Circle with a diameter of `width`
+    Ball,
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bar" => Ok(Shape::Bar),
+            "rect" => Ok(Shape::Rect),
+            "ball" => Ok(Shape::Ball),
+            _ => Err(format!("unknown shape {}, expected bar, rect or ball", s)),
+        }
+    }
+}
+
+/// What happens when an [`Object`] reaches the edge of the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    /// Leaves on one side and enters again on the opposite one
+    Wrap,
+    /// Reverses its direction, staying completely inside the frame
+    Bounce,
+}
+
+impl FromStr for Motion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(Motion::Wrap),
+            "bounce" => Ok(Motion::Bounce),
+            _ => Err(format!("unknown motion {}, expected wrap or bounce", s)),
+        }
+    }
+}
+
+/// A shape moving with constant velocity
+///
+/// The position only depends on the frame index, so every frame of a
+/// stream can be rendered independently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Object {
+    pub shape: Shape,
+    pub width: u32,
+    pub height: u32,
+    /// Big-endian ARGB color
+    pub color: u32,
+    /// Position of the top left corner in the first frame
+    pub x: i32,
+    pub y: i32,
+    /// Pixels moved per frame
+    pub velocity_x: i32,
+    pub velocity_y: i32,
+    pub motion: Motion,
+}
+
+impl Default for Object {
+    fn default() -> Self {
+        Object {
+            shape: Shape::Rect,
+            width: 50,
+            height: 50,
+            color: 0xffffffff,
+            x: 0,
+            y: 0,
+            velocity_x: 0,
+            velocity_y: 0,
+            motion: Motion::Wrap,
+        }
+    }
+}
+
+impl Object {
+    // Size of the covered area in a frame of the given width
+    fn extent(&self, frame_width: usize) -> (usize, usize) {
+        match self.shape {
+            Shape::Bar => (frame_width, self.height as usize),
+            Shape::Rect => (self.width as usize, self.height as usize),
+            Shape::Ball => (self.width as usize, self.width as usize),
+        }
+    }
+
+    /// Top left corner in frame `frame_index` of a `width` x `height` frame
+    pub fn position(&self, frame_index: u64, width: usize, height: usize) -> (i64, i64) {
+        let (w, h) = self.extent(width);
+        let x = if self.shape == Shape::Bar {
+            0
+        } else {
+            self.motion
+                .place(self.x as i64, self.velocity_x as i64, frame_index, width, w)
+        };
+        let y = self.motion.place(
+            self.y as i64,
+            self.velocity_y as i64,
+            frame_index,
+            height,
+            h,
+        );
+
+        (x, y)
+    }
+
+    pub(super) fn draw(&self, canvas: &mut [u32], width: usize, height: usize, frame_index: u64) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let (w, h) = self.extent(width);
+        let (x0, y0) = self.position(frame_index, width, height);
+        let wrap = self.motion == Motion::Wrap;
+
+        // Pixel centers of a ball are tested in doubled coordinates to stay
+        // in integers
+        let d = w as i64;
+        for dy in 0..h.min(height) as i64 {
+            let y = match fold(y0 + dy, height, wrap) {
+                Some(y) => y,
+                None => continue,
+            };
+            for dx in 0..w.min(width) as i64 {
+                if self.shape == Shape::Ball {
+                    let (cx, cy) = (2 * dx + 1 - d, 2 * dy + 1 - d);
+                    if cx * cx + cy * cy > d * d {
+                        continue;
+                    }
+                }
+                if let Some(x) = fold(x0 + dx, width, wrap) {
+                    canvas[y * width + x] = self.color;
+                }
+            }
+        }
+    }
+}
+
+impl Motion {
+    // Coordinate along one axis after `frame_index` frames, for an object
+    // `size` pixels long on an axis `length` pixels long
+    fn place(self, start: i64, velocity: i64, frame_index: u64, length: usize, size: usize) -> i64 {
+        let pos = start + velocity * frame_index as i64;
+
+        match self {
+            Motion::Wrap => pos.rem_euclid(length.max(1) as i64),
+            Motion::Bounce => {
+                let range = length.saturating_sub(size) as i64;
+                if range == 0 {
+                    return 0;
+                }
+                let pos = pos.rem_euclid(2 * range);
+                if pos > range {
+                    2 * range - pos
+                } else {
+                    pos
+                }
+            }
+        }
+    }
+}
+
+// Maps a coordinate into the frame, wrapping around or dropping it
+fn fold(pos: i64, length: usize, wrap: bool) -> Option<usize> {
+    if wrap {
+        Some(pos.rem_euclid(length as i64) as usize)
+    } else if pos >= 0 && pos < length as i64 {
+        Some(pos as usize)
+    } else {
+        None
+    }
+}
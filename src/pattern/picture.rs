@@ -0,0 +1,55 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Decoded still image the image pattern scrolls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+}
+
+impl Image {
+    /// Wraps big-endian ARGB `pixels`, line by line without padding. Returns
+    /// `None` if their number doesn't match `width` x `height` or the image
+    /// is empty.
+    pub fn new(width: usize, height: usize, pixels: Vec<u32>) -> Option<Self> {
+        if width == 0 || height == 0 || width.checked_mul(height) != Some(pixels.len()) {
+            return None;
+        }
+
+        Some(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Tiles `image` over the canvas, moved down by `offset` lines and
+/// wrapping around at the bottom
+pub(super) fn scroll(canvas: &mut [u32], width: usize, image: &Image, offset: u32) {
+    let shift = offset as usize % image.height;
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        let line = (y + image.height - shift) % image.height;
+        let src = &image.pixels[line * image.width..(line + 1) * image.width];
+        for chunk in row.chunks_mut(image.width) {
+            chunk.copy_from_slice(&src[..chunk.len()]);
+        }
+    }
+}
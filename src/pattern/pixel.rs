@@ -0,0 +1,27 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Column and line of the single lit pixel of the moving pixel pattern in
+/// frame `frame_index` of a `width` x `height` frame
+///
+/// The pixel moves through the frame in raster order, one pixel per frame,
+/// and wraps around to the top left corner after the last one.
+pub fn moving_pixel(frame_index: u64, width: usize, height: usize) -> (usize, usize) {
+    let pixels = (width as u64 * height as u64).max(1);
+    let index = (frame_index % pixels) as usize;
+    (index % width.max(1), index / width.max(1))
+}
+
+/// Column of the edge of the moving edge pattern in frame `frame_index`,
+/// moving `speed` pixels per frame to the right, or to the left if
+/// negative, in a frame `width` pixels wide and wrapping around at the edges
+pub fn moving_edge(frame_index: u64, speed: i32, width: usize) -> usize {
+    (frame_index as i128 * speed as i128).rem_euclid(width.max(1) as i128) as usize
+}
@@ -0,0 +1,69 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::gradient::lerp;
+
+// Every term of the plasma is a sine along one direction, with the number of
+// periods across the frame in 1/16 and the speed in 1/256 of a period per
+// second
+const TERMS: [(u64, u64); 4] = [(24, 64), (16, 90), (32, 51), (21, 77)];
+// Speed the palette rotates with, in 1/256 of a period per second
+const PALETTE_SPEED: u64 = 26;
+
+// Phase in 1/256 of a period after `time` nanoseconds at `speed`
+fn time_phase(time: u64, speed: u64) -> usize {
+    (time as u128 * speed as u128 / 1_000_000_000) as usize
+}
+
+// Sine values along an axis `length` pixels long, for `periods` in 1/16
+// across the frame
+fn wave(sine: &[u8], length: usize, frame_length: usize, periods: u64, phase: usize) -> Vec<u16> {
+    let frame_length = frame_length.max(1) as u64;
+    (0..length)
+        .map(|pos| {
+            let index = (pos as u64 * periods * 16 / frame_length) as usize + phase;
+            sine[index & 0xff] as u16
+        })
+        .collect()
+}
+
+/// Draws a plasma at stream `time` in nanoseconds, the sum of four moving
+/// sines along the columns, lines and both diagonals, mapped to a palette
+/// cycling from `from` to `to` and back. `sine` is a table from
+/// [`super::zoneplate::sine_table`].
+pub(super) fn plasma(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    time: u64,
+    sine: &[u8],
+    (from, to): (u32, u32),
+) {
+    let phase = |term: usize| time_phase(time, TERMS[term].1);
+    let columns = wave(sine, width, width, TERMS[0].0, phase(0));
+    let lines = wave(sine, height, height, TERMS[1].0, phase(1));
+    let diagonal = wave(sine, width + height, width + height, TERMS[2].0, phase(2));
+    let anti_diagonal = wave(sine, width + height, width + height, TERMS[3].0, phase(3));
+
+    let shift = time_phase(time, PALETTE_SPEED);
+    let palette: Vec<u32> = (0..256)
+        .map(|i| lerp(from, to, sine[(i + shift) & 0xff] as u32, 255))
+        .collect();
+
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        let line = lines[y];
+        let diagonal = &diagonal[y..y + width];
+        let anti_diagonal = &anti_diagonal[height - 1 - y..height - 1 - y + width];
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let sum = columns[x] + line + diagonal[x] + anti_diagonal[x];
+            *pixel = palette[(sum >> 2) as usize];
+        }
+    }
+}
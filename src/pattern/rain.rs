@@ -0,0 +1,71 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::Rng;
+
+/// Head of a short vertical streak falling down the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Particle {
+    pub x: u32,
+    pub y: u32,
+    /// Lines moved per frame
+    pub speed: u32,
+}
+
+impl Particle {
+    /// Creates `count` particles at random positions inside a `width` x
+    /// `height` frame with random speeds between `min_speed` and `max_speed`
+    pub fn spawn(
+        rng: &mut Rng,
+        count: u32,
+        (width, height): (u32, u32),
+        min_speed: u32,
+        max_speed: u32,
+    ) -> Vec<Particle> {
+        let (min_speed, max_speed) = (min_speed.min(max_speed), min_speed.max(max_speed));
+
+        (0..count)
+            .map(|_| Particle {
+                x: rng.below(width as u64) as u32,
+                y: rng.below(height as u64) as u32,
+                speed: min_speed + rng.below((max_speed - min_speed) as u64 + 1) as u32,
+            })
+            .collect()
+    }
+
+    /// Moves the particle down by one frame, leaving at the bottom and
+    /// entering again at the top of a frame `height` lines high
+    pub fn advance(&mut self, height: u32) {
+        self.y = ((self.y as u64 + self.speed as u64) % height.max(1) as u64) as u32;
+    }
+}
+
+/// Draws every particle as a streak of `length` lines ending at its
+/// position, clipped at the top of the frame
+pub(super) fn draw(
+    canvas: &mut [u32],
+    width: usize,
+    particles: &[Particle],
+    length: u32,
+    color: u32,
+) {
+    let height = canvas.len() / width.max(1);
+
+    for particle in particles {
+        let (x, y) = (particle.x as usize, particle.y as usize);
+        if x >= width || y >= height {
+            continue;
+        }
+        let top = (y + 1).saturating_sub(length as usize);
+        for line in top..=y {
+            canvas[line * width + x] = color;
+        }
+    }
+}
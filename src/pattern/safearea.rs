@@ -0,0 +1,139 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+const GRAY: u32 = 0xff808080;
+
+// Fills the rectangle at `x`, `y` of `w` x `h` pixels, clipped at the
+// canvas edges
+fn fill_rect(
+    canvas: &mut [u32],
+    width: usize,
+    (x, y): (usize, usize),
+    (w, h): (usize, usize),
+    color: u32,
+) {
+    let height = canvas.len() / width;
+    let (end_x, end_y) = ((x + w).min(width), (y + h).min(height));
+    if x >= end_x {
+        return;
+    }
+    for row in canvas[y.min(end_y) * width..end_y * width].chunks_exact_mut(width) {
+        row[x..end_x].fill(color);
+    }
+}
+
+// Draws the outline of the centered rectangle covering `fraction` of the
+// width and height. The insets are the same on opposite sides, so the
+// rectangle is symmetric.
+fn safe_rect(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    fraction: f64,
+    line: usize,
+    color: u32,
+) {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let inset_x = (width as f64 * (1.0 - fraction) / 2.0).round() as usize;
+    let inset_y = (height as f64 * (1.0 - fraction) / 2.0).round() as usize;
+    let (w, h) = (width - 2 * inset_x, height - 2 * inset_y);
+    if w == 0 || h == 0 {
+        return;
+    }
+    let line_x = line.min(w);
+    let line_y = line.min(h);
+
+    fill_rect(canvas, width, (inset_x, inset_y), (w, line_y), color);
+    fill_rect(
+        canvas,
+        width,
+        (inset_x, height - inset_y - line_y),
+        (w, line_y),
+        color,
+    );
+    fill_rect(canvas, width, (inset_x, inset_y), (line_x, h), color);
+    fill_rect(
+        canvas,
+        width,
+        (width - inset_x - line_x, inset_y),
+        (line_x, h),
+        color,
+    );
+}
+
+/// Draws a `line` pixels thick border in `color` along the edges of the
+/// canvas, only touching the border pixels. A border thicker than half the
+/// canvas fills all of it.
+pub(super) fn border(canvas: &mut [u32], width: usize, height: usize, line: usize, color: u32) {
+    let top = line.min(height);
+    let bottom = line.min(height - top);
+    let left = line.min(width);
+    let right = line.min(width - left);
+
+    fill_rect(canvas, width, (0, 0), (width, top), color);
+    fill_rect(canvas, width, (0, height - bottom), (width, bottom), color);
+    let rows = height - top - bottom;
+    fill_rect(canvas, width, (0, top), (left, rows), color);
+    fill_rect(canvas, width, (width - right, top), (right, rows), color);
+}
+
+/// Draws the action safe and title safe rectangles covering `action` and
+/// `title` of the width and height, L shaped markers in the frame corners
+/// and a cross in the center, all in `color` on mid gray. Lines are one
+/// pixel thick per 480 lines of height, at least one.
+pub(super) fn safe_area(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    action: f64,
+    title: f64,
+    color: u32,
+) {
+    canvas.fill(GRAY);
+
+    let line = (height / 480).max(1);
+    safe_rect(canvas, width, height, action, line, color);
+    safe_rect(canvas, width, height, title, line, color);
+
+    // Corner markers, every arm a twentieth of the shorter side long
+    let arm = (width.min(height) / 20).max(line);
+    let (line_x, line_y) = (line.min(width), line.min(height));
+    let (arm_x, arm_y) = (arm.min(width), arm.min(height));
+    for &(left, top) in &[(true, true), (false, true), (true, false), (false, false)] {
+        let x = if left { 0 } else { width - arm_x };
+        let y = if top { 0 } else { height - line_y };
+        fill_rect(canvas, width, (x, y), (arm_x, line_y), color);
+        let x = if left { 0 } else { width - line_x };
+        let y = if top { 0 } else { height - arm_y };
+        fill_rect(canvas, width, (x, y), (line_x, arm_y), color);
+    }
+
+    // Center cross with arms about a tenth of the shorter side long. The
+    // arms are made as odd or even as the frame size so they are exactly
+    // centered, the lines are half a pixel up and to the left if they can't.
+    let arm = (width.min(height) / 10).max(line);
+    let (arm_x, arm_y) = (arm.min(width), arm.min(height));
+    let arm_x = arm_x + ((width - arm_x) & 1);
+    let arm_y = arm_y + ((height - arm_y) & 1);
+    fill_rect(
+        canvas,
+        width,
+        ((width - arm_x) / 2, (height - line_y) / 2),
+        (arm_x, line_y),
+        color,
+    );
+    fill_rect(
+        canvas,
+        width,
+        ((width - line_x) / 2, (height - arm_y) / 2),
+        (line_x, arm_y),
+        color,
+    );
+}
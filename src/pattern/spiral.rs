@@ -0,0 +1,70 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::f64::consts::PI;
+
+/// Angle and distance from the center of every pixel of a frame, in 1/65536
+/// of a turn and 1/16 pixel
+#[derive(Debug)]
+pub(super) struct PolarMap {
+    width: usize,
+    height: usize,
+    angles: Vec<u16>,
+    radii: Vec<u32>,
+}
+
+impl PolarMap {
+    pub(super) fn new(width: usize, height: usize) -> Self {
+        let mut angles = Vec::with_capacity(width * height);
+        let mut radii = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let dy = y as f64 + 0.5 - height as f64 / 2.0;
+            for x in 0..width {
+                let dx = x as f64 + 0.5 - width as f64 / 2.0;
+                let turns = dy.atan2(dx) / (2.0 * PI);
+                angles.push((turns.rem_euclid(1.0) * 65536.0) as u32 as u16);
+                radii.push((dx.hypot(dy) * 16.0) as u32);
+            }
+        }
+
+        PolarMap {
+            width,
+            height,
+            angles,
+            radii,
+        }
+    }
+
+    pub(super) fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Draws an Archimedean spiral of `arms` arms in `color`, alternating
+    /// with the existing canvas content and turned `rotation` degrees
+    /// clockwise. Every arm moves outwards by a sixth of the shorter frame
+    /// side per turn.
+    pub(super) fn spiral(&self, canvas: &mut [u32], arms: u32, rotation: f64, color: u32) {
+        let pitch = (self.width.min(self.height) / 6).max(1) as u64;
+        // Turns per 1/16 pixel of radius, in 1/65536 of a turn and 16 more
+        // bits of precision
+        let per_radius = ((1u64 << 32) / (16 * pitch)) as u32;
+        let rotation = ((rotation / 360.0).rem_euclid(1.0) * 65536.0) as u32;
+
+        for ((pixel, &angle), &radius) in canvas.iter_mut().zip(&self.angles).zip(&self.radii) {
+            let phase = (angle as u32)
+                .wrapping_mul(arms)
+                .wrapping_sub(radius.wrapping_mul(per_radius) >> 16)
+                .wrapping_sub(rotation.wrapping_mul(arms));
+            if phase & 0x8000 == 0 {
+                *pixel = color;
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::f64::consts::PI;
+
+use super::gradient::lerp;
+
+// Samples per axis of pixels close to the center
+const SUPERSAMPLING: usize = 4;
+
+/// Draws a Siemens star with `spokes` sectors of `color` alternating with
+/// sectors of `background`, which also surrounds the star. The diameter is
+/// `radius` times the shorter frame side.
+pub(super) fn siemens_star(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    spokes: u32,
+    radius: f64,
+    color: u32,
+    background: u32,
+) {
+    let spokes = spokes.max(1) as f64;
+    let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+    let radius = radius.clamp(0.0, 1.0) * width.min(height) as f64 / 2.0;
+    // Sectors get narrower than a pixel inside of this radius
+    let inner = spokes / PI;
+
+    let inside = |x: f64, y: f64| {
+        let (dx, dy) = (x - cx, y - cy);
+        if dx * dx + dy * dy > radius * radius {
+            return false;
+        }
+        let angle = dy.atan2(dx) + PI;
+        (angle * spokes / PI) as u64 & 1 == 0
+    };
+
+    for (y, line) in canvas.chunks_exact_mut(width).enumerate() {
+        for (x, pixel) in line.iter_mut().enumerate() {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            let (dx, dy) = (px - cx, py - cy);
+
+            *pixel = if dx * dx + dy * dy > inner * inner {
+                if inside(px, py) {
+                    color
+                } else {
+                    background
+                }
+            } else {
+                let step = 1.0 / SUPERSAMPLING as f64;
+                let mut hits = 0;
+                for sy in 0..SUPERSAMPLING {
+                    for sx in 0..SUPERSAMPLING {
+                        let sample_x = x as f64 + (sx as f64 + 0.5) * step;
+                        let sample_y = y as f64 + (sy as f64 + 0.5) * step;
+                        hits += inside(sample_x, sample_y) as u32;
+                    }
+                }
+                lerp(
+                    background,
+                    color,
+                    hits,
+                    (SUPERSAMPLING * SUPERSAMPLING) as u32,
+                )
+            };
+        }
+    }
+}
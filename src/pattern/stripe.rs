@@ -0,0 +1,141 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// The stripe is a row of equally wide blocks, black for a set bit: a white
+// and a black marker block, the time and frame number as big-endian u64s
+// and their XOR parity byte, all most significant bit first. Pixels right of
+// the last block are black.
+const BLOCKS: usize = 2 + 128 + 8;
+
+const BLACK: u32 = 0xff000000;
+const WHITE: u32 = 0xffffffff;
+
+fn payload(time: u64, frame: u64) -> [u8; 17] {
+    let mut payload = [0; 17];
+    payload[..8].copy_from_slice(&time.to_be_bytes());
+    payload[8..16].copy_from_slice(&frame.to_be_bytes());
+    payload[16] = payload[..16].iter().fold(0, |parity, byte| parity ^ byte);
+    payload
+}
+
+/// Draws the stripe of `time` and `frame` over the top `lines` lines of
+/// the canvas. Every block is `width / 138` pixels wide, at least one.
+pub(super) fn draw(canvas: &mut [u32], width: usize, lines: u32, time: u64, frame: u64) {
+    let block = (width / BLOCKS).max(1);
+    let payload = payload(time, frame);
+
+    let lines = (lines as usize).min(canvas.len() / width);
+    for row in canvas[..lines * width].chunks_exact_mut(width) {
+        row.fill(BLACK);
+        for (i, pixels) in row.chunks_mut(block).take(BLOCKS).enumerate() {
+            let black = match i {
+                0 => false,
+                1 => true,
+                _ => payload[(i - 2) / 8] & (0x80 >> ((i - 2) % 8)) != 0,
+            };
+            if !black {
+                pixels.fill(WHITE);
+            }
+        }
+    }
+}
+
+/// Reads the time and frame number from a timestamp stripe in 8 bit luma
+/// samples
+///
+/// `luma` holds lines of `stride` bytes of a frame `width` pixels wide, only
+/// the first line is read. Every block is sampled at its center and compared
+/// against the mean of the two marker blocks. Returns `None` if the markers
+/// are missing or the parity doesn't match.
+pub fn decode_timestamp_stripe(luma: &[u8], stride: usize, width: usize) -> Option<(u64, u64)> {
+    let line = luma.get(..width.min(stride))?;
+    let block = (width / BLOCKS).max(1);
+    let sample = |i: usize| line.get(i * block + block / 2).copied();
+
+    let (white, black) = (sample(0)?, sample(1)?);
+    if white <= black {
+        return None;
+    }
+    let threshold = (white as u16 + black as u16).div_ceil(2) as u8;
+
+    let mut payload = [0u8; 17];
+    for bit in 0..BLOCKS - 2 {
+        if sample(bit + 2)? < threshold {
+            payload[bit / 8] |= 0x80 >> (bit % 8);
+        }
+    }
+
+    let parity = payload[..16].iter().fold(0, |parity, byte| parity ^ byte);
+    if parity != payload[16] {
+        return None;
+    }
+
+    let mut time = [0; 8];
+    let mut frame = [0; 8];
+    time.copy_from_slice(&payload[..8]);
+    frame.copy_from_slice(&payload[8..16]);
+
+    Some((u64::from_be_bytes(time), u64::from_be_bytes(frame)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Format, FrameLayout, PatternParams, Renderer, Rng};
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut rng = Rng::new(1);
+
+        for &(width, height) in &[(138, 16), (320, 240), (1000, 562), (1920, 1080)] {
+            let layout = FrameLayout::new(Format::Nv12, width, height);
+            let mut out = vec![0; layout.size()];
+            let mut renderer = Renderer::new();
+
+            for _ in 0..16 {
+                let (time, frame) = (rng.next_u64(), rng.next_u64());
+                let params = PatternParams {
+                    stripe_lines: 4,
+                    time,
+                    ..PatternParams::default()
+                };
+                renderer.render(frame, &params, &layout, &mut out);
+
+                let stride = layout.planes[0].stride;
+                assert_eq!(
+                    decode_timestamp_stripe(&out, stride, width as usize),
+                    Some((time, frame)),
+                    "{}x{}",
+                    width,
+                    height
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parity_mismatch() {
+        let width = 276;
+        let mut canvas = vec![0; width * 2];
+        draw(&mut canvas, width, 1, 1234, 5678);
+
+        let mut luma = canvas.iter().map(|&pixel| pixel as u8).collect::<Vec<_>>();
+        assert_eq!(
+            decode_timestamp_stripe(&luma, width, width),
+            Some((1234, 5678))
+        );
+
+        // Flip the last bit of the frame number
+        let bit = 2 + 127;
+        luma[bit * 2] = !luma[bit * 2];
+        luma[bit * 2 + 1] = !luma[bit * 2 + 1];
+        assert_eq!(decode_timestamp_stripe(&luma, width, width), None);
+    }
+}
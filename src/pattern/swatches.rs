@@ -0,0 +1,54 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// sRGB reference colors of the 24 ColorChecker patches as big-endian ARGB,
+/// row by row as drawn by the color checker pattern
+///
+/// These are the values published by X-Rite for the ColorChecker Classic,
+/// rounded to 8 bits.
+pub const COLOR_CHECKER: [u32; 24] = [
+    // Dark skin, light skin, blue sky, foliage, blue flower, bluish green
+    0xff735244, 0xffc29682, 0xff627a9d, 0xff576c43, 0xff8580b1, 0xff67bdaa,
+    // Orange, purplish blue, moderate red, purple, yellow green, orange
+    // yellow
+    0xffd67e2c, 0xff505ba6, 0xffc15a63, 0xff5e3c6c, 0xff9dbc40, 0xffe0a32e,
+    // Blue, green, red, yellow, magenta, cyan
+    0xff383d96, 0xff469449, 0xffaf363c, 0xffe7c71f, 0xffbb5695, 0xff0885a1,
+    // White, neutral 8, neutral 6.5, neutral 5, neutral 3.5, black
+    0xfff3f3f2, 0xffc8c8c8, 0xffa0a0a0, 0xff7a7a79, 0xff555555, 0xff343434,
+];
+
+const COLUMNS: usize = 6;
+const ROWS: usize = 4;
+
+/// Draws the [`COLOR_CHECKER`] colors as a 6 x 4 grid of equally sized
+/// swatches, centered in the canvas. Swatches are separated by black lines
+/// 1/16 of their size thick, at least one pixel, and the area around the
+/// grid is black.
+pub(super) fn color_checker(canvas: &mut [u32], width: usize, height: usize) {
+    canvas.fill(0xff000000);
+
+    let line = ((width / COLUMNS).min(height / ROWS) / 16).max(1);
+    let pitch = (width.saturating_sub(line) / COLUMNS).min(height.saturating_sub(line) / ROWS);
+    if pitch <= line {
+        return;
+    }
+    let left = (width - (COLUMNS * pitch + line)) / 2 + line;
+    let top = (height - (ROWS * pitch + line)) / 2 + line;
+    let size = pitch - line;
+
+    for (i, &color) in COLOR_CHECKER.iter().enumerate() {
+        let x = left + i % COLUMNS * pitch;
+        let y = top + i / COLUMNS * pitch;
+        for row in canvas[y * width..(y + size) * width].chunks_exact_mut(width) {
+            row[x..x + size].fill(color);
+        }
+    }
+}
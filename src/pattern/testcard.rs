@@ -0,0 +1,93 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Composite monitor test card in the spirit of the PM5544: a white
+// crosshatch on gray with a centered circle containing color bars, gray
+// steps, a center cross, a resolution wedge and a black and white strip.
+// All sizes are relative to the frame height, so the circle stays round
+// with square pixels.
+
+const BACKGROUND: u32 = 0xff404040;
+const WHITE: u32 = 0xffffffff;
+const BLACK: u32 = 0xff000000;
+const BARS: [u32; 8] = [
+    0xffbfbfbf, 0xffbfbf00, 0xff00bfbf, 0xff00bf00, 0xffbf00bf, 0xffbf0000, 0xff0000bf, 0xff000000,
+];
+const STEPS: usize = 6;
+// Periods of the line gratings of the wedge in pixels, coarsest first
+const WEDGE: [usize; 5] = [8, 6, 4, 3, 2];
+
+/// Draws the test card into a `width` x `height` canvas
+pub(super) fn test_card(canvas: &mut [u32], width: usize, height: usize) {
+    let spacing = (height / 14).max(2);
+    let line_width = (height / 288).max(1);
+    let radius = (height * 6 / 14).min(width / 2).max(1);
+    let (cx, cy) = (width / 2, height / 2);
+    let left = cx.saturating_sub(radius);
+    let top = cy.saturating_sub(radius);
+
+    // Grid lines are centered on the frame center, the circle is tested in
+    // doubled coordinates around the exact center
+    let on_line = |pos: usize, center: usize| {
+        (pos as i64 - center as i64 + (line_width / 2) as i64).rem_euclid(spacing as i64)
+            < line_width as i64
+    };
+    let outer = (2 * radius * 2 * radius) as i64;
+    let inner = (2 * (radius - line_width.min(radius)) as i64).pow(2);
+
+    for (y, row) in canvas.chunks_exact_mut(width).enumerate() {
+        let dy = 2 * y as i64 + 1 - height as i64;
+        let band = (y.saturating_sub(top)) * 10 / (2 * radius);
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let dx = 2 * x as i64 + 1 - width as i64;
+            let distance = dx * dx + dy * dy;
+            *pixel = if distance > outer {
+                if on_line(x, cx) || on_line(y, cy) {
+                    WHITE
+                } else {
+                    BACKGROUND
+                }
+            } else if distance > inner {
+                WHITE
+            } else {
+                let column = x.saturating_sub(left);
+                match band {
+                    0 | 1 => BARS[(column * BARS.len() / (2 * radius)).min(BARS.len() - 1)],
+                    2 | 3 => {
+                        let step = (column * STEPS / (2 * radius)).min(STEPS - 1);
+                        let level = (step * 255 / (STEPS - 1)) as u32;
+                        BLACK | level << 16 | level << 8 | level
+                    }
+                    4 | 5 => {
+                        let cross = |pos: usize, center: usize| {
+                            (center..center + line_width).contains(&(pos + line_width / 2))
+                        };
+                        if cross(x, cx) || cross(y, cy) {
+                            WHITE
+                        } else {
+                            BLACK
+                        }
+                    }
+                    6 | 7 => {
+                        let period =
+                            WEDGE[(column * WEDGE.len() / (2 * radius)).min(WEDGE.len() - 1)];
+                        if column % period < period / 2 {
+                            WHITE
+                        } else {
+                            BLACK
+                        }
+                    }
+                    _ if x < cx => WHITE,
+                    _ => BLACK,
+                }
+            };
+        }
+    }
+}
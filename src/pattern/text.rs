@@ -0,0 +1,172 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Built-in 5x8 bitmap font covering printable ASCII for the text overlay.
+// Every glyph is one byte per line, bit 4 is the leftmost pixel. The last
+// line is only used by descenders.
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 8;
+// Glyphs are one font pixel apart
+const ADVANCE: usize = GLYPH_WIDTH + 1;
+
+// Characters ' ' to '~'
+#[rustfmt::skip]
+const GLYPHS: [[u8; GLYPH_HEIGHT]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04, 0x00], // !
+    [0x0a, 0x0a, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0a, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x0a, 0x00], // #
+    [0x04, 0x0f, 0x14, 0x0e, 0x05, 0x1e, 0x04, 0x00], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03, 0x00], // %
+    [0x0c, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0d, 0x00], // &
+    [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02, 0x00], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08, 0x00], // )
+    [0x00, 0x04, 0x15, 0x0e, 0x15, 0x04, 0x00, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08, 0x00], // ,
+    [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c, 0x00], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00, 0x00], // /
+    [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e, 0x00], // 0
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e, 0x00], // 1
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f, 0x00], // 2
+    [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e, 0x00], // 3
+    [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02, 0x00], // 4
+    [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e, 0x00], // 5
+    [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e, 0x00], // 6
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08, 0x00], // 7
+    [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e, 0x00], // 8
+    [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c, 0x00], // 9
+    [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00, 0x00], // :
+    [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x04, 0x08, 0x00], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02, 0x00], // <
+    [0x00, 0x00, 0x1f, 0x00, 0x1f, 0x00, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08, 0x00], // >
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04, 0x00], // ?
+    [0x0e, 0x11, 0x01, 0x0d, 0x15, 0x15, 0x0e, 0x00], // @
+    [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11, 0x00], // A
+    [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e, 0x00], // B
+    [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e, 0x00], // C
+    [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c, 0x00], // D
+    [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f, 0x00], // E
+    [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10, 0x00], // F
+    [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f, 0x00], // G
+    [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11, 0x00], // H
+    [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e, 0x00], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c, 0x00], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11, 0x00], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f, 0x00], // L
+    [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11, 0x00], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11, 0x00], // N
+    [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e, 0x00], // O
+    [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10, 0x00], // P
+    [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d, 0x00], // Q
+    [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11, 0x00], // R
+    [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e, 0x00], // S
+    [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e, 0x00], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04, 0x00], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a, 0x00], // W
+    [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11, 0x00], // X
+    [0x11, 0x11, 0x0a, 0x04, 0x04, 0x04, 0x04, 0x00], // Y
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f, 0x00], // Z
+    [0x0e, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0e, 0x00], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00, 0x00], // \
+    [0x0e, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0e, 0x00], // ]
+    [0x04, 0x0a, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f, 0x00], // _
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00], // `
+    [0x00, 0x00, 0x0e, 0x01, 0x0f, 0x11, 0x0f, 0x00], // a
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1e, 0x00], // b
+    [0x00, 0x00, 0x0e, 0x10, 0x10, 0x11, 0x0e, 0x00], // c
+    [0x01, 0x01, 0x0d, 0x13, 0x11, 0x11, 0x0f, 0x00], // d
+    [0x00, 0x00, 0x0e, 0x11, 0x1f, 0x10, 0x0e, 0x00], // e
+    [0x06, 0x09, 0x08, 0x1c, 0x08, 0x08, 0x08, 0x00], // f
+    [0x00, 0x00, 0x0f, 0x11, 0x11, 0x0f, 0x01, 0x0e], // g
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // h
+    [0x04, 0x00, 0x0c, 0x04, 0x04, 0x04, 0x0e, 0x00], // i
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x02, 0x12, 0x0c], // j
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12, 0x00], // k
+    [0x0c, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e, 0x00], // l
+    [0x00, 0x00, 0x1a, 0x15, 0x15, 0x11, 0x11, 0x00], // m
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11, 0x00], // n
+    [0x00, 0x00, 0x0e, 0x11, 0x11, 0x11, 0x0e, 0x00], // o
+    [0x00, 0x00, 0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10], // p
+    [0x00, 0x00, 0x0f, 0x11, 0x11, 0x0f, 0x01, 0x01], // q
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10, 0x00], // r
+    [0x00, 0x00, 0x0f, 0x10, 0x0e, 0x01, 0x1e, 0x00], // s
+    [0x08, 0x08, 0x1c, 0x08, 0x08, 0x09, 0x06, 0x00], // t
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0d, 0x00], // u
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0a, 0x04, 0x00], // v
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0a, 0x00], // w
+    [0x00, 0x00, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x00], // x
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0f, 0x01, 0x0e], // y
+    [0x00, 0x00, 0x1f, 0x02, 0x04, 0x08, 0x1f, 0x00], // z
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02, 0x00], // {
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00], // |
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08, 0x00], // }
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00, 0x00], // ~
+];
+
+/// Draws `text` in `color` on a `background` box at `x`, `y` in the canvas,
+/// every font pixel `scale` pixels wide. The box leaves one font pixel
+/// around the text and is clipped at the frame edges. Characters outside
+/// of printable ASCII are left blank.
+pub(super) fn text(
+    canvas: &mut [u32],
+    width: usize,
+    height: usize,
+    (x, y): (u32, u32),
+    scale: u32,
+    text: &str,
+    (color, background): (u32, u32),
+) {
+    let scale = scale.max(1) as usize;
+    let (x0, y0) = (x as usize, y as usize);
+    let box_width = (text.chars().count() * ADVANCE + 1) * scale;
+    let box_height = (GLYPH_HEIGHT + 2) * scale;
+    let right = x0.saturating_add(box_width).min(width);
+    let bottom = y0.saturating_add(box_height).min(height);
+    if x0 >= right || y0 >= bottom {
+        return;
+    }
+
+    for row in canvas[y0 * width..bottom * width].chunks_exact_mut(width) {
+        row[x0..right].fill(background);
+    }
+
+    for (i, c) in text.chars().enumerate() {
+        let left = x0 + (1 + i * ADVANCE) * scale;
+        if left >= right {
+            break;
+        }
+        let glyph = match c {
+            ' '..='~' => &GLYPHS[c as usize - ' ' as usize],
+            _ => continue,
+        };
+        for (line, bits) in glyph.iter().enumerate() {
+            let top = y0 + (1 + line) * scale;
+            for bit in 0..GLYPH_WIDTH {
+                if bits & (0x10 >> bit) == 0 {
+                    continue;
+                }
+                let start = left + bit * scale;
+                let end = (start + scale).min(right);
+                for y in top..(top + scale).min(bottom) {
+                    if start < end {
+                        canvas[y * width + start..y * width + end].fill(color);
+                    }
+                }
+            }
+        }
+    }
+}
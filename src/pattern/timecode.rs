@@ -0,0 +1,40 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+const SECOND: u128 = 1_000_000_000;
+
+/// Non-drop-frame timecode HH:MM:SS:FF of the frame at stream `time` in
+/// nanoseconds, for a stream with `framerate` as numerator and denominator
+///
+/// The frame number is computed from the exact fraction and counted in
+/// seconds of the rate rounded up, e.g. 30 frames for 30000/1001, so the
+/// frames field never skips or repeats. Hours wrap around after a day.
+/// Without a frame rate the frames field stays 0.
+pub fn timecode(time: u64, framerate: (u32, u32)) -> String {
+    let (numer, denom) = (framerate.0 as u128, framerate.1 as u128);
+    let (seconds, frames) = if numer > 0 && denom > 0 {
+        // Timestamps of frames are rounded down, so round to the nearest
+        // frame to get back the frame number
+        let period = denom * SECOND;
+        let frame = (time as u128 * numer + period / 2) / period;
+        let rate = numer.div_ceil(denom);
+        (frame / rate, frame % rate)
+    } else {
+        (time as u128 / SECOND, 0)
+    };
+
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+        frames
+    )
+}
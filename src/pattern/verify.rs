@@ -0,0 +1,71 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{Format, FrameLayout};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// Iterates over the bytes of a plane that carry picture content, i.e.
+// skipping stride padding and bytes without meaning in the format (the x of
+// BGRx)
+fn visible_bytes<'a>(
+    layout: &'a FrameLayout,
+    plane: usize,
+    data: &'a [u8],
+) -> impl Iterator<Item = u8> + 'a {
+    let stride = layout.planes[plane].stride;
+    let row_size = layout.row_size(plane);
+    let bytes_per_group = match layout.format {
+        Format::Bgrx | Format::Bgra => 4,
+        Format::Nv12 => 1,
+    };
+    let significant = match layout.format {
+        Format::Bgrx => 3,
+        Format::Bgra => 4,
+        Format::Nv12 => 1,
+    };
+
+    data.chunks(stride)
+        .take(layout.plane_height(plane))
+        .flat_map(move |line| {
+            line[..row_size]
+                .chunks_exact(bytes_per_group)
+                .flat_map(move |group| group[..significant].iter().copied())
+        })
+}
+
+/// Computes a digest over the visible content of a frame
+///
+/// Stride padding and bytes without meaning in the format (the x of BGRx)
+/// are skipped, so the same picture gives the same digest no matter how the
+/// buffer memory was laid out. The digest is a 64 bit FNV-1a hash, which is
+/// meant for detecting changes, not for security purposes.
+pub fn frame_digest(layout: &FrameLayout, planes: &[&[u8]]) -> u64 {
+    planes
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, data)| visible_bytes(layout, idx, data))
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+}
+
+/// Returns the largest difference between corresponding bytes of the visible
+/// content of two frames sharing the same layout
+pub fn max_difference(layout: &FrameLayout, a: &[&[u8]], b: &[&[u8]]) -> u8 {
+    a.iter()
+        .zip(b.iter())
+        .enumerate()
+        .flat_map(|(idx, (a, b))| visible_bytes(layout, idx, a).zip(visible_bytes(layout, idx, b)))
+        .map(|(a, b)| a.abs_diff(b))
+        .max()
+        .unwrap_or(0)
+}
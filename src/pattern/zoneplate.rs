@@ -0,0 +1,76 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::f64::consts::PI;
+
+/// Coefficients of the zone plate phase, in 1/256 of a period, with the same
+/// meaning as for videotestsrc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ZonePlate {
+    /// Per column
+    pub kx: i32,
+    /// Per line
+    pub ky: i32,
+    /// Per frame duration of the stream time, per second without a
+    /// framerate
+    pub kt: i32,
+    /// Per squared column distance from the center, scaled by the width
+    pub kx2: i32,
+    /// Per squared line distance from the center, scaled by the height
+    pub ky2: i32,
+}
+
+/// One period of a sine as gray levels, indexed by the phase
+pub(super) fn sine_table() -> Vec<u8> {
+    (0..256)
+        .map(|i| (127.5 + 127.5 * (i as f64 * 2.0 * PI / 256.0).sin()).round() as u8)
+        .collect()
+}
+
+impl ZonePlate {
+    /// Draws the zone plate at stream time `time` in nanoseconds of a stream
+    /// with `framerate` using a table from [`sine_table`]
+    pub(super) fn draw(
+        &self,
+        canvas: &mut [u32],
+        width: usize,
+        height: usize,
+        time: u64,
+        framerate: (u32, u32),
+        sine: &[u8],
+    ) {
+        let (w, h) = (width.max(1) as i64, height.max(1) as i64);
+        let (numer, denom) = match framerate {
+            (0, _) | (_, 0) => (1, 1),
+            framerate => framerate,
+        };
+        // Rounded, timestamps of frames are truncated to nanoseconds
+        let second = denom as i128 * 1_000_000_000;
+        let t =
+            (self.kt as i128 * time as i128 * numer as i128 + second / 2).div_euclid(second) as i64;
+
+        // The terms only depending on the column are the same for all lines
+        let columns: Vec<i64> = (0..w)
+            .map(|x| {
+                let dx = x - w / 2;
+                self.kx as i64 * x + self.kx2 as i64 * dx * dx / w
+            })
+            .collect();
+
+        for (y, line) in canvas.chunks_exact_mut(width).enumerate() {
+            let (y, dy) = (y as i64, y as i64 - h / 2);
+            let row = t.wrapping_add(self.ky as i64 * y + self.ky2 as i64 * dy * dy / h);
+            for (pixel, &column) in line.iter_mut().zip(columns.iter()) {
+                let level = sine[(row.wrapping_add(column) & 0xff) as usize] as u32;
+                *pixel = 0xff000000 | (level * 0x010101);
+            }
+        }
+    }
+}
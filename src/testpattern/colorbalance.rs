@@ -0,0 +1,159 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// GstColorBalance implementation of the test pattern source. The bindings
+// don't allow implementing this interface from Rust, so the interface is
+// added to the type and its vtable filled in through the C API.
+
+use gst::glib;
+use gst::glib::translate::*;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+
+use glib::ffi as glib_ffi;
+use glib::gobject_ffi;
+use gst_video::ffi as video_ffi;
+
+use once_cell::sync::Lazy;
+use std::os::raw::c_int;
+use std::ptr;
+use std::sync::Once;
+
+use crate::pattern::ColorBalance;
+
+const LABELS: [&[u8]; 4] = [b"BRIGHTNESS\0", b"CONTRAST\0", b"HUE\0", b"SATURATION\0"];
+
+// The channels are only descriptions, so they are shared by all instances
+struct Channels {
+    channels: [*mut video_ffi::GstColorBalanceChannel; 4],
+    list: *mut glib_ffi::GList,
+}
+
+// SAFETY: the channels are never modified after creation and stay alive
+// for the whole process
+unsafe impl Send for Channels {}
+unsafe impl Sync for Channels {}
+
+static CHANNELS: Lazy<Channels> = Lazy::new(|| unsafe {
+    let mut channels = [ptr::null_mut(); 4];
+    let mut list = ptr::null_mut();
+
+    for (channel, label) in channels.iter_mut().zip(LABELS.iter()) {
+        let obj =
+            gobject_ffi::g_object_new(video_ffi::gst_color_balance_channel_get_type(), ptr::null())
+                as *mut video_ffi::GstColorBalanceChannel;
+        (*obj).label = glib_ffi::g_strdup(label.as_ptr() as *const _);
+        (*obj).min_value = ColorBalance::MIN;
+        (*obj).max_value = ColorBalance::MAX;
+
+        list = glib_ffi::g_list_append(list, obj as glib_ffi::gpointer);
+        *channel = obj;
+    }
+
+    Channels { channels, list }
+});
+
+// Adds the interface to the element type, has to happen before the first
+// instance is created
+pub fn add_interface(type_: glib::Type) {
+    static ADD: Once = Once::new();
+
+    ADD.call_once(|| unsafe {
+        let info = gobject_ffi::GInterfaceInfo {
+            interface_init: Some(interface_init),
+            interface_finalize: None,
+            interface_data: ptr::null_mut(),
+        };
+        gobject_ffi::g_type_add_interface_static(
+            type_.into_glib(),
+            video_ffi::gst_color_balance_get_type(),
+            &info,
+        );
+    });
+}
+
+unsafe extern "C" fn interface_init(iface: glib_ffi::gpointer, _data: glib_ffi::gpointer) {
+    let iface = &mut *(iface as *mut video_ffi::GstColorBalanceInterface);
+    iface.list_channels = Some(list_channels);
+    iface.set_value = Some(set_value);
+    iface.get_value = Some(get_value);
+    iface.get_balance_type = Some(get_balance_type);
+}
+
+// Index of a channel in CHANNELS
+fn channel_index(channel: *mut video_ffi::GstColorBalanceChannel) -> Option<usize> {
+    CHANNELS.channels.iter().position(|&c| c == channel)
+}
+
+fn channel_value(balance: &mut ColorBalance, index: usize) -> &mut i32 {
+    match index {
+        0 => &mut balance.brightness,
+        1 => &mut balance.contrast,
+        2 => &mut balance.hue,
+        _ => &mut balance.saturation,
+    }
+}
+
+unsafe fn with_element<R>(
+    balance: *mut video_ffi::GstColorBalance,
+    f: impl FnOnce(&super::imp::TestPatternSrc) -> R,
+) -> R {
+    let obj: Borrowed<glib::Object> = from_glib_borrow(balance as *mut gobject_ffi::GObject);
+    let element = obj.unsafe_cast_ref::<super::TestPatternSrc>();
+    f(super::imp::TestPatternSrc::from_instance(element))
+}
+
+unsafe extern "C" fn list_channels(
+    _balance: *mut video_ffi::GstColorBalance,
+) -> *const glib_ffi::GList {
+    CHANNELS.list
+}
+
+unsafe extern "C" fn set_value(
+    balance: *mut video_ffi::GstColorBalance,
+    channel: *mut video_ffi::GstColorBalanceChannel,
+    value: c_int,
+) {
+    let index = match channel_index(channel) {
+        Some(index) => index,
+        None => return,
+    };
+    let value = value.clamp(ColorBalance::MIN, ColorBalance::MAX);
+
+    let changed = with_element(balance, |imp| {
+        let mut color_balance = imp.color_balance();
+        *channel_value(&mut color_balance, index) = value;
+        imp.set_color_balance(color_balance)
+    });
+
+    // Emitted without holding any lock, handlers may query the values
+    if changed {
+        video_ffi::gst_color_balance_value_changed(balance, channel, value);
+    }
+}
+
+unsafe extern "C" fn get_value(
+    balance: *mut video_ffi::GstColorBalance,
+    channel: *mut video_ffi::GstColorBalanceChannel,
+) -> c_int {
+    match channel_index(channel) {
+        Some(index) => with_element(balance, |imp| {
+            *channel_value(&mut imp.color_balance(), index)
+        }),
+        None => 0,
+    }
+}
+
+// The balance is applied while rendering, not by any hardware
+unsafe extern "C" fn get_balance_type(
+    _balance: *mut video_ffi::GstColorBalance,
+) -> video_ffi::GstColorBalanceType {
+    video_ffi::GST_COLOR_BALANCE_SOFTWARE
+}
@@ -9,14 +9,24 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use gst::glib;
+use gst::glib::translate::ToGlibPtr;
 use gst::subclass::prelude::*;
 use gst_base::prelude::*;
 use gst_base::subclass::base_src::CreateSuccess;
 use gst_base::subclass::prelude::*;
-use std::sync::Mutex;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 
 use once_cell::sync::Lazy;
 
+use crate::pattern;
+use crate::video;
+
+use super::{
+    AnimationMode, BackgroundPattern, Channels, Corner, CorruptionMode, MismatchAction,
+    Orientation, Pattern, WrapMode,
+};
+
 // This module contains the private implementation details of our element
 //
 static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
@@ -28,20 +38,325 @@ static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
 });
 
 // Default values of properties
+const DEFAULT_PATTERN: Pattern = Pattern::Bar;
 const DEFAULT_FOREGROUND_COLOR: u32 = 0xffffffff;
 const DEFAULT_BACKGROUND_COLOR: u32 = 0xff000000;
-const DEFAULT_SPEED: u32 = 5;
+const DEFAULT_SPEED: i32 = 5;
 const DEFAULT_SIZE: u32 = 50;
+const DEFAULT_CHECKER_SIZE: u32 = 8;
+const DEFAULT_GRADIENT_ANGLE: f64 = 0.0;
+const DEFAULT_OCTAVES: u32 = 4;
+const DEFAULT_BALL_RADIUS: u32 = 20;
+const DEFAULT_GRAVITY: f64 = 980.0;
+const DEFAULT_ELASTICITY: f64 = 0.8;
+const DEFAULT_PARTICLE_COUNT: u32 = 40;
+const DEFAULT_PARTICLE_MIN_SPEED: u32 = 2;
+const DEFAULT_PARTICLE_MAX_SPEED: u32 = 10;
+const DEFAULT_STREAK_LENGTH: u32 = 12;
+const DEFAULT_KX2: i32 = 20;
+const DEFAULT_KY2: i32 = 20;
+const DEFAULT_SPOKES: u32 = 36;
+const DEFAULT_STAR_RADIUS: f64 = 0.9;
+const DEFAULT_BLINK_INTERVAL: u32 = 1;
+const DEFAULT_HUE_SPEED: f64 = 0.0;
+const DEFAULT_BAR_COUNT: u32 = 1;
+const DEFAULT_BAR_SPACING: u32 = 50;
+const DEFAULT_GRID_SPACING: u32 = 64;
+const DEFAULT_LINE_WIDTH: u32 = 1;
+const DEFAULT_RING_WIDTH: u32 = 16;
+const DEFAULT_FRAME_COUNTER: Corner = Corner::None;
+const DEFAULT_SHOW_TIMECODE: bool = false;
+const DEFAULT_CODE_SIZE: u32 = 0;
+const DEFAULT_CODE_X: u32 = 0;
+const DEFAULT_CODE_Y: u32 = 0;
+const DEFAULT_TIMESTAMP_STRIPE: bool = false;
+const DEFAULT_STRIPE_HEIGHT: u32 = 8;
+const DEFAULT_STEPS: u32 = 16;
+const DEFAULT_STEP_ROWS: bool = false;
+const DEFAULT_DITHER: bool = false;
+const DEFAULT_ACTION_SAFE: f64 = 0.9;
+const DEFAULT_TITLE_SAFE: f64 = 0.8;
+const DEFAULT_HOLD_FRAMES: u32 = 30;
+const DEFAULT_ITERATIONS: u32 = 256;
+const DEFAULT_ARMS: u32 = 4;
+const DEFAULT_ROTATION_SPEED: f64 = 90.0;
+const DEFAULT_TRAIL_LENGTH: u32 = 0;
+const DEFAULT_BOX_WIDTH: u32 = 64;
+const DEFAULT_BOX_HEIGHT: u32 = 48;
+const DEFAULT_SPEED_X: i32 = 3;
+const DEFAULT_SPEED_Y: i32 = 2;
+const DEFAULT_HUE_STEP: f64 = 1.0;
+const DEFAULT_HUE_QUANTIZE: bool = false;
+const DEFAULT_GRAIN_INTENSITY: u32 = 16;
+const DEFAULT_GRAIN_SIZE: u32 = 1;
+const DEFAULT_TEXT_X: u32 = 0;
+const DEFAULT_TEXT_Y: u32 = 0;
+const DEFAULT_TEXT_SCALE: u32 = 2;
+const DEFAULT_FLASH_PERIOD: u64 = 2_000_000_000;
+const DEFAULT_PATTERN_LEFT: Pattern = Pattern::Snow;
+const DEFAULT_PATTERN_RIGHT: Pattern = Pattern::HorizontalGradient;
+const DEFAULT_MARGIN_TOP: u32 = 0;
+const DEFAULT_MARGIN_BOTTOM: u32 = 0;
+const DEFAULT_ORIENTATION: Orientation = Orientation::Vertical;
+const DEFAULT_SPEED_FLOAT: f64 = 0.0;
+const DEFAULT_VELOCITY: f64 = 0.0;
+const DEFAULT_OFFSET: u32 = 0;
+const DEFAULT_ANIMATION_MODE: AnimationMode = AnimationMode::Frames;
+const DEFAULT_WRAP_MODE: WrapMode = WrapMode::Wrap;
+const DEFAULT_SOFTNESS: u32 = 0;
+const DEFAULT_TRANSPARENT_BACKGROUND: bool = false;
+const DEFAULT_RELATIVE_SIZE: f64 = 0.0;
+const DEFAULT_PAUSE_MOTION: bool = false;
+const DEFAULT_LOOP_DURATION: u64 = 0;
+const DEFAULT_ACCELERATION: f64 = 0.0;
+const DEFAULT_MAX_SPEED: f64 = 0.0;
+const DEFAULT_MARK_DISCONT: bool = false;
+const DEFAULT_MARK_FRAMES: u32 = 5;
+const DEFAULT_FOREGROUND_COLOR_2: u32 = 0xff000000;
+const DEFAULT_FADE_PERIOD: u64 = 0;
+const DEFAULT_AMPLITUDE: u32 = 100;
+const DEFAULT_PERIOD: u64 = 1_000_000_000;
+const DEFAULT_STEP_FRAMES: u32 = 1;
+const DEFAULT_JITTER: u32 = 0;
+const DEFAULT_JITTER_SEED: u64 = 0;
+const DEFAULT_TRANSITION_DURATION: u64 = 0;
+const DEFAULT_CHANNELS: Channels = Channels::all();
+const DEFAULT_BORDER_WIDTH: u32 = 0;
+const DEFAULT_BORDER_COLOR: u32 = 0xffffffff;
+const DEFAULT_BACKGROUND_PATTERN: BackgroundPattern = BackgroundPattern::None;
+const DEFAULT_SEED: u64 = 0;
+const DEFAULT_IS_LIVE: bool = false;
+const DEFAULT_MIRROR: bool = false;
+const DEFAULT_VERIFICATION: bool = false;
+const DEFAULT_MISMATCH_ACTION: MismatchAction = MismatchAction::Error;
+const DEFAULT_META_PAD: bool = false;
+const DEFAULT_TIMELINE_LOOP: bool = false;
+const DEFAULT_CORRUPTION_PROBABILITY: f64 = 0.0;
+const DEFAULT_CORRUPTION_MODE: CorruptionMode = CorruptionMode::BitFlips;
+const DEFAULT_AB_INTERVAL: u64 = 0;
+const DEFAULT_VIEWPORT_CLEAR: bool = true;
+const DEFAULT_CYCLE_INTERVAL: u64 = 0;
+
+// Bits flipped in a frame corrupted with CorruptionMode::BitFlips
+const CORRUPTION_BIT_FLIPS: usize = 16;
+
+const OVERLAY_COMPOSITION_FEATURE: &str = "meta:GstVideoOverlayComposition";
+
+// Width, height and framerate of an entry of cycle-resolutions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Resolution {
+    width: i32,
+    height: i32,
+    fps: gst::Fraction,
+}
+
+impl std::str::FromStr for Resolution {
+    type Err = String;
+
+    // Parses "WxH@N/D"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("invalid resolution {}, expected WxH@N/D", s);
+        let (size, fps) = s.split_once('@').ok_or_else(err)?;
+        let (width, height) = size.split_once('x').ok_or_else(err)?;
+        let (numer, denom) = fps.split_once('/').ok_or_else(err)?;
+
+        let parse = |v: &str| v.trim().parse::<i32>().ok().filter(|v| *v > 0);
+        match (parse(width), parse(height), parse(numer), parse(denom)) {
+            (Some(width), Some(height), Some(numer), Some(denom)) => Ok(Resolution {
+                width,
+                height,
+                fps: gst::Fraction::new(numer, denom),
+            }),
+            _ => Err(err()),
+        }
+    }
+}
 
 // Property value storage
 #[derive(Debug, Clone)]
 struct Settings {
+    pattern: Pattern,
     foreground_color: u32,
     background_color: u32,
     info: Option<gst_video::VideoInfo>,
+    // Order of the fields if the negotiated caps are interleaved
+    field_order: Option<pattern::FieldOrder>,
+    // Downstream composites the border, labels and codes itself
+    attach_composition: bool,
     size: u32,
+    checker_size: u32,
+    gradient_angle: f64,
+    octaves: u32,
+    ball_radius: u32,
+    gravity: f64,
+    elasticity: f64,
+    particle_count: u32,
+    particle_min_speed: u32,
+    particle_max_speed: u32,
+    streak_length: u32,
+    zone_plate: pattern::ZonePlate,
+    spokes: u32,
+    star_radius: f64,
+    blink_interval: u32,
+    hue_speed: f64,
+    bar_count: u32,
+    bar_spacing: u32,
+    bar_colors: Vec<u32>,
+    grid_spacing: u32,
+    line_width: u32,
+    ring_width: u32,
+    frame_counter: Corner,
+    show_timecode: bool,
+    code_size: u32,
+    code_x: u32,
+    code_y: u32,
+    timestamp_stripe: bool,
+    stripe_height: u32,
+    steps: u32,
+    step_rows: bool,
+    dither: bool,
+    action_safe: f64,
+    title_safe: f64,
+    hold_frames: u32,
+    iterations: u32,
+    arms: u32,
+    rotation_speed: f64,
+    trail_length: u32,
+    box_width: u32,
+    box_height: u32,
+    speed_x: i32,
+    speed_y: i32,
+    hue_step: f64,
+    hue_quantize: bool,
+    grain_intensity: u32,
+    grain_size: u32,
+    text: Option<String>,
+    text_x: u32,
+    text_y: u32,
+    text_scale: u32,
+    flash_period: u64,
+    pattern_left: Pattern,
+    pattern_right: Pattern,
+    margin_top: u32,
+    margin_bottom: u32,
+    orientation: Orientation,
+    speed_float: f64,
+    // Frame, running time and offset the position of the image is computed
+    // from, moved whenever the motion changes
+    origin: (u64, gst::ClockTime, u32),
+    // Set by the reset signal, applied before the next frame
+    reset_pending: bool,
+    velocity: f64,
+    start_offset: u32,
+    animation_mode: AnimationMode,
+    // Framerate speed is relative to in the time based animation modes,
+    // taken from the first caps after starting
+    animation_rate: Option<gst::Fraction>,
+    // Time of the previous frame, the frames at animation_rate since the
+    // origin and the fraction of a pixel not moved yet
+    animation_last: Option<gst::ClockTime>,
+    animation_frames: f64,
+    animation_residual: f64,
+    wrap_mode: WrapMode,
+    softness: u32,
+    transparent_background: bool,
+    relative_size: f64,
+    pause_motion: bool,
+    // Frames and running time the motion was paused for, the animation
+    // lags behind the stream by them
+    motion_hold: (u64, gst::ClockTime),
+    // Frame index, time and noise seed the last frame was drawn with
+    last_motion: Option<(u64, gst::ClockTime, u64)>,
+    loop_duration: u64,
+    acceleration: f64,
+    max_speed: f64,
+    mark_discont: bool,
+    mark_frames: u32,
+    // Frames still to be marked since the last discontinuity
+    marks_left: u32,
+    foreground_color_2: u32,
+    fade_period: u64,
+    amplitude: u32,
+    period: u64,
+    step_frames: u32,
+    // Frames still to repeat the last motion step in
+    steps_left: u32,
+    jitter: u32,
+    jitter_seed: u64,
+    transition_duration: u64,
+    // Pattern fading out and the running time the transition started at
+    transition: Option<(Pattern, gst::ClockTime)>,
+    channels: Channels,
+    border_width: u32,
+    border_color: u32,
+    background_pattern: BackgroundPattern,
+    seed: u64,
+    // Seed property value or the one derived from the time at start
+    active_seed: u64,
     offset: u32,
-    speed: u32,
+    // Offset the bar was drawn at in the last frame, folded and jittered
+    drawn_offset: u32,
+    speed: i32,
+    is_live: bool,
+    balance: pattern::ColorBalance,
+    mirror_x: bool,
+    mirror_y: bool,
+    objects: Vec<pattern::Object>,
+    // Disabled while the width or height is 0
+    viewport: pattern::Viewport,
+    viewport_clear: bool,
+    viewport_warned: bool,
+    // Structures the objects were parsed from, for reading the property
+    object_structures: Vec<gst::Structure>,
+    verification: bool,
+    expected_digests: Option<String>,
+    mismatch_action: MismatchAction,
+    expected: Vec<u64>,
+    location: Option<String>,
+    // Decoded from `location` in start()
+    image: Option<Arc<pattern::Image>>,
+    meta_enabled: bool,
+    meta_state: Option<gst::Structure>,
+    // Keyframes sorted by running time
+    timeline: Vec<(gst::ClockTime, gst::Structure)>,
+    // Pattern changes sorted by running time, with the structures they were
+    // parsed from
+    cues: Vec<(gst::ClockTime, Pattern, gst::Structure)>,
+    // Index of the cue in effect and the pattern from before the first one
+    active_cue: Option<usize>,
+    cue_fallback: Pattern,
+    timeline_loop: bool,
+    // Next keyframe to apply and running time the keyframe times are relative to
+    timeline_pos: usize,
+    timeline_origin: gst::ClockTime,
+    // Changes requested by "set-test-pattern" events, sorted by running time
+    changes: Vec<(gst::ClockTime, gst::Structure)>,
+    // Alternating configurations and the one applied last
+    config_a: Option<gst::Structure>,
+    config_b: Option<gst::Structure>,
+    ab_interval: u64,
+    ab_active: Option<&'static str>,
+    // Resolutions renegotiated to every cycle_interval frames, the next one
+    // and the one requested from downstream
+    cycle_resolutions: Vec<(String, Resolution)>,
+    cycle_interval: u64,
+    cycle_pos: usize,
+    cycle_target: Option<Resolution>,
+    corruption_probability: f64,
+    corruption_mode: CorruptionMode,
+    corrupted_frames: u64,
+    rng: pattern::Rng,
+    // Random numbers for the patterns, e.g. the noise seed of every frame
+    noise_rng: pattern::Rng,
+    bouncing_ball: pattern::BouncingBall,
+    split_balls: [pattern::BouncingBall; 2],
+    // Centers of the ball in the last trail-length frames, oldest first
+    ball_trail: VecDeque<(f64, f64)>,
+    // Rain streaks and the frame size they were spawned for
+    particles: Vec<pattern::Particle>,
+    particles_spawned: Option<(u32, u32, u32, u32, u32)>,
+    // State of a previous run to continue from on the next start
+    restore_state: Option<gst::Structure>,
 
     accum_frames: u64,
     n_frames: u64,
@@ -52,12 +367,164 @@ struct Settings {
 impl Default for Settings {
     fn default() -> Self {
         Settings {
+            pattern: DEFAULT_PATTERN,
             foreground_color: DEFAULT_FOREGROUND_COLOR,
             background_color: DEFAULT_BACKGROUND_COLOR,
             speed: DEFAULT_SPEED,
             size: DEFAULT_SIZE,
+            checker_size: DEFAULT_CHECKER_SIZE,
+            gradient_angle: DEFAULT_GRADIENT_ANGLE,
+            octaves: DEFAULT_OCTAVES,
+            ball_radius: DEFAULT_BALL_RADIUS,
+            gravity: DEFAULT_GRAVITY,
+            elasticity: DEFAULT_ELASTICITY,
+            particle_count: DEFAULT_PARTICLE_COUNT,
+            particle_min_speed: DEFAULT_PARTICLE_MIN_SPEED,
+            particle_max_speed: DEFAULT_PARTICLE_MAX_SPEED,
+            streak_length: DEFAULT_STREAK_LENGTH,
+            zone_plate: pattern::ZonePlate {
+                kx2: DEFAULT_KX2,
+                ky2: DEFAULT_KY2,
+                ..pattern::ZonePlate::default()
+            },
+            spokes: DEFAULT_SPOKES,
+            star_radius: DEFAULT_STAR_RADIUS,
+            blink_interval: DEFAULT_BLINK_INTERVAL,
+            hue_speed: DEFAULT_HUE_SPEED,
+            bar_count: DEFAULT_BAR_COUNT,
+            bar_spacing: DEFAULT_BAR_SPACING,
+            bar_colors: Vec::new(),
+            grid_spacing: DEFAULT_GRID_SPACING,
+            line_width: DEFAULT_LINE_WIDTH,
+            ring_width: DEFAULT_RING_WIDTH,
+            frame_counter: DEFAULT_FRAME_COUNTER,
+            show_timecode: DEFAULT_SHOW_TIMECODE,
+            code_size: DEFAULT_CODE_SIZE,
+            code_x: DEFAULT_CODE_X,
+            code_y: DEFAULT_CODE_Y,
+            timestamp_stripe: DEFAULT_TIMESTAMP_STRIPE,
+            stripe_height: DEFAULT_STRIPE_HEIGHT,
+            steps: DEFAULT_STEPS,
+            step_rows: DEFAULT_STEP_ROWS,
+            dither: DEFAULT_DITHER,
+            action_safe: DEFAULT_ACTION_SAFE,
+            title_safe: DEFAULT_TITLE_SAFE,
+            hold_frames: DEFAULT_HOLD_FRAMES,
+            iterations: DEFAULT_ITERATIONS,
+            arms: DEFAULT_ARMS,
+            rotation_speed: DEFAULT_ROTATION_SPEED,
+            trail_length: DEFAULT_TRAIL_LENGTH,
+            box_width: DEFAULT_BOX_WIDTH,
+            box_height: DEFAULT_BOX_HEIGHT,
+            speed_x: DEFAULT_SPEED_X,
+            speed_y: DEFAULT_SPEED_Y,
+            hue_step: DEFAULT_HUE_STEP,
+            hue_quantize: DEFAULT_HUE_QUANTIZE,
+            grain_intensity: DEFAULT_GRAIN_INTENSITY,
+            grain_size: DEFAULT_GRAIN_SIZE,
+            text: None,
+            text_x: DEFAULT_TEXT_X,
+            text_y: DEFAULT_TEXT_Y,
+            text_scale: DEFAULT_TEXT_SCALE,
+            flash_period: DEFAULT_FLASH_PERIOD,
+            pattern_left: DEFAULT_PATTERN_LEFT,
+            pattern_right: DEFAULT_PATTERN_RIGHT,
+            margin_top: DEFAULT_MARGIN_TOP,
+            margin_bottom: DEFAULT_MARGIN_BOTTOM,
+            orientation: DEFAULT_ORIENTATION,
+            speed_float: DEFAULT_SPEED_FLOAT,
+            origin: (0, gst::ClockTime::ZERO, DEFAULT_OFFSET),
+            reset_pending: false,
+            velocity: DEFAULT_VELOCITY,
+            start_offset: DEFAULT_OFFSET,
+            animation_mode: DEFAULT_ANIMATION_MODE,
+            animation_rate: None,
+            animation_last: None,
+            animation_frames: 0.0,
+            animation_residual: 0.0,
+            wrap_mode: DEFAULT_WRAP_MODE,
+            softness: DEFAULT_SOFTNESS,
+            transparent_background: DEFAULT_TRANSPARENT_BACKGROUND,
+            relative_size: DEFAULT_RELATIVE_SIZE,
+            pause_motion: DEFAULT_PAUSE_MOTION,
+            motion_hold: (0, gst::ClockTime::ZERO),
+            last_motion: None,
+            loop_duration: DEFAULT_LOOP_DURATION,
+            acceleration: DEFAULT_ACCELERATION,
+            max_speed: DEFAULT_MAX_SPEED,
+            mark_discont: DEFAULT_MARK_DISCONT,
+            mark_frames: DEFAULT_MARK_FRAMES,
+            marks_left: 0,
+            foreground_color_2: DEFAULT_FOREGROUND_COLOR_2,
+            fade_period: DEFAULT_FADE_PERIOD,
+            amplitude: DEFAULT_AMPLITUDE,
+            period: DEFAULT_PERIOD,
+            step_frames: DEFAULT_STEP_FRAMES,
+            steps_left: 0,
+            jitter: DEFAULT_JITTER,
+            jitter_seed: DEFAULT_JITTER_SEED,
+            transition_duration: DEFAULT_TRANSITION_DURATION,
+            transition: None,
+            channels: DEFAULT_CHANNELS,
+            border_width: DEFAULT_BORDER_WIDTH,
+            border_color: DEFAULT_BORDER_COLOR,
+            background_pattern: DEFAULT_BACKGROUND_PATTERN,
+            seed: DEFAULT_SEED,
+            active_seed: DEFAULT_SEED,
             offset: 0,
+            drawn_offset: 0,
             info: None,
+            field_order: None,
+            attach_composition: false,
+            is_live: DEFAULT_IS_LIVE,
+            balance: pattern::ColorBalance::default(),
+            mirror_x: DEFAULT_MIRROR,
+            mirror_y: DEFAULT_MIRROR,
+            objects: Vec::new(),
+            viewport: pattern::Viewport {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+            },
+            viewport_clear: DEFAULT_VIEWPORT_CLEAR,
+            viewport_warned: false,
+            object_structures: Vec::new(),
+            verification: DEFAULT_VERIFICATION,
+            expected_digests: None,
+            mismatch_action: DEFAULT_MISMATCH_ACTION,
+            expected: Vec::new(),
+            location: None,
+            image: None,
+            meta_enabled: DEFAULT_META_PAD,
+            meta_state: None,
+            timeline: Vec::new(),
+            cues: Vec::new(),
+            active_cue: None,
+            cue_fallback: DEFAULT_PATTERN,
+            timeline_loop: DEFAULT_TIMELINE_LOOP,
+            timeline_pos: 0,
+            timeline_origin: gst::ClockTime::ZERO,
+            changes: Vec::new(),
+            config_a: None,
+            config_b: None,
+            ab_interval: DEFAULT_AB_INTERVAL,
+            ab_active: None,
+            cycle_resolutions: Vec::new(),
+            cycle_interval: DEFAULT_CYCLE_INTERVAL,
+            cycle_pos: 0,
+            cycle_target: None,
+            corruption_probability: DEFAULT_CORRUPTION_PROBABILITY,
+            corruption_mode: DEFAULT_CORRUPTION_MODE,
+            corrupted_frames: 0,
+            rng: pattern::Rng::new(0),
+            noise_rng: pattern::Rng::new(0),
+            bouncing_ball: pattern::BouncingBall::default(),
+            split_balls: [pattern::BouncingBall::default(); 2],
+            ball_trail: VecDeque::new(),
+            particles: Vec::new(),
+            particles_spawned: None,
+            restore_state: None,
 
             accum_frames: 0,
             n_frames: 0,
@@ -67,113 +534,3241 @@ impl Default for Settings {
     }
 }
 
+impl Settings {
+    fn params(&self) -> pattern::PatternParams {
+        pattern::PatternParams {
+            pattern: self.pattern.into(),
+            foreground_color: self.foreground_color,
+            background_color: Some(if self.transparent_background {
+                0x00000000
+            } else {
+                self.background_color
+            }),
+            offset: self.offset,
+            size: self.bar_size(),
+            speed: self.speed,
+            checker_size: self.checker_size,
+            gradient_angle: self.gradient_angle,
+            seed: self.active_seed,
+            octaves: self.octaves,
+            ball_radius: self.ball_radius,
+            bouncing_ball: self.bouncing_ball,
+            pattern_left: self.pattern_left.into(),
+            pattern_right: self.pattern_right.into(),
+            split_balls: self.split_balls,
+            trail: self.ball_trail.iter().copied().collect(),
+            particles: self.particles.clone(),
+            streak_length: self.streak_length,
+            zone_plate: self.zone_plate,
+            spokes: self.spokes,
+            star_radius: self.star_radius,
+            blink_interval: self.blink_interval,
+            hue_speed: self.hue_speed,
+            bar_count: self.bar_count,
+            bar_spacing: self.bar_spacing,
+            bar_colors: self.bar_colors.clone(),
+            grid_spacing: self.grid_spacing,
+            line_width: self.line_width,
+            ring_width: self.ring_width,
+            frame_counter: self.frame_counter.into(),
+            show_timecode: self.show_timecode,
+            code_size: self.code_size,
+            code_position: (self.code_x, self.code_y),
+            stripe_lines: if self.timestamp_stripe {
+                self.stripe_height
+            } else {
+                0
+            },
+            steps: self.steps,
+            step_rows: self.step_rows,
+            dither: self.dither,
+            action_safe: self.action_safe,
+            title_safe: self.title_safe,
+            hold_frames: self.hold_frames,
+            iterations: self.iterations,
+            arms: self.arms,
+            rotation_speed: self.rotation_speed,
+            box_width: self.box_width,
+            box_height: self.box_height,
+            speed_x: self.speed_x,
+            speed_y: self.speed_y,
+            image: self.image.clone(),
+            hue_step: self.hue_step,
+            hue_quantize: self.hue_quantize,
+            grain_intensity: self.grain_intensity,
+            grain_size: self.grain_size,
+            text_scale: self.text_scale,
+            text: self.text.clone().unwrap_or_default(),
+            text_position: (self.text_x, self.text_y),
+            flash_period: self.flash_period,
+            margin_top: self.margin_top,
+            margin_bottom: self.margin_bottom,
+            orientation: self.orientation.into(),
+            wrap_mode: self.wrap_mode.into(),
+            softness: self.softness,
+            border_width: self.border_width,
+            border_color: self.border_color,
+            background_pattern: self.background_pattern.into(),
+            balance: self.balance,
+            mirror_x: self.mirror_x,
+            mirror_y: self.mirror_y,
+            objects: self.objects.clone(),
+            viewport: Some(self.viewport).filter(|v| v.width > 0 && v.height > 0),
+            viewport_clear: self.viewport_clear,
+            ..Default::default()
+        }
+    }
+
+    // Parameters of a frame at `pts` in the format of `info`, with the
+    // colors faded and composed as they end up in the frame
+    fn frame_params(
+        &self,
+        info: &gst_video::VideoInfo,
+        pts: gst::ClockTime,
+    ) -> pattern::PatternParams {
+        let mut params = self.params();
+        params.foreground_color = pattern::fade_color(
+            self.foreground_color,
+            self.foreground_color_2,
+            pts.nseconds(),
+            self.fade_period,
+        );
+        if !info.format_info().has_alpha() {
+            // Translucent colors are composed over the background, which
+            // itself ends up over black
+            let background = params
+                .background_color
+                .map(|color| pattern::flatten(color, 0xff000000));
+            // Bars are blended over a background pattern while drawing
+            if params.background_pattern.is_none() {
+                params.foreground_color =
+                    pattern::flatten(params.foreground_color, background.unwrap_or(0xff000000));
+            }
+            params.border_color =
+                pattern::flatten(params.border_color, background.unwrap_or(0xff000000));
+            params.background_color = background;
+        }
+        params.time = pts.nseconds();
+        params
+    }
+
+    // Changes the pattern from the frame at `running_time` on, cross-fading
+    // with transition-duration. Custom patterns are drawn by the application
+    // after rendering, so they can't be faded.
+    fn switch_pattern(&mut self, pattern: Pattern, running_time: gst::ClockTime) {
+        if self.transition_duration > 0
+            && self.info.is_some()
+            && pattern != self.pattern
+            && pattern != Pattern::Custom
+            && self.pattern != Pattern::Custom
+        {
+            self.transition = Some((self.pattern, running_time));
+        } else {
+            self.transition = None;
+        }
+        self.pattern = pattern;
+    }
+
+    // Switches to the pattern of the last cue at or before `running_time`, or
+    // back to the one from before the first cue. Only depends on the time,
+    // so seeking back brings earlier cues back.
+    fn apply_cues(&mut self, running_time: gst::ClockTime) {
+        let active = self
+            .cues
+            .iter()
+            .rposition(|(time, _, _)| *time <= running_time);
+        if active == self.active_cue {
+            return;
+        }
+
+        if self.active_cue.is_none() {
+            self.cue_fallback = self.pattern;
+        }
+        let pattern = match active {
+            Some(idx) => self.cues[idx].1,
+            None => self.cue_fallback,
+        };
+        self.switch_pattern(pattern, running_time);
+        self.active_cue = active;
+    }
+
+    // Takes all keyframes due at the given running time off the timeline, in
+    // the order they have to be applied
+    fn due_keyframes(&mut self, running_time: gst::ClockTime) -> Vec<gst::Structure> {
+        let mut due = Vec::new();
+
+        loop {
+            let (time, keyframe) = match self.timeline.get(self.timeline_pos) {
+                Some((time, keyframe)) => (*time, keyframe.clone()),
+                None => break,
+            };
+            if self.timeline_origin + time > running_time {
+                break;
+            }
+
+            due.push(keyframe);
+            self.timeline_pos += 1;
+
+            // Start over, relative to the time of the last keyframe
+            if self.timeline_pos == self.timeline.len() && self.timeline_loop && !time.is_zero() {
+                self.timeline_origin += time;
+                self.timeline_pos = 0;
+            }
+        }
+
+        let n_changes = self
+            .changes
+            .iter()
+            .take_while(|(time, _)| *time <= running_time)
+            .count();
+        due.extend(self.changes.drain(..n_changes).map(|(_, change)| change));
+
+        due
+    }
+
+    // Returns the configuration to apply when the frame is the first of an
+    // A/B interval, every ab-interval frames config-a and config-b swap
+    fn ab_switch(&mut self, frame_index: u64) -> Option<gst::Structure> {
+        let (name, config) = match (&self.config_a, &self.config_b) {
+            (Some(a), Some(b)) if self.ab_interval > 0 => {
+                if (frame_index / self.ab_interval) % 2 == 0 {
+                    ("a", a)
+                } else {
+                    ("b", b)
+                }
+            }
+            _ => {
+                self.ab_active = None;
+                return None;
+            }
+        };
+        if self.ab_active == Some(name) {
+            return None;
+        }
+
+        let config = config.clone();
+        self.ab_active = Some(name);
+        Some(config)
+    }
+
+    // Ground truth of the frame about to be rendered, pushed on the meta pad
+    fn pattern_state(&self, frame_index: u64) -> gst::Structure {
+        let mut state = gst::Structure::builder("pattern-state")
+            .field("frame-index", frame_index)
+            .field("offset", self.drawn_offset)
+            .field("size", self.bar_size())
+            .field("speed", self.speed)
+            .field("foreground-color", self.foreground_color)
+            .field("background-color", self.background_color)
+            .build();
+        if let Some(config) = self.ab_active {
+            state.set("config", config);
+        }
+        state
+    }
+
+    // Brings the ball patterns back to their initial state
+    fn reset_motion(&mut self) {
+        self.bouncing_ball = pattern::BouncingBall::default();
+        self.split_balls = [pattern::BouncingBall::default(); 2];
+        self.ball_trail.clear();
+        self.animation_last = None;
+        self.animation_frames = 0.0;
+        self.animation_residual = 0.0;
+        self.motion_hold = (0, gst::ClockTime::ZERO);
+        self.last_motion = None;
+        self.steps_left = 0;
+    }
+
+    // Position of a frame of the animation in the loop. At the start of the
+    // loop the simulated patterns start over like at the start of the
+    // stream, so every loop renders the same frames.
+    fn wrap_loop(&mut self, frame_index: u64, time: gst::ClockTime) -> (u64, gst::ClockTime) {
+        if self.loop_duration == 0 {
+            return (frame_index, time);
+        }
+
+        let fps = match self.info {
+            Some(ref info) => info.fps(),
+            None => gst::Fraction::new(0, 1),
+        };
+        let looped = if fps.numer() > 0 {
+            let frames = unsafe {
+                ffi::gst_util_uint64_scale_round(
+                    self.loop_duration,
+                    fps.numer() as u64,
+                    fps.denom() as u64 * gst::ClockTime::SECOND.nseconds(),
+                )
+            };
+            frame_index % frames.max(1)
+        } else {
+            frame_index
+        };
+        if looped == 0 && frame_index > 0 {
+            self.bouncing_ball = pattern::BouncingBall::default();
+            self.split_balls = [pattern::BouncingBall::default(); 2];
+            self.ball_trail.clear();
+            self.noise_rng = self.initial_noise_rng();
+            self.particles_spawned = None;
+        }
+
+        let time = gst::ClockTime::from_nseconds(time.nseconds() % self.loop_duration);
+        (looped, time)
+    }
+
+    // Generator the noise starts from, giving another sequence than the
+    // corruption one for the same seed
+    fn initial_noise_rng(&self) -> pattern::Rng {
+        pattern::Rng::new(!self.active_seed)
+    }
+
+    // Frame index and running time the motion is at in the next frame
+    fn next_motion_frame(&self) -> (u64, gst::ClockTime) {
+        let (frames, time) = self.motion_hold;
+        (
+            (self.accum_frames + self.n_frames).saturating_sub(frames),
+            (self.accum_rtime + self.running_time).saturating_sub(time),
+        )
+    }
+
+    // Lines covered by the bar in the negotiated frame size, at least one
+    // with relative-size
+    fn bar_size(&self) -> u32 {
+        match self.info {
+            Some(ref info) if self.relative_size > 0.0 => {
+                let length = match self.orientation {
+                    Orientation::Vertical => info.height(),
+                    Orientation::Horizontal => info.width(),
+                };
+                ((self.relative_size * length as f64).round() as u32).max(1)
+            }
+            _ => self.size,
+        }
+    }
+
+    // Whether any of the colors is translucent
+    fn has_alpha(&self) -> bool {
+        self.foreground_color >> 24 != 0xff
+            || (self.fade_period > 0 && self.foreground_color_2 >> 24 != 0xff)
+            || (self.border_width > 0 && self.border_color >> 24 != 0xff)
+            || self.background_color >> 24 != 0xff
+            || self.transparent_background
+    }
+
+    // Pixels per frame, speed-float wins unless 0
+    fn frame_speed(&self) -> f64 {
+        if self.speed_float != 0.0 {
+            self.speed_float
+        } else {
+            self.speed as f64
+        }
+    }
+
+    // Offset of the image in frame `frame_index` at running time `time` and
+    // the part of a pixel it was rounded by, only depending on the origin.
+    // None in the wall time animation mode, which needs the time of the
+    // previous frame.
+    fn position(&self, frame_index: u64, time: gst::ClockTime, period: u32) -> Option<(u32, f64)> {
+        let (origin_frame, origin_time, origin_offset) = self.origin;
+        let seconds = (time.nseconds() as f64 - origin_time.nseconds() as f64) / 1_000_000_000.0;
+
+        let distance = if self.velocity != 0.0 {
+            self.travelled(self.velocity, seconds)
+        } else {
+            match self.animation_mode {
+                AnimationMode::Frames => {
+                    let frames = frame_index as f64 - origin_frame as f64;
+                    self.travelled(self.frame_speed(), frames)
+                }
+                AnimationMode::RunningTime => {
+                    let rate = self.animation_rate?;
+                    let frames = seconds * rate.numer() as f64 / rate.denom() as f64;
+                    self.travelled(self.frame_speed(), frames)
+                }
+                AnimationMode::WallTime => return None,
+            }
+        };
+
+        let offset = (origin_offset as f64 + distance.round()).rem_euclid(period.max(1) as f64);
+        Some((offset as u32, distance - distance.round()))
+    }
+
+    // Distance covered in `units` frames or seconds starting with `speed`,
+    // accelerating up to max-speed. The speed only changes linearly until
+    // it is clamped, so this integrates clamp(speed + acceleration * t) in
+    // closed form.
+    fn travelled(&self, speed: f64, units: f64) -> f64 {
+        if self.acceleration == 0.0 {
+            return speed.clamp(-self.limit(), self.limit()) * units;
+        }
+
+        let limit = self.limit();
+        // Integral of clamp(v, -limit, limit) from 0 to v
+        let integral = |v: f64| {
+            let clamped = v.clamp(-limit, limit);
+            clamped * clamped / 2.0 + clamped * (v - clamped)
+        };
+        let end = speed + self.acceleration * units;
+        (integral(end) - integral(speed)) / self.acceleration
+    }
+
+    // Largest speed in either direction
+    fn limit(&self) -> f64 {
+        if self.max_speed > 0.0 {
+            self.max_speed
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    // Restarts the motion from where the image is in the next frame, so
+    // changing it doesn't make the image jump
+    fn anchor(&mut self) {
+        let (frame_index, time) = self.next_motion_frame();
+        let offset = match self.info {
+            Some(ref info) => {
+                let period = self.params().motion_period(info.width(), info.height());
+                self.position(frame_index, time, period)
+                    .map_or(self.offset, |(offset, _)| offset)
+            }
+            None => self.origin.2,
+        };
+        self.origin = (frame_index, time, offset);
+        self.animation_frames = 0.0;
+    }
+
+    // Moves the offset by the distance covered since the frame before `now`
+    // in the wall time animation mode. Without a time or framerate to
+    // relate speed to the image stays where it is.
+    fn advance_timed(&mut self, now: Option<gst::ClockTime>, period: u32) {
+        let (now, rate) = match (now, self.animation_rate) {
+            (Some(now), Some(rate)) => (now, rate),
+            _ => {
+                self.animation_last = None;
+                return;
+            }
+        };
+
+        if let Some(last) = self.animation_last {
+            let elapsed = now.nseconds().saturating_sub(last.nseconds()) as f64 / 1_000_000_000.0;
+            let frames = elapsed * rate.numer() as f64 / rate.denom() as f64;
+            let distance = self.travelled(self.frame_speed(), self.animation_frames + frames)
+                - self.travelled(self.frame_speed(), self.animation_frames)
+                + self.animation_residual;
+            self.animation_frames += frames;
+            let step = distance.round();
+            self.animation_residual = distance - step;
+            self.offset = (self.offset as f64 + step).rem_euclid(period as f64) as u32;
+        }
+        self.animation_last = Some(now);
+    }
+
+    // Everything needed for the next run to continue the sequence of frames
+    fn save_state(&self) -> gst::Structure {
+        let mut state = gst::Structure::builder("rstestpattern-state")
+            .field("frames", self.accum_frames + self.n_frames)
+            .field(
+                "running-time",
+                (self.accum_rtime + self.running_time).nseconds(),
+            )
+            .field("offset", self.offset)
+            .field("timeline-position", self.timeline_pos as u64)
+            .field("timeline-origin", self.timeline_origin.nseconds())
+            .field("rng-state", self.rng.state())
+            .field("noise-state", self.noise_rng.state())
+            .field("motion-hold-frames", self.motion_hold.0)
+            .field("motion-hold-time", self.motion_hold.1.nseconds())
+            .field(
+                "bouncing-ball",
+                state_array(&[self.bouncing_ball.height, self.bouncing_ball.velocity]),
+            )
+            .field(
+                "split-balls",
+                state_array(
+                    &self
+                        .split_balls
+                        .iter()
+                        .flat_map(|ball| vec![ball.height, ball.velocity])
+                        .collect::<Vec<_>>(),
+                ),
+            )
+            .field(
+                "ball-trail",
+                state_array(
+                    &self
+                        .ball_trail
+                        .iter()
+                        .flat_map(|&(x, y)| vec![x, y])
+                        .collect::<Vec<_>>(),
+                ),
+            )
+            .field(
+                "particles",
+                state_array(
+                    &self
+                        .particles
+                        .iter()
+                        .flat_map(|particle| vec![particle.x, particle.y, particle.speed])
+                        .collect::<Vec<_>>(),
+                ),
+            );
+        if let Some((a, b, c, d, e)) = self.particles_spawned {
+            state = state.field("particles-spawned", state_array(&[a, b, c, d, e]));
+        }
+        state.build()
+    }
+
+    // Continues from a state returned by save_state(), the counters are
+    // accumulated like after a caps change
+    fn apply_state(&mut self, state: &gst::Structure) -> Result<(), String> {
+        if state.name() != "rstestpattern-state" {
+            return Err(format!("unexpected structure {}", state.name()));
+        }
+
+        let frames = state.get::<u64>("frames").map_err(|err| err.to_string())?;
+        let running_time = state
+            .get::<u64>("running-time")
+            .map_err(|err| err.to_string())?;
+        let offset = state.get::<u32>("offset").map_err(|err| err.to_string())?;
+        let timeline_pos = state
+            .get_optional::<u64>("timeline-position")
+            .map_err(|err| err.to_string())?
+            .unwrap_or(0);
+        let timeline_origin = state
+            .get_optional::<u64>("timeline-origin")
+            .map_err(|err| err.to_string())?
+            .unwrap_or(0);
+        let rng_state = state
+            .get_optional::<u64>("rng-state")
+            .map_err(|err| err.to_string())?;
+        let noise_state = state
+            .get_optional::<u64>("noise-state")
+            .map_err(|err| err.to_string())?;
+
+        self.accum_frames = frames;
+        self.accum_rtime = gst::ClockTime::from_nseconds(running_time);
+        self.n_frames = 0;
+        self.running_time = gst::ClockTime::ZERO;
+        self.offset = offset;
+        self.origin = (frames, self.accum_rtime, offset);
+        self.timeline_pos = (timeline_pos as usize).min(self.timeline.len());
+        self.timeline_origin = gst::ClockTime::from_nseconds(timeline_origin);
+        if let Some(rng_state) = rng_state {
+            self.rng = pattern::Rng::new(rng_state);
+        }
+        if let Some(noise_state) = noise_state {
+            self.noise_rng = pattern::Rng::new(noise_state);
+        }
+
+        // The simulated patterns, states without them start the simulation
+        // over
+        let hold_frames = state
+            .get_optional::<u64>("motion-hold-frames")
+            .map_err(|err| err.to_string())?
+            .unwrap_or(0);
+        let hold_time = state
+            .get_optional::<u64>("motion-hold-time")
+            .map_err(|err| err.to_string())?
+            .unwrap_or(0);
+        self.motion_hold = (hold_frames, gst::ClockTime::from_nseconds(hold_time));
+        match state_values::<f64>(state, "bouncing-ball", 2)?.as_deref() {
+            Some(&[height, velocity]) => {
+                self.bouncing_ball = pattern::BouncingBall { height, velocity };
+            }
+            Some(_) => return Err("invalid bouncing-ball".into()),
+            None => (),
+        }
+        if let Some(balls) = state_values::<f64>(state, "split-balls", 2)? {
+            for (ball, values) in self.split_balls.iter_mut().zip(balls.chunks(2)) {
+                *ball = pattern::BouncingBall {
+                    height: values[0],
+                    velocity: values[1],
+                };
+            }
+        }
+        if let Some(trail) = state_values::<f64>(state, "ball-trail", 2)? {
+            self.ball_trail = trail.chunks(2).map(|xy| (xy[0], xy[1])).collect();
+        }
+        if let Some(particles) = state_values::<u32>(state, "particles", 3)? {
+            self.particles = particles
+                .chunks(3)
+                .map(|values| pattern::Particle {
+                    x: values[0],
+                    y: values[1],
+                    speed: values[2],
+                })
+                .collect();
+        }
+        match state_values::<u32>(state, "particles-spawned", 5)?.as_deref() {
+            Some(&[a, b, c, d, e]) => self.particles_spawned = Some((a, b, c, d, e)),
+            Some(_) => return Err("invalid particles-spawned".into()),
+            None => (),
+        }
+        Ok(())
+    }
+}
+
+// Flat array of numbers as stored in a saved state
+fn state_array<T: ToSendValue>(values: &[T]) -> gst::Array {
+    gst::Array::from_values(values.iter().map(|value| value.to_send_value()))
+}
+
+// Reads an array written by state_array() holding a multiple of `group`
+// values, e.g. x/y pairs
+fn state_values<T>(
+    state: &gst::Structure,
+    name: &str,
+    group: usize,
+) -> Result<Option<Vec<T>>, String>
+where
+    T: for<'a> glib::value::FromValue<'a>,
+{
+    let array = match state
+        .get_optional::<gst::Array>(name)
+        .map_err(|err| err.to_string())?
+    {
+        Some(array) => array,
+        None => return Ok(None),
+    };
+
+    let values = array
+        .as_slice()
+        .iter()
+        .map(|value| value.get::<T>().map_err(|_| format!("invalid {}", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if values.len() % group != 0 {
+        return Err(format!("invalid {}", name));
+    }
+
+    Ok(Some(values))
+}
+
 // Struct containing all the element data
 #[derive(Default)]
 pub struct TestPatternSrc {
     settings: Mutex<Settings>,
+    renderer: Mutex<pattern::Renderer>,
+    meta_pad: Mutex<Option<gst::Pad>>,
 }
 
 impl TestPatternSrc {
+    // Accessors for the GstColorBalance implementation
+    pub(super) fn color_balance(&self) -> pattern::ColorBalance {
+        self.settings.lock().unwrap().balance
+    }
+
+    // Returns whether the balance changed
+    pub(super) fn set_color_balance(&self, balance: pattern::ColorBalance) -> bool {
+        let mut settings = self.settings.lock().unwrap();
+        if settings.balance == balance {
+            return false;
+        }
+
+        gst::info!(CAT, "Changing color balance to {:?}", balance);
+        settings.balance = balance;
+        true
+    }
+
     fn make_image(
         &self,
-        _pts: gst::ClockTime,
+        pts: gst::ClockTime,
+        now: Option<gst::ClockTime>,
         frame: &mut gst_video::VideoFrameRef<&mut gst::BufferRef>,
         settings: &mut Settings,
-    ) {
+    ) -> Result<(), gst::FlowError> {
         let info = settings.info.to_owned().unwrap();
-        let stride = frame.plane_stride()[0] as usize;
-        let width = frame.width() as usize * 4;
-
-        let data = frame.plane_data_mut(0).unwrap();
-        for (idx, line) in data.chunks_exact_mut(stride).enumerate() {
-            for out_p in line[..width].chunks_exact_mut(4) {
-                assert_eq!(out_p.len(), 4);
-                let line_idx = idx as u32;
-
-                if (line_idx >= settings.offset) && line_idx < (settings.offset + settings.size) {
-                    out_p[0] = 0xff;
-                    out_p[1] = 0xff;
-                    out_p[2] = 0xff;
-                } else {
-                    out_p[0] = 0x00;
-                    out_p[1] = 0x00;
-                    out_p[2] = 0x00;
+        let frame_index = settings.accum_frames + settings.n_frames;
+
+        if settings.reset_pending {
+            settings.reset_pending = false;
+            settings.reset_motion();
+            settings.origin = (frame_index, pts, settings.start_offset);
+            settings.noise_rng = settings.initial_noise_rng();
+            settings.particles_spawned = None;
+        }
+
+        // While paused the last frame is drawn again, afterwards the motion
+        // continues from there. Between the steps of step-frames the frame
+        // is drawn again too, but the motion keeps going in the meantime.
+        let (stream_frame, stream_pts) = (frame_index, pts);
+        let stepping = settings.steps_left > 0 && settings.last_motion.is_some();
+        settings.steps_left = match settings.steps_left {
+            0 => settings.step_frames - 1,
+            left => left - 1,
+        };
+        let (frame_index, pts, noise_seed) = match settings.last_motion {
+            Some(last) if settings.pause_motion || stepping => last,
+            _ => {
+                let (frames, time) = settings.motion_hold;
+                let (frame_index, pts) = settings
+                    .wrap_loop(frame_index.saturating_sub(frames), pts.saturating_sub(time));
+                (frame_index, pts, settings.noise_rng.next_u64())
+            }
+        };
+        if settings.pause_motion {
+            settings.motion_hold = (
+                stream_frame.saturating_sub(frame_index),
+                stream_pts.saturating_sub(pts),
+            );
+        }
+        settings.last_motion = Some((frame_index, pts, noise_seed));
+
+        // Size of the area the pattern moves in
+        let (mut width, mut height) = (info.width(), info.height());
+        if settings.viewport.width > 0 && settings.viewport.height > 0 {
+            let viewport = settings.viewport;
+            let layout = video::frame_layout(frame).ok_or(gst::FlowError::NotNegotiated)?;
+            let fitted = viewport.fit(&layout);
+            if fitted != viewport && !settings.viewport_warned {
+                gst::warning!(
+                    CAT,
+                    "Viewport {:?} doesn't fit into the frame, using {:?}",
+                    viewport,
+                    fitted
+                );
+                settings.viewport_warned = true;
+            }
+            width = fitted.width;
+            height = fitted.height;
+        }
+
+        // New rain whenever the size of the area or the parameters change,
+        // so no streak ends up outside
+        let spawn = (
+            width,
+            height,
+            settings.particle_count,
+            settings.particle_min_speed,
+            settings.particle_max_speed,
+        );
+        if settings.pattern == Pattern::Rain && settings.particles_spawned != Some(spawn) {
+            settings.particles = pattern::Particle::spawn(
+                &mut settings.noise_rng,
+                settings.particle_count,
+                (width, height),
+                settings.particle_min_speed,
+                settings.particle_max_speed,
+            );
+            settings.particles_spawned = Some(spawn);
+        }
+
+        let mut params = settings.frame_params(&info, pts);
+        params.noise_seed = noise_seed;
+        let period = params.motion_period(width, height);
+        // Computed from the position in the stream, so rendering a frame
+        // twice gives the same image however often fill() ran in between
+        let fraction = if settings.wrap_mode == WrapMode::Oscillate {
+            let oscillation = (settings.amplitude, settings.period);
+            let (offset, fraction) = params.oscillate(pts.nseconds(), oscillation, width, height);
+            settings.offset = offset;
+            fraction
+        } else {
+            match settings.position(frame_index, pts, period) {
+                Some((offset, fraction)) => {
+                    settings.offset = offset;
+                    fraction
+                }
+                None if settings.pause_motion => {
+                    settings.animation_last = None;
+                    settings.animation_residual
+                }
+                None if stepping => settings.animation_residual,
+                None => {
+                    settings.advance_timed(now, period);
+                    settings.animation_residual
+                }
+            }
+        };
+        params.offset = params.fold_offset(settings.offset, width, height);
+        // Moving backwards after bouncing off an edge
+        params.offset_fraction = if params.offset == settings.offset {
+            fraction
+        } else {
+            -fraction
+        };
+        // Only the drawn offset is moved, the motion itself stays smooth
+        if settings.jitter > 0 {
+            let jitter = settings.jitter as i64;
+            // An explicit jitter seed keeps the jitter the same whatever the
+            // seed property is
+            let seed = match settings.jitter_seed {
+                0 => settings.active_seed,
+                jitter_seed => jitter_seed,
+            };
+            let mut rng = pattern::Rng::new(seed ^ frame_index.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let shift = rng.below(2 * jitter as u64 + 1) as i64 - jitter;
+            params.offset = params.shift_offset(params.offset, shift, width, height);
+        }
+        settings.drawn_offset = params.offset;
+        params.framerate = (info.fps().numer() as u32, info.fps().denom() as u32);
+        params.stream_position = Some((stream_frame, stream_pts.nseconds()));
+        params.invert = settings.mark_discont && settings.marks_left > 0;
+        if let Some((pattern, start)) = settings.transition {
+            let elapsed = stream_pts.saturating_sub(start).nseconds();
+            if elapsed < settings.transition_duration {
+                let progress = elapsed as u128 * 256 / settings.transition_duration as u128;
+                params.transition = Some((pattern.into(), progress as u32));
+            } else {
+                settings.transition = None;
+            }
+        }
+        settings.marks_left = settings.marks_left.saturating_sub(1);
+        params.field_order = settings.field_order;
+        params.pixel_aspect = (info.par().numer() as u32, info.par().denom() as u32);
+        params.overlays_in_frame = !settings.attach_composition;
+
+        let mut renderer = self.renderer.lock().unwrap();
+        video::render_frame(&mut renderer, frame_index, &params, frame)?;
+        if settings.channels != Channels::all() {
+            let background = params.background_color.unwrap_or(0);
+            Self::isolate_channels(frame, settings.channels, background)?;
+        }
+        if settings.attach_composition {
+            let layout =
+                pattern::FrameLayout::new(pattern::Format::Bgra, info.width(), info.height());
+            if let Some(overlay) = renderer.render_overlay(frame_index, &params, &layout) {
+                let composition = Self::composition(overlay)?;
+                gst_video::VideoOverlayCompositionMeta::add(frame.buffer_mut(), &composition);
+            }
+        }
+
+        if settings.pause_motion || stepping {
+            return Ok(());
+        }
+
+        // Simulations catch up with the frames the step is shown in
+        let step = settings.step_frames;
+        for particle in settings.particles.iter_mut() {
+            for _ in 0..step {
+                particle.advance(height);
+            }
+        }
+
+        if let Some(center) = params.ball_center(width as usize, height as usize) {
+            settings.ball_trail.push_back(center);
+        }
+        while settings.ball_trail.len() > settings.trail_length as usize {
+            settings.ball_trail.pop_front();
+        }
+
+        // The simulation advances by the duration of the step
+        let fps = info.fps();
+        if fps.numer() > 0 {
+            let dt = step as f64 * fps.denom() as f64 / fps.numer() as f64;
+            settings.bouncing_ball.advance(
+                dt,
+                settings.gravity,
+                settings.elasticity,
+                (width as usize, height as usize),
+                params.ball_radius,
+            );
+            if settings.pattern == Pattern::Split {
+                let left = (width as usize).div_ceil(2);
+                let halves = [left, width as usize - left];
+                for (ball, &half) in settings.split_balls.iter_mut().zip(halves.iter()) {
+                    ball.advance(
+                        dt,
+                        settings.gravity,
+                        settings.elasticity,
+                        (half, height as usize),
+                        params.ball_radius,
+                    );
                 }
             }
         }
-        settings.offset += settings.speed;
-        settings.offset %= info.height();
+        Ok(())
     }
 
-    fn fill_image(
-        &self,
+    // Reads the digests of a previous run, one hexadecimal digest per line
+    // in frame order. Empty lines and lines starting with '#' are skipped.
+    fn load_digests(location: &str) -> Result<Vec<u64>, gst::ErrorMessage> {
+        let contents = std::fs::read_to_string(location).map_err(|err| {
+            gst::error_msg!(
+                gst::ResourceError::OpenRead,
+                ["Failed to read digests from {}: {}", location, err]
+            )
+        })?;
+
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .enumerate()
+            .map(|(idx, line)| {
+                let hex = line.trim_start_matches("0x");
+                u64::from_str_radix(hex, 16).map_err(|err| {
+                    gst::error_msg!(
+                        gst::ResourceError::Read,
+                        [
+                            "Invalid digest {} for frame {} in {}: {}",
+                            line,
+                            idx,
+                            location,
+                            err
+                        ]
+                    )
+                })
+            })
+            .collect()
+    }
+
+    // Decodes the image for the image pattern and converts it to ARGB
+    #[cfg(feature = "image")]
+    fn load_image(location: &str) -> Result<pattern::Image, gst::ErrorMessage> {
+        let decoded = image::open(location)
+            .map_err(|err| {
+                gst::error_msg!(
+                    gst::ResourceError::OpenRead,
+                    ["Failed to load image from {}: {}", location, err]
+                )
+            })?
+            .to_rgba8();
+
+        let (width, height) = decoded.dimensions();
+        let pixels = decoded
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                u32::from_be_bytes([a, r, g, b])
+            })
+            .collect();
+        pattern::Image::new(width as usize, height as usize, pixels).ok_or_else(|| {
+            gst::error_msg!(gst::ResourceError::Read, ["Image {} is empty", location])
+        })
+    }
+
+    #[cfg(not(feature = "image"))]
+    fn load_image(location: &str) -> Result<pattern::Image, gst::ErrorMessage> {
+        Err(gst::error_msg!(
+            gst::CoreError::NotImplemented,
+            ["Can't load {}, built without image support", location]
+        ))
+    }
+
+    fn frame_digest(
+        buffer: &gst::BufferRef,
+        info: &gst_video::VideoInfo,
+    ) -> Result<u64, gst::FlowError> {
+        let frame = gst_video::VideoFrameRef::from_buffer_ref_readable(buffer, info)
+            .map_err(|_| gst::FlowError::Error)?;
+        video::frame_digest(&frame).ok_or(gst::FlowError::NotNegotiated)
+    }
+
+    // Damages a rendered frame with the configured probability and marks it
+    // as corrupted. Returns whether the frame was damaged.
+    fn corrupt(
+        element: &super::TestPatternSrc,
         buffer: &mut gst::BufferRef,
         settings: &mut Settings,
-    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+    ) -> Result<bool, gst::FlowError> {
+        if settings.corruption_probability <= 0.0
+            || settings.rng.next_f64() >= settings.corruption_probability
+        {
+            return Ok(false);
+        }
+
         let info = settings.info.to_owned().unwrap();
+        match settings.corruption_mode {
+            CorruptionMode::BitFlips => {
+                let mut map = buffer.map_writable().map_err(|_| gst::FlowError::Error)?;
+                let data = map.as_mut_slice();
+                if data.is_empty() {
+                    return Ok(false);
+                }
+                for _ in 0..CORRUPTION_BIT_FLIPS {
+                    let bit = settings.rng.below(data.len() as u64 * 8) as usize;
+                    data[bit / 8] ^= 1 << (bit % 8);
+                }
+            }
+            CorruptionMode::ZeroedLines => {
+                let height = info.height() as u64;
+                let lines = 1 + settings.rng.below((height / 8).max(1));
+                let first = settings.rng.below(height.saturating_sub(lines) + 1) as usize;
 
-        if info.format() == gst_video::VideoFormat::Unknown {
-            return Err(gst::FlowError::NotNegotiated);
+                let mut frame = gst_video::VideoFrameRef::from_buffer_ref_writable(buffer, &info)
+                    .map_err(|_| gst::FlowError::Error)?;
+                let stride = frame.plane_stride()[0] as usize;
+                let data = frame.plane_data_mut(0).map_err(|_| gst::FlowError::Error)?;
+                let start = (first * stride).min(data.len());
+                let end = (start + lines as usize * stride).min(data.len());
+                data[start..end].fill(0);
+            }
+            CorruptionMode::TruncatedBuffer => {
+                let size = buffer.size();
+                buffer.set_size(size / 2);
+            }
+            CorruptionMode::WrongStride => {
+                if let Some(meta) = buffer.meta_mut::<gst_video::VideoMeta>() {
+                    meta.remove();
+                }
+                // Every line of every plane is read 4 bytes earlier than the
+                // one before, frames too narrow for that are left alone
+                let n_planes = info.n_planes() as usize;
+                let strides = info.stride()[..n_planes]
+                    .iter()
+                    .map(|&stride| Some(stride - 4).filter(|&stride| stride > 0))
+                    .collect::<Option<Vec<_>>>();
+                let strides = match strides {
+                    Some(strides) => strides,
+                    None => {
+                        gst::debug!(CAT, obj: element, "Frame too narrow to shrink the stride");
+                        return Ok(false);
+                    }
+                };
+                if gst_video::VideoMeta::add_full(
+                    buffer,
+                    gst_video::VideoFrameFlags::empty(),
+                    info.format(),
+                    info.width(),
+                    info.height(),
+                    &info.offset()[..n_planes],
+                    &strides,
+                )
+                .is_err()
+                {
+                    gst::warning!(
+                        CAT,
+                        obj: element,
+                        "Failed to add meta with strides {:?}",
+                        strides
+                    );
+                    return Ok(false);
+                }
+            }
         }
-        let pts = buffer.pts().unwrap();
-        match gst_video::VideoFrameRef::from_buffer_ref_writable(buffer, &info) {
-            Err(_) => gst::debug!(CAT, "invalid frame"),
-            Ok(mut frame) => self.make_image(pts, &mut frame, settings),
+
+        gst::debug!(
+            CAT,
+            obj: element,
+            "Corrupted frame at {} with {:?}",
+            buffer.pts().display(),
+            settings.corruption_mode
+        );
+        settings.corrupted_frames += 1;
+        buffer.set_flags(gst::BufferFlags::CORRUPTED);
+        Ok(true)
+    }
+
+    // Posts the digest of a frame and compares it against the expected one
+    fn verify_frame(
+        &self,
+        element: &super::TestPatternSrc,
+        frame_index: u64,
+        pts: gst::ClockTime,
+        digest: u64,
+        expected: Option<u64>,
+        mismatch_action: MismatchAction,
+    ) -> Result<(), gst::FlowError> {
+        let s = gst::Structure::builder("rstestpattern-digest")
+            .field("frame-index", frame_index)
+            .field("pts", pts)
+            .field("digest", format!("{:016x}", digest))
+            .build();
+        let _ = element.post_message(gst::message::Element::builder(s).src(element).build());
+
+        match expected {
+            Some(expected) if expected != digest => match mismatch_action {
+                MismatchAction::Warning => {
+                    gst::element_warning!(
+                        element,
+                        gst::StreamError::Failed,
+                        ["Digest mismatch"],
+                        [
+                            "Frame {} has digest {:016x}, expected {:016x}",
+                            frame_index,
+                            digest,
+                            expected
+                        ]
+                    );
+                    Ok(())
+                }
+                MismatchAction::Error => {
+                    gst::element_error!(
+                        element,
+                        gst::StreamError::Failed,
+                        ["Digest mismatch"],
+                        [
+                            "Frame {} has digest {:016x}, expected {:016x}",
+                            frame_index,
+                            digest,
+                            expected
+                        ]
+                    );
+                    Err(gst::FlowError::Error)
+                }
+            },
+            _ => Ok(()),
         }
-        Ok(gst::FlowSuccess::Ok)
     }
-}
 
-// This trait registers our type with the GObject object system and
-// provides the entry points for creating a new instance and setting
-// up the class data
-#[glib::object_subclass]
-impl ObjectSubclass for TestPatternSrc {
-    const NAME: &'static str = "TestPatternSrc";
-    type Type = super::TestPatternSrc;
-    type ParentType = gst_base::PushSrc;
-}
+    fn keyframe_time(keyframe: &gst::StructureRef, field: &str) -> Option<gst::ClockTime> {
+        let time = keyframe.value(field).ok()?.transform::<u64>().ok()?;
+        time.get::<u64>().ok().map(gst::ClockTime::from_nseconds)
+    }
 
-// Implementation of glib::Object virtual methods
-impl ObjectImpl for TestPatternSrc {
-    fn properties() -> &'static [glib::ParamSpec] {
-        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
-            vec![
+    // Unknown properties are only warned about as they're skipped when the
+    // keyframe is applied anyway
+    fn check_keyframe(
+        element: &super::TestPatternSrc,
+        time: gst::ClockTime,
+        keyframe: &gst::StructureRef,
+    ) {
+        for (name, _) in keyframe.iter().filter(|(name, _)| *name != "time") {
+            match element.find_property(name) {
+                Some(pspec)
+                    if pspec.flags().contains(glib::ParamFlags::WRITABLE)
+                        && !name.starts_with("timeline") => {}
+                _ => gst::warning!(
+                    CAT,
+                    obj: element,
+                    "Keyframe at {} sets unknown property {}",
+                    time,
+                    name
+                ),
+            }
+        }
+    }
+
+    // Sorts and checks the keyframes of a new timeline. Keyframes without a
+    // time are dropped.
+    fn parse_timeline(
+        element: &super::TestPatternSrc,
+        timeline: &gst::Array,
+    ) -> Vec<(gst::ClockTime, gst::Structure)> {
+        let mut keyframes = Vec::new();
+
+        for value in timeline.as_slice() {
+            let keyframe = match value.get::<gst::Structure>() {
+                Ok(keyframe) => keyframe,
+                Err(_) => {
+                    gst::warning!(CAT, obj: element, "Ignoring timeline entry {:?}", value);
+                    continue;
+                }
+            };
+            let time = match Self::keyframe_time(&keyframe, "time") {
+                Some(time) => time,
+                None => {
+                    gst::warning!(CAT, obj: element, "Ignoring keyframe without time: {}", keyframe);
+                    continue;
+                }
+            };
+
+            Self::check_keyframe(element, time, &keyframe);
+            keyframes.push((time, keyframe));
+        }
+
+        // Stable, so keyframes with the same time stay in array order
+        keyframes.sort_by_key(|(time, _)| *time);
+        keyframes
+    }
+
+    // Sorts and checks the entries of the cues property, dropping invalid
+    // ones and all but the first of the ones with the same time
+    fn parse_cues(
+        element: &super::TestPatternSrc,
+        cues: &gst::Array,
+    ) -> Vec<(gst::ClockTime, Pattern, gst::Structure)> {
+        let mut parsed = Vec::new();
+
+        for (idx, value) in cues.as_slice().iter().enumerate() {
+            let res = value
+                .get::<gst::Structure>()
+                .map_err(|_| String::from("not a structure"))
+                .and_then(|cue| Self::parse_cue(&cue).map(|(time, pattern)| (time, pattern, cue)));
+            match res {
+                Ok(cue) => parsed.push(cue),
+                Err(err) => gst::warning!(CAT, obj: element, "Ignoring cue {}: {}", idx, err),
+            }
+        }
+
+        parsed.sort_by_key(|(time, _, _)| *time);
+        parsed.dedup_by(|(time, _, cue), (first, _, _)| {
+            let duplicate = time == first;
+            if duplicate {
+                gst::warning!(CAT, obj: element, "Ignoring cue {} with the same time", cue);
+            }
+            duplicate
+        });
+        parsed
+    }
+
+    fn parse_cue(cue: &gst::StructureRef) -> Result<(gst::ClockTime, Pattern), String> {
+        if matches!(cue.get::<i64>("time"), Ok(time) if time < 0) {
+            return Err(String::from("negative time"));
+        }
+        let time = Self::keyframe_time(cue, "time").ok_or("missing or invalid time")?;
+
+        let value = cue.value("pattern").map_err(|err| err.to_string())?;
+        let pattern = match value.get::<&str>() {
+            Ok(nick) => glib::EnumClass::new(Pattern::static_type())
+                .and_then(|class| class.to_value_by_nick(nick))
+                .and_then(|value| value.get::<Pattern>().ok()),
+            Err(_) => value
+                .transform::<Pattern>()
+                .ok()
+                .and_then(|value| value.get::<Pattern>().ok()),
+        };
+
+        match pattern {
+            Some(pattern) => Ok((time, pattern)),
+            None => Err(format!("invalid pattern {:?}", value)),
+        }
+    }
+
+    // Converts the entries of the objects property. Invalid entries are
+    // dropped with a warning naming the offending field.
+    fn parse_objects(
+        element: &super::TestPatternSrc,
+        objects: &gst::Array,
+    ) -> Vec<(pattern::Object, gst::Structure)> {
+        objects
+            .as_slice()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, value)| {
+                let res = value
+                    .get::<gst::Structure>()
+                    .map_err(|_| String::from("not a structure"))
+                    .and_then(|structure| {
+                        Self::parse_object(&structure).map(|object| (object, structure))
+                    });
+                match res {
+                    Ok(object) => Some(object),
+                    Err(err) => {
+                        gst::warning!(CAT, obj: element, "Ignoring object {}: {}", idx, err);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn parse_object(structure: &gst::StructureRef) -> Result<pattern::Object, String> {
+        fn field<T>(name: &str, value: &glib::SendValue) -> Result<T, String>
+        where
+            T: glib::value::ValueType + for<'a> glib::value::FromValue<'a>,
+        {
+            value
+                .transform::<T>()
+                .ok()
+                .and_then(|value| value.get::<T>().ok())
+                .ok_or_else(|| format!("invalid {} {:?}", name, value))
+        }
+
+        let mut object = pattern::Object::default();
+        for (name, value) in structure.iter() {
+            match name {
+                "shape" => object.shape = field::<String>(name, value)?.parse()?,
+                "motion" => object.motion = field::<String>(name, value)?.parse()?,
+                "size" => {
+                    object.width = field(name, value)?;
+                    object.height = object.width;
+                }
+                "width" => object.width = field(name, value)?,
+                "height" => object.height = field(name, value)?,
+                "color" => object.color = field(name, value)?,
+                "x" => object.x = field(name, value)?,
+                "y" => object.y = field(name, value)?,
+                "velocity-x" => object.velocity_x = field(name, value)?,
+                "velocity-y" => object.velocity_y = field(name, value)?,
+                _ => return Err(format!("unknown field {}", name)),
+            }
+        }
+
+        Ok(object)
+    }
+
+    // Handles a "set-test-pattern" custom event, e.g. sent from a gst-validate
+    // scenario. Its fields are property values like the ones of a keyframe,
+    // applied in the first frame reaching the optional "running-time" field
+    // or in the next frame without one.
+    fn queue_change(&self, element: &super::TestPatternSrc, structure: &gst::StructureRef) -> bool {
+        let time = if structure.has_field("running-time") {
+            match Self::keyframe_time(structure, "running-time") {
+                Some(time) => time,
+                None => {
+                    gst::warning!(CAT, obj: element, "Invalid running-time in {}", structure);
+                    return false;
+                }
+            }
+        } else {
+            gst::ClockTime::ZERO
+        };
+
+        let mut change = structure.to_owned();
+        change.remove_field("running-time");
+        Self::check_keyframe(element, time, &change);
+        gst::debug!(CAT, obj: element, "Queueing change at {}: {}", time, change);
+
+        // Behind all changes with the same time, so they apply in order
+        let mut settings = self.settings.lock().unwrap();
+        let pos = settings
+            .changes
+            .iter()
+            .take_while(|(t, _)| *t <= time)
+            .count();
+        settings.changes.insert(pos, (time, change));
+        true
+    }
+
+    fn apply_keyframe(&self, element: &super::TestPatternSrc, keyframe: &gst::StructureRef) {
+        gst::debug!(CAT, obj: element, "Applying keyframe {}", keyframe);
+
+        for (name, value) in keyframe.iter() {
+            if name == "time" || name.starts_with("timeline") {
+                continue;
+            }
+
+            let res = element
+                .find_property(name)
+                .ok_or_else(|| glib::bool_error!("No such property"))
+                .and_then(|pspec| value.transform_with_type(pspec.value_type()))
+                .and_then(|value| element.try_set_property_from_value(name, &value));
+            if let Err(err) = res {
+                gst::warning!(CAT, obj: element, "Failed to set {} from keyframe: {}", name, err);
+            }
+        }
+    }
+
+    // Stores a property in the settings, set_property() notifies about it
+    fn update_property(
+        &self,
+        obj: &super::TestPatternSrc,
+        value: &glib::Value,
+        pspec: &glib::ParamSpec,
+    ) {
+        let mut settings = self.settings.lock().unwrap();
+
+        match pspec.name() {
+            "pattern" => {
+                let running_time = settings.accum_rtime + settings.running_time;
+                settings.switch_pattern(value.get().expect("type checked upstream"), running_time);
+            }
+            "foreground-color" => {
+                settings.foreground_color = value.get().expect("type checked upstream");
+            }
+            "background-color" => {
+                settings.background_color = value.get().expect("type checked upstream");
+            }
+            "foreground-color-string" | "background-color-string" => {
+                let color = value
+                    .get::<Option<String>>()
+                    .expect("type checked upstream");
+                match color.as_deref().map(pattern::parse_color) {
+                    Some(Ok(color)) if pspec.name() == "foreground-color-string" => {
+                        settings.foreground_color = color
+                    }
+                    Some(Ok(color)) => settings.background_color = color,
+                    Some(Err(err)) => {
+                        gst::warning!(CAT, obj: obj, "Ignoring {}: {}", pspec.name(), err)
+                    }
+                    None => (),
+                }
+            }
+            "speed" => {
+                settings.anchor();
+                settings.speed = value.get().expect("type checked upstream");
+            }
+            "size" => {
+                settings.size = value.get().expect("type checked upstream");
+            }
+            "checker-size" => {
+                settings.checker_size = value.get().expect("type checked upstream");
+            }
+            "gradient-angle" => {
+                settings.gradient_angle = value.get().expect("type checked upstream");
+            }
+            "octaves" => {
+                settings.octaves = value.get().expect("type checked upstream");
+            }
+            "ball-radius" => {
+                settings.ball_radius = value.get().expect("type checked upstream");
+            }
+            "gravity" => {
+                settings.gravity = value.get().expect("type checked upstream");
+            }
+            "elasticity" => {
+                settings.elasticity = value.get().expect("type checked upstream");
+            }
+            "particle-count" => {
+                settings.particle_count = value.get().expect("type checked upstream");
+            }
+            "particle-min-speed" => {
+                settings.particle_min_speed = value.get().expect("type checked upstream");
+            }
+            "particle-max-speed" => {
+                settings.particle_max_speed = value.get().expect("type checked upstream");
+            }
+            "streak-length" => {
+                settings.streak_length = value.get().expect("type checked upstream");
+            }
+            "kx" => {
+                settings.zone_plate.kx = value.get().expect("type checked upstream");
+            }
+            "ky" => {
+                settings.zone_plate.ky = value.get().expect("type checked upstream");
+            }
+            "kt" => {
+                settings.zone_plate.kt = value.get().expect("type checked upstream");
+            }
+            "kx2" => {
+                settings.zone_plate.kx2 = value.get().expect("type checked upstream");
+            }
+            "ky2" => {
+                settings.zone_plate.ky2 = value.get().expect("type checked upstream");
+            }
+            "spokes" => {
+                settings.spokes = value.get().expect("type checked upstream");
+            }
+            "star-radius" => {
+                settings.star_radius = value.get().expect("type checked upstream");
+            }
+            "blink-interval" => {
+                settings.blink_interval = value.get().expect("type checked upstream");
+            }
+            "hue-speed" => {
+                settings.hue_speed = value.get().expect("type checked upstream");
+            }
+            "bar-count" => {
+                settings.bar_count = value.get().expect("type checked upstream");
+            }
+            "bar-spacing" => {
+                settings.bar_spacing = value.get().expect("type checked upstream");
+            }
+            "bar-colors" => {
+                let colors = value.get::<gst::Array>().expect("type checked upstream");
+                settings.bar_colors = colors
+                    .iter()
+                    .map(|color| color.get::<u32>().expect("type checked upstream"))
+                    .collect();
+            }
+            "grid-spacing" => {
+                settings.grid_spacing = value.get().expect("type checked upstream");
+            }
+            "line-width" => {
+                settings.line_width = value.get().expect("type checked upstream");
+            }
+            "ring-width" => {
+                settings.ring_width = value.get().expect("type checked upstream");
+            }
+            "frame-counter" => {
+                settings.frame_counter = value.get().expect("type checked upstream");
+            }
+            "show-timecode" => {
+                settings.show_timecode = value.get().expect("type checked upstream");
+            }
+            "code-size" => {
+                settings.code_size = value.get().expect("type checked upstream");
+            }
+            "code-x" => {
+                settings.code_x = value.get().expect("type checked upstream");
+            }
+            "code-y" => {
+                settings.code_y = value.get().expect("type checked upstream");
+            }
+            "timestamp-stripe" => {
+                settings.timestamp_stripe = value.get().expect("type checked upstream");
+            }
+            "stripe-height" => {
+                settings.stripe_height = value.get().expect("type checked upstream");
+            }
+            "steps" => {
+                settings.steps = value.get().expect("type checked upstream");
+            }
+            "step-rows" => {
+                settings.step_rows = value.get().expect("type checked upstream");
+            }
+            "dither" => {
+                settings.dither = value.get().expect("type checked upstream");
+            }
+            "action-safe" => {
+                settings.action_safe = value.get().expect("type checked upstream");
+            }
+            "title-safe" => {
+                settings.title_safe = value.get().expect("type checked upstream");
+            }
+            "hold-frames" => {
+                settings.hold_frames = value.get().expect("type checked upstream");
+            }
+            "iterations" => {
+                settings.iterations = value.get().expect("type checked upstream");
+            }
+            "arms" => {
+                settings.arms = value.get().expect("type checked upstream");
+            }
+            "rotation-speed" => {
+                settings.rotation_speed = value.get().expect("type checked upstream");
+            }
+            "trail-length" => {
+                settings.trail_length = value.get().expect("type checked upstream");
+            }
+            "box-width" => {
+                settings.box_width = value.get().expect("type checked upstream");
+            }
+            "box-height" => {
+                settings.box_height = value.get().expect("type checked upstream");
+            }
+            "speed-x" => {
+                settings.speed_x = value.get().expect("type checked upstream");
+            }
+            "speed-y" => {
+                settings.speed_y = value.get().expect("type checked upstream");
+            }
+            "hue-step" => {
+                settings.hue_step = value.get().expect("type checked upstream");
+            }
+            "hue-quantize" => {
+                settings.hue_quantize = value.get().expect("type checked upstream");
+            }
+            "grain-intensity" => {
+                settings.grain_intensity = value.get().expect("type checked upstream");
+            }
+            "grain-size" => {
+                settings.grain_size = value.get().expect("type checked upstream");
+            }
+            "text" => {
+                settings.text = value.get().expect("type checked upstream");
+            }
+            "text-x" => {
+                settings.text_x = value.get().expect("type checked upstream");
+            }
+            "text-y" => {
+                settings.text_y = value.get().expect("type checked upstream");
+            }
+            "text-scale" => {
+                settings.text_scale = value.get().expect("type checked upstream");
+            }
+            "flash-period" => {
+                settings.flash_period = value.get().expect("type checked upstream");
+            }
+            "pattern-left" => {
+                settings.pattern_left = value.get().expect("type checked upstream");
+            }
+            "pattern-right" => {
+                settings.pattern_right = value.get().expect("type checked upstream");
+            }
+            "margin-top" => {
+                settings.margin_top = value.get().expect("type checked upstream");
+            }
+            "margin-bottom" => {
+                settings.margin_bottom = value.get().expect("type checked upstream");
+            }
+            "orientation" => {
+                settings.orientation = value.get().expect("type checked upstream");
+            }
+            "speed-float" => {
+                settings.anchor();
+                settings.speed_float = value.get().expect("type checked upstream");
+            }
+            "velocity" => {
+                settings.anchor();
+                settings.velocity = value.get().expect("type checked upstream");
+            }
+            "offset" => {
+                settings.start_offset = value.get().expect("type checked upstream");
+                settings.offset = settings.start_offset;
+                if let Some(ref info) = settings.info {
+                    settings.offset %= settings.params().motion_period(info.width(), info.height());
+                }
+                let (frame_index, time) = settings.next_motion_frame();
+                settings.origin = (frame_index, time, settings.offset);
+            }
+            "animation-mode" => {
+                settings.anchor();
+                settings.animation_mode = value.get().expect("type checked upstream");
+            }
+            "wrap-mode" => {
+                let wrap_mode = value.get().expect("type checked upstream");
+                // Continue from wherever the image swung to
+                if settings.wrap_mode == WrapMode::Oscillate && wrap_mode != WrapMode::Oscillate {
+                    let (frame_index, time) = settings.next_motion_frame();
+                    settings.origin = (frame_index, time, settings.offset);
+                }
+                settings.wrap_mode = wrap_mode;
+            }
+            "softness" => {
+                settings.softness = value.get().expect("type checked upstream");
+            }
+            "transparent-background" => {
+                settings.transparent_background = value.get().expect("type checked upstream");
+            }
+            "relative-size" => {
+                settings.relative_size = value.get().expect("type checked upstream");
+            }
+            "pause-motion" => {
+                settings.pause_motion = value.get().expect("type checked upstream");
+            }
+            "loop-duration" => {
+                settings.loop_duration = value.get().expect("type checked upstream");
+            }
+            "acceleration" => {
+                settings.anchor();
+                settings.acceleration = value.get().expect("type checked upstream");
+            }
+            "max-speed" => {
+                settings.anchor();
+                settings.max_speed = value.get().expect("type checked upstream");
+            }
+            "mark-discont" => {
+                settings.mark_discont = value.get().expect("type checked upstream");
+            }
+            "mark-frames" => {
+                settings.mark_frames = value.get().expect("type checked upstream");
+            }
+            "foreground-color-2" => {
+                settings.foreground_color_2 = value.get().expect("type checked upstream");
+            }
+            "fade-period" => {
+                settings.fade_period = value.get().expect("type checked upstream");
+            }
+            "amplitude" => {
+                settings.amplitude = value.get().expect("type checked upstream");
+            }
+            "period" => {
+                settings.period = value.get().expect("type checked upstream");
+            }
+            "step-frames" => {
+                settings.step_frames = value.get().expect("type checked upstream");
+                settings.steps_left = settings.steps_left.min(settings.step_frames - 1);
+            }
+            "jitter" => {
+                settings.jitter = value.get().expect("type checked upstream");
+            }
+            "jitter-seed" => {
+                settings.jitter_seed = value.get().expect("type checked upstream");
+            }
+            "transition-duration" => {
+                settings.transition_duration = value.get().expect("type checked upstream");
+            }
+            "channels" => {
+                settings.channels = value.get().expect("type checked upstream");
+            }
+            "border-width" => {
+                settings.border_width = value.get().expect("type checked upstream");
+            }
+            "border-color" => {
+                settings.border_color = value.get().expect("type checked upstream");
+            }
+            "background-pattern" => {
+                settings.background_pattern = value.get().expect("type checked upstream");
+            }
+            "seed" => {
+                settings.seed = value.get().expect("type checked upstream");
+                settings.active_seed = settings.seed;
+            }
+            "is-live" => {
+                settings.is_live = value.get().expect("type checked upstream");
+                obj.set_live(settings.is_live);
+            }
+            "mirror-x" => {
+                settings.mirror_x = value.get().expect("type checked upstream");
+            }
+            "mirror-y" => {
+                settings.mirror_y = value.get().expect("type checked upstream");
+            }
+            "objects" => {
+                let objects = value.get::<gst::Array>().expect("type checked upstream");
+                let (objects, structures) = Self::parse_objects(obj, &objects).into_iter().unzip();
+                settings.objects = objects;
+                settings.object_structures = structures;
+            }
+            "cues" => {
+                let cues = value.get::<gst::Array>().expect("type checked upstream");
+                if settings.active_cue.take().is_some() {
+                    settings.pattern = settings.cue_fallback;
+                }
+                settings.cues = Self::parse_cues(obj, &cues);
+            }
+            "viewport-x" => {
+                settings.viewport.x = value.get().expect("type checked upstream");
+                settings.viewport_warned = false;
+            }
+            "viewport-y" => {
+                settings.viewport.y = value.get().expect("type checked upstream");
+                settings.viewport_warned = false;
+            }
+            "viewport-width" => {
+                settings.viewport.width = value.get().expect("type checked upstream");
+                settings.viewport_warned = false;
+            }
+            "viewport-height" => {
+                settings.viewport.height = value.get().expect("type checked upstream");
+                settings.viewport_warned = false;
+            }
+            "viewport-clear" => {
+                settings.viewport_clear = value.get().expect("type checked upstream");
+            }
+            "verification" => {
+                settings.verification = value.get().expect("type checked upstream");
+            }
+            "expected-digests" => {
+                settings.expected_digests = value.get().expect("type checked upstream");
+            }
+            "location" => {
+                settings.location = value.get().expect("type checked upstream");
+            }
+            "mismatch-action" => {
+                settings.mismatch_action = value.get().expect("type checked upstream");
+            }
+            "meta-pad" => {
+                settings.meta_enabled = value.get().expect("type checked upstream");
+            }
+            "timeline" => {
+                let timeline = value.get::<gst::Array>().expect("type checked upstream");
+                settings.timeline = Self::parse_timeline(obj, &timeline);
+                settings.timeline_pos = 0;
+                settings.timeline_origin = gst::ClockTime::ZERO;
+            }
+            "timeline-loop" => {
+                settings.timeline_loop = value.get().expect("type checked upstream");
+            }
+            "config-a" => {
+                settings.config_a = value.get().expect("type checked upstream");
+                settings.ab_active = None;
+            }
+            "config-b" => {
+                settings.config_b = value.get().expect("type checked upstream");
+                settings.ab_active = None;
+            }
+            "ab-interval" => {
+                settings.ab_interval = value.get().expect("type checked upstream");
+                settings.ab_active = None;
+            }
+            "cycle-resolutions" => {
+                let resolutions = value.get::<gst::Array>().expect("type checked upstream");
+                settings.cycle_resolutions = resolutions
+                    .as_slice()
+                    .iter()
+                    .filter_map(|value| {
+                        let s = value.get::<String>().ok()?;
+                        match s.parse::<Resolution>() {
+                            Ok(resolution) => Some((s, resolution)),
+                            Err(err) => {
+                                gst::warning!(CAT, obj: obj, "Ignoring {}", err);
+                                None
+                            }
+                        }
+                    })
+                    .collect();
+                settings.cycle_pos = 0;
+            }
+            "cycle-interval" => {
+                settings.cycle_interval = value.get().expect("type checked upstream");
+            }
+            "corruption-probability" => {
+                settings.corruption_probability = value.get().expect("type checked upstream");
+            }
+            "corruption-mode" => {
+                settings.corruption_mode = value.get().expect("type checked upstream");
+            }
+            "state" => {
+                settings.restore_state = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn add_meta_pad(&self, element: &super::TestPatternSrc) -> Result<(), gst::StateChangeError> {
+        if !self.settings.lock().unwrap().meta_enabled {
+            return Ok(());
+        }
+
+        let templ = element.pad_template("meta").unwrap();
+        let pad = gst::Pad::builder_with_template(&templ, Some("meta")).build();
+        pad.use_fixed_caps();
+        element.add_pad(&pad).map_err(|_| {
+            gst::error!(CAT, obj: element, "Failed to add meta pad");
+            gst::StateChangeError
+        })?;
+        element.no_more_pads();
+
+        *self.meta_pad.lock().unwrap() = Some(pad);
+        Ok(())
+    }
+
+    fn remove_meta_pad(&self, element: &super::TestPatternSrc) {
+        if let Some(pad) = self.meta_pad.lock().unwrap().take() {
+            let _ = element.remove_pad(&pad);
+        }
+    }
+
+    fn activate_meta_pad(&self, active: bool) -> Result<(), gst::StateChangeError> {
+        match *self.meta_pad.lock().unwrap() {
+            Some(ref pad) => pad.set_active(active).map_err(|_| gst::StateChangeError),
+            None => Ok(()),
+        }
+    }
+
+    // Mirrors the stream of the video pad onto the meta pad: the events keep
+    // both streams in the same segment and for every video buffer the state
+    // it was rendered with is pushed with identical timestamps
+    fn forward_to_meta_pad(&self, element: &super::TestPatternSrc, info: &gst::PadProbeInfo) {
+        let meta_pad = match *self.meta_pad.lock().unwrap() {
+            Some(ref pad) => pad.clone(),
+            None => return,
+        };
+
+        match info.data {
+            Some(gst::PadProbeData::Buffer(ref buffer)) => {
+                let state = match self.settings.lock().unwrap().meta_state.take() {
+                    Some(state) => state,
+                    None => return,
+                };
+
+                let mut meta = gst::Buffer::from_slice(state.to_string().into_bytes());
+                {
+                    let meta = meta.get_mut().unwrap();
+                    meta.set_pts(buffer.pts());
+                    meta.set_duration(buffer.duration());
+                    meta.set_offset(buffer.offset());
+                    meta.set_offset_end(buffer.offset_end());
+                }
+
+                match meta_pad.push(meta) {
+                    Ok(_) | Err(gst::FlowError::NotLinked) | Err(gst::FlowError::Flushing) => (),
+                    Err(err) => {
+                        gst::warning!(CAT, obj: element, "Failed to push pattern state: {:?}", err)
+                    }
+                }
+            }
+            Some(gst::PadProbeData::Event(ref event)) => match event.view() {
+                gst::EventView::StreamStart(stream_start) => {
+                    let stream_id = meta_pad.create_stream_id(element, Some("meta"));
+                    // Same seqnum and group so downstream can correlate both
+                    // streams, the base class picks a fresh group on every
+                    // start
+                    let mut builder =
+                        gst::event::StreamStart::builder(&stream_id).seqnum(stream_start.seqnum());
+                    if let Some(group_id) = stream_start.group_id() {
+                        builder = builder.group_id(group_id);
+                    }
+                    meta_pad.push_event(builder.build());
+
+                    let caps = gst::Caps::builder("application/x-pattern-state").build();
+                    meta_pad.push_event(gst::event::Caps::new(&caps));
+                }
+                gst::EventView::Segment(_)
+                | gst::EventView::Eos(_)
+                | gst::EventView::FlushStart(_)
+                | gst::EventView::FlushStop(_) => {
+                    meta_pad.push_event(event.clone());
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    // Wraps the border, labels and codes rendered on their own into a
+    // composition with a single rectangle
+    fn composition(
+        overlay: pattern::Overlay,
+    ) -> Result<gst_video::VideoOverlayComposition, gst::FlowError> {
+        let pattern::Overlay {
+            x,
+            y,
+            width,
+            height,
+            data,
+        } = overlay;
+        let mut buffer = gst::Buffer::from_mut_slice(data);
+        gst_video::VideoMeta::add(
+            buffer.get_mut().unwrap(),
+            gst_video::VideoFrameFlags::empty(),
+            gst_video::VideoFormat::Bgra,
+            width,
+            height,
+        )
+        .map_err(|_| gst::FlowError::Error)?;
+
+        let rectangle = gst_video::VideoOverlayRectangle::new_raw(
+            &buffer,
+            x as i32,
+            y as i32,
+            width,
+            height,
+            gst_video::VideoOverlayFormatFlags::empty(),
+        );
+        gst_video::VideoOverlayComposition::new(Some(&rectangle)).map_err(|_| gst::FlowError::Error)
+    }
+
+    // Overwrites the color components not in `channels` with the ones of
+    // `background`, the byte of every component is taken from the format
+    fn isolate_channels(
+        frame: &mut gst_video::VideoFrameRef<&mut gst::BufferRef>,
+        channels: Channels,
+        background: u32,
+    ) -> Result<(), gst::FlowError> {
+        let format_info = frame.format_info();
+        if !format_info.is_rgb() {
+            return Ok(());
+        }
+
+        let [_, r, g, b] = background.to_be_bytes();
+        let components = [
+            (Channels::RED, r),
+            (Channels::GREEN, g),
+            (Channels::BLUE, b),
+        ];
+        let fixed = components
+            .iter()
+            .enumerate()
+            .filter(|(_, (channel, _))| !channels.contains(*channel))
+            .map(|(component, &(_, value))| (format_info.poffset()[component] as usize, value))
+            .collect::<Vec<_>>();
+        let pixel_stride = format_info.pixel_stride()[0] as usize;
+        let (width, height) = (frame.width() as usize, frame.height() as usize);
+        let stride = frame.plane_stride()[0] as usize;
+        let data = frame.plane_data_mut(0).map_err(|_| gst::FlowError::Error)?;
+
+        for line in data.chunks_mut(stride).take(height) {
+            for pixel in line[..width * pixel_stride].chunks_exact_mut(pixel_stride) {
+                for &(offset, value) in &fixed {
+                    pixel[offset] = value;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Lets the application draw the custom pattern into the first plane
+    fn emit_draw(
+        element: &super::TestPatternSrc,
+        buffer: &mut gst::BufferRef,
+        info: &gst_video::VideoInfo,
+    ) -> Result<(), gst::FlowError> {
+        let pts = buffer.pts().unwrap();
+        let mut frame = gst_video::VideoFrameRef::from_buffer_ref_writable(buffer, info)
+            .map_err(|_| gst::FlowError::Error)?;
+        let (width, height) = (frame.width(), frame.height());
+        let stride = frame.plane_stride()[0];
+        let data = frame.plane_data_mut(0).map_err(|_| gst::FlowError::Error)?;
+
+        element.emit_by_name::<()>(
+            "draw",
+            &[
+                &pts.nseconds(),
+                &width,
+                &height,
+                &stride,
+                &(data.as_mut_ptr() as glib::Pointer),
+            ],
+        );
+        Ok(())
+    }
+
+    fn fill_image(
+        &self,
+        buffer: &mut gst::BufferRef,
+        now: Option<gst::ClockTime>,
+        settings: &mut Settings,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let info = settings.info.to_owned().unwrap();
+
+        if info.format() == gst_video::VideoFormat::Unknown {
+            return Err(gst::FlowError::NotNegotiated);
+        }
+        let pts = buffer.pts().unwrap();
+        match gst_video::VideoFrameRef::from_buffer_ref_writable(buffer, &info) {
+            Err(_) => gst::debug!(CAT, "invalid frame"),
+            Ok(mut frame) => self.make_image(pts, now, &mut frame, settings)?,
+        }
+        Ok(gst::FlowSuccess::Ok)
+    }
+}
+
+// This trait registers our type with the GObject object system and
+// provides the entry points for creating a new instance and setting
+// up the class data
+#[glib::object_subclass]
+impl ObjectSubclass for TestPatternSrc {
+    const NAME: &'static str = "TestPatternSrc";
+    type Type = super::TestPatternSrc;
+    type ParentType = gst_base::PushSrc;
+}
+
+// Implementation of glib::Object virtual methods
+impl ObjectImpl for TestPatternSrc {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecEnum::new(
+                    "pattern",
+                    "Pattern",
+                    "Type of test pattern to generate",
+                    Pattern::static_type(),
+                    DEFAULT_PATTERN as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "foreground-color",
+                    "Foreground Color",
+                    "Foreground color to use (big-endian ARGB)",
+                    0,
+                    u32::MAX,
+                    DEFAULT_FOREGROUND_COLOR,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | glib::ParamFlags::CONTROLLABLE
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "background-color",
+                    "Background Color",
+                    "Background color to use (big-endian ARGB)",
+                    0,
+                    u32::MAX,
+                    DEFAULT_BACKGROUND_COLOR,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | glib::ParamFlags::CONTROLLABLE
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecString::new(
+                    "foreground-color-string",
+                    "Foreground Color String",
+                    "Foreground color as #RRGGBB, #AARRGGBB or a name like white or red, sets foreground-color",
+                    None,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecString::new(
+                    "background-color-string",
+                    "Background Color String",
+                    "Background color as #RRGGBB, #AARRGGBB or a name like black or transparent, sets background-color",
+                    None,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecInt::new(
+                    "speed",
+                    "Speed",
+                    "Scroll image number of pixels per frame, negative values scroll backwards",
+                    i32::MIN,
+                    i32::MAX,
+                    DEFAULT_SPEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | glib::ParamFlags::CONTROLLABLE
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "size",
+                    "size",
+                    "Vertical width of horizontal bar",
+                    u32::MIN,
+                    u32::MAX,
+                    DEFAULT_SPEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | glib::ParamFlags::CONTROLLABLE
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "checker-size",
+                    "Checker Size",
+                    "Edge length of the checkerboard squares in pixels",
+                    1,
+                    u32::MAX,
+                    DEFAULT_CHECKER_SIZE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "gradient-angle",
+                    "Gradient Angle",
+                    "Direction of the angled gradient in degrees, clockwise from pointing right",
+                    -360.0,
+                    360.0,
+                    DEFAULT_GRADIENT_ANGLE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "octaves",
+                    "Octaves",
+                    "Number of octaves summed up by the value noise",
+                    1,
+                    16,
+                    DEFAULT_OCTAVES,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "ball-radius",
+                    "Ball Radius",
+                    "Radius of the moving ball in pixels",
+                    1,
+                    u32::MAX,
+                    DEFAULT_BALL_RADIUS,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "gravity",
+                    "Gravity",
+                    "Acceleration of the bouncing ball in pixels per second squared",
+                    0.0,
+                    f64::MAX,
+                    DEFAULT_GRAVITY,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "elasticity",
+                    "Elasticity",
+                    "Fraction of the velocity the bouncing ball keeps when bouncing",
+                    0.0,
+                    1.0,
+                    DEFAULT_ELASTICITY,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "particle-count",
+                    "Particle Count",
+                    "Number of rain streaks",
+                    0,
+                    u32::MAX,
+                    DEFAULT_PARTICLE_COUNT,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "particle-min-speed",
+                    "Particle Min Speed",
+                    "Lowest speed of a rain streak in lines per frame",
+                    0,
+                    u32::MAX,
+                    DEFAULT_PARTICLE_MIN_SPEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "particle-max-speed",
+                    "Particle Max Speed",
+                    "Highest speed of a rain streak in lines per frame",
+                    0,
+                    u32::MAX,
+                    DEFAULT_PARTICLE_MAX_SPEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "streak-length",
+                    "Streak Length",
+                    "Number of lines covered by a rain streak",
+                    1,
+                    u32::MAX,
+                    DEFAULT_STREAK_LENGTH,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecInt::new(
+                    "kx",
+                    "KX",
+                    "Zone plate phase change per column, in 1/256 of a period",
+                    i32::MIN,
+                    i32::MAX,
+                    0,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecInt::new(
+                    "ky",
+                    "KY",
+                    "Zone plate phase change per line, in 1/256 of a period",
+                    i32::MIN,
+                    i32::MAX,
+                    0,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecInt::new(
+                    "kt",
+                    "KT",
+                    "Zone plate phase change per frame duration of the buffer timestamps, in 1/256 of a period",
+                    i32::MIN,
+                    i32::MAX,
+                    0,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecInt::new(
+                    "kx2",
+                    "KX2",
+                    "Zone plate phase change per squared column distance from the center, in 1/256 of a period",
+                    i32::MIN,
+                    i32::MAX,
+                    DEFAULT_KX2,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecInt::new(
+                    "ky2",
+                    "KY2",
+                    "Zone plate phase change per squared line distance from the center, in 1/256 of a period",
+                    i32::MIN,
+                    i32::MAX,
+                    DEFAULT_KY2,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "spokes",
+                    "Spokes",
+                    "Number of sectors of the Siemens star in the foreground color",
+                    1,
+                    u32::MAX,
+                    DEFAULT_SPOKES,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "star-radius",
+                    "Star Radius",
+                    "Diameter of the Siemens star relative to the shorter side of the frame",
+                    0.0,
+                    1.0,
+                    DEFAULT_STAR_RADIUS,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "blink-interval",
+                    "Blink Interval",
+                    "Number of frames between two color changes of the blink pattern",
+                    1,
+                    u32::MAX,
+                    DEFAULT_BLINK_INTERVAL,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "hue-speed",
+                    "Hue Speed",
+                    "Degrees per second of running time the hue of the bar rotates by, saturation and value are those of the foreground color",
+                    -f64::MAX,
+                    f64::MAX,
+                    DEFAULT_HUE_SPEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "bar-count",
+                    "Bar Count",
+                    "Number of bars scrolling together, each wrapping around at the bottom on its own",
+                    1,
+                    4096,
+                    DEFAULT_BAR_COUNT,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "bar-spacing",
+                    "Bar Spacing",
+                    "Lines between two consecutive bars",
+                    0,
+                    u32::MAX,
+                    DEFAULT_BAR_SPACING,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                gst::ParamSpecArray::new(
+                    "bar-colors",
+                    "Bar Colors",
+                    "Big-endian ARGB colors of the bars in order, bars without an entry use the foreground color",
+                    Some(&glib::ParamSpecUInt::new(
+                        "bar-color",
+                        "Bar Color",
+                        "Color of one bar",
+                        0,
+                        u32::MAX,
+                        0,
+                        glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                    )),
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "grid-spacing",
+                    "Grid Spacing",
+                    "Distance in pixels between two lines of the grid pattern",
+                    1,
+                    u32::MAX,
+                    DEFAULT_GRID_SPACING,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "line-width",
+                    "Line Width",
+                    "Width in pixels of the lines of the grid pattern, lines at least as wide as the spacing fill the frame",
+                    0,
+                    u32::MAX,
+                    DEFAULT_LINE_WIDTH,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "ring-width",
+                    "Ring Width",
+                    "Width in pixels of one ring of the circles pattern, the rings move outwards with the speed",
+                    1,
+                    u32::MAX,
+                    DEFAULT_RING_WIDTH,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecEnum::new(
+                    "frame-counter",
+                    "Frame Counter",
+                    "Corner the frame number, the buffer offset, is drawn into on top of the pattern",
+                    Corner::static_type(),
+                    DEFAULT_FRAME_COUNTER as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "show-timecode",
+                    "Show Timecode",
+                    "Draw the non-drop-frame timecode of the stream time at the top of the frame, on top of the pattern",
+                    DEFAULT_SHOW_TIMECODE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "code-size",
+                    "Code Size",
+                    "Edge length of a square machine readable code of the PTS and the frame number drawn on top of the pattern, 16 modules across, 0 to disable",
+                    0,
+                    u32::MAX,
+                    DEFAULT_CODE_SIZE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "code-x",
+                    "Code X",
+                    "Left edge of the machine readable code",
+                    0,
+                    u32::MAX,
+                    DEFAULT_CODE_X,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "code-y",
+                    "Code Y",
+                    "Top edge of the machine readable code",
+                    0,
+                    u32::MAX,
+                    DEFAULT_CODE_Y,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "timestamp-stripe",
+                    "Timestamp Stripe",
+                    "Draw the PTS and the frame number as a row of black and white blocks over the top lines, on top of the pattern",
+                    DEFAULT_TIMESTAMP_STRIPE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "stripe-height",
+                    "Stripe Height",
+                    "Number of lines covered by the timestamp stripe",
+                    1,
+                    u32::MAX,
+                    DEFAULT_STRIPE_HEIGHT,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "steps",
+                    "Steps",
+                    "Number of gray steps of the staircase pattern",
+                    2,
+                    u32::MAX,
+                    DEFAULT_STEPS,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "step-rows",
+                    "Step Rows",
+                    "Arrange the steps of the staircase pattern as rows from top to bottom instead of columns",
+                    DEFAULT_STEP_ROWS,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "dither",
+                    "Dither",
+                    "Apply 2x2 ordered dithering to the ramp pattern in frames wider than 256 pixels",
+                    DEFAULT_DITHER,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "action-safe",
+                    "Action Safe",
+                    "Fraction of the width and height inside the action safe rectangle of the safe area pattern",
+                    0.0,
+                    1.0,
+                    DEFAULT_ACTION_SAFE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "title-safe",
+                    "Title Safe",
+                    "Fraction of the width and height inside the title safe rectangle of the safe area pattern",
+                    0.0,
+                    1.0,
+                    DEFAULT_TITLE_SAFE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "hold-frames",
+                    "Hold Frames",
+                    "Number of frames every color of the color cycle pattern is shown",
+                    1,
+                    u32::MAX,
+                    DEFAULT_HOLD_FRAMES,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "iterations",
+                    "Iterations",
+                    "Maximum number of iterations per pixel of the Mandelbrot pattern",
+                    1,
+                    u32::MAX,
+                    DEFAULT_ITERATIONS,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "arms",
+                    "Arms",
+                    "Number of foreground colored arms of the spiral pattern",
+                    1,
+                    u32::MAX,
+                    DEFAULT_ARMS,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "rotation-speed",
+                    "Rotation Speed",
+                    "Degrees per second of running time the spiral and cube patterns rotate by",
+                    -f64::MAX,
+                    f64::MAX,
+                    DEFAULT_ROTATION_SPEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "trail-length",
+                    "Trail Length",
+                    "Number of previous positions of the ball patterns drawn fading out behind the ball",
+                    0,
+                    1024,
+                    DEFAULT_TRAIL_LENGTH,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "box-width",
+                    "Box Width",
+                    "Width of the moving box pattern",
+                    1,
+                    u32::MAX,
+                    DEFAULT_BOX_WIDTH,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "box-height",
+                    "Box Height",
+                    "Height of the moving box pattern",
+                    1,
+                    u32::MAX,
+                    DEFAULT_BOX_HEIGHT,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecInt::new(
+                    "speed-x",
+                    "Speed X",
+                    "Pixels per frame the moving box pattern moves to the right, negative to the left",
+                    -4096,
+                    4096,
+                    DEFAULT_SPEED_X,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecInt::new(
+                    "speed-y",
+                    "Speed Y",
+                    "Pixels per frame the moving box pattern moves down, negative up",
+                    -4096,
+                    4096,
+                    DEFAULT_SPEED_Y,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "hue-step",
+                    "Hue Step",
+                    "Degrees per frame the hue of the color vectors pattern advances by",
+                    -360.0,
+                    360.0,
+                    DEFAULT_HUE_STEP,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "hue-quantize",
+                    "Hue Quantize",
+                    "Round the hue of the color vectors pattern to the nearest primary or secondary color",
+                    DEFAULT_HUE_QUANTIZE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "grain-intensity",
+                    "Grain Intensity",
+                    "Standard deviation of the grain pattern noise in 8 bit levels",
+                    0,
+                    255,
+                    DEFAULT_GRAIN_INTENSITY,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "grain-size",
+                    "Grain Size",
+                    "Width and height of a single grain of the grain pattern in pixels",
+                    1,
+                    1024,
+                    DEFAULT_GRAIN_SIZE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecString::new(
+                    "text",
+                    "Text",
+                    "Text drawn on top of the pattern with the built-in font",
+                    None,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "text-x",
+                    "Text X",
+                    "Left edge of the text box in pixels",
+                    0,
+                    u32::MAX,
+                    DEFAULT_TEXT_X,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "text-y",
+                    "Text Y",
+                    "Top edge of the text box in pixels",
+                    0,
+                    u32::MAX,
+                    DEFAULT_TEXT_Y,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "text-scale",
+                    "Text Scale",
+                    "Width and height of a pixel of the text font in pixels",
+                    1,
+                    64,
+                    DEFAULT_TEXT_SCALE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "flash-period",
+                    "Flash Period",
+                    "Nanoseconds of stream time between the white flash frames of the flash pattern",
+                    1,
+                    u64::MAX,
+                    DEFAULT_FLASH_PERIOD,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecEnum::new(
+                    "pattern-left",
+                    "Pattern Left",
+                    "Pattern in the left half of the split pattern",
+                    Pattern::static_type(),
+                    DEFAULT_PATTERN_LEFT as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecEnum::new(
+                    "pattern-right",
+                    "Pattern Right",
+                    "Pattern in the right half of the split pattern",
+                    Pattern::static_type(),
+                    DEFAULT_PATTERN_RIGHT as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "margin-top",
+                    "Margin Top",
+                    "Lines at the top of the frame the bar never enters",
+                    0,
+                    u32::MAX,
+                    DEFAULT_MARGIN_TOP,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "margin-bottom",
+                    "Margin Bottom",
+                    "Lines at the bottom of the frame the bar never enters",
+                    0,
+                    u32::MAX,
+                    DEFAULT_MARGIN_BOTTOM,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecEnum::new(
+                    "orientation",
+                    "Orientation",
+                    "Direction the bar scrolls in, size and speed are in columns when horizontal",
+                    Orientation::static_type(),
+                    DEFAULT_ORIENTATION as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "speed-float",
+                    "Speed Float",
+                    "Fractional pixels per frame the image scrolls by, overrides speed unless 0",
+                    -f64::MAX,
+                    f64::MAX,
+                    DEFAULT_SPEED_FLOAT,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "velocity",
+                    "Velocity",
+                    "Pixels per second of stream time the image scrolls by independent of the framerate, overrides speed and speed-float unless 0",
+                    -f64::MAX,
+                    f64::MAX,
+                    DEFAULT_VELOCITY,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
                 glib::ParamSpecUInt::new(
-                    "foreground-color",
-                    "Foreground Color",
-                    "Foreground color to use (big-endian ARGB)",
+                    "offset",
+                    "Offset",
+                    "Position of the image when the stream starts, reads back the current position",
                     0,
                     u32::MAX,
-                    DEFAULT_FOREGROUND_COLOR,
-                    glib::ParamFlags::READWRITE,
+                    DEFAULT_OFFSET,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecEnum::new(
+                    "animation-mode",
+                    "Animation Mode",
+                    "What speed and speed-float are relative to, time based modes move by the same distance per second as at the first negotiated framerate",
+                    AnimationMode::static_type(),
+                    DEFAULT_ANIMATION_MODE as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecEnum::new(
+                    "wrap-mode",
+                    "Wrap Mode",
+                    "Whether the image wraps around at the end of the scroll period, bounces back or oscillates",
+                    WrapMode::static_type(),
+                    DEFAULT_WRAP_MODE as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
                 ),
                 glib::ParamSpecUInt::new(
-                    "background-color",
-                    "Background Color",
-                    "Background color to use (big-endian ARGB)",
+                    "softness",
+                    "Softness",
+                    "Pixels the edges of the bar are blended into the background over, 0 for hard edges",
                     0,
                     u32::MAX,
-                    DEFAULT_BACKGROUND_COLOR,
-                    glib::ParamFlags::READWRITE,
+                    DEFAULT_SOFTNESS,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "transparent-background",
+                    "Transparent Background",
+                    "Fill everything but the pattern with transparent black instead of background-color, opaque black without an alpha channel downstream",
+                    DEFAULT_TRANSPARENT_BACKGROUND,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                glib::ParamSpecDouble::new(
+                    "relative-size",
+                    "Relative Size",
+                    "Size of the bar as a fraction of the frame height, or width with horizontal orientation, overrides size unless 0",
+                    0.0,
+                    1.0,
+                    DEFAULT_RELATIVE_SIZE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "pause-motion",
+                    "Pause Motion",
+                    "Repeat the last picture while buffers keep flowing, the motion continues from there once unset",
+                    DEFAULT_PAUSE_MOTION,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "loop-duration",
+                    "Loop Duration",
+                    "Nanoseconds of stream time after which the animation starts over, 0 to never repeat",
+                    0,
+                    u64::MAX,
+                    DEFAULT_LOOP_DURATION,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "acceleration",
+                    "Acceleration",
+                    "Change of the scroll speed per frame, or per second with velocity, since the motion last changed or was reset",
+                    -f64::MAX,
+                    f64::MAX,
+                    DEFAULT_ACCELERATION,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecDouble::new(
+                    "max-speed",
+                    "Max Speed",
+                    "Limit of the scroll speed reached with acceleration in either direction, 0 for no limit",
+                    0.0,
+                    f64::MAX,
+                    DEFAULT_MAX_SPEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "mark-discont",
+                    "Mark Discont",
+                    "Invert the colors of the first mark-frames frames after starting, flushing or renegotiating",
+                    DEFAULT_MARK_DISCONT,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
                 ),
                 glib::ParamSpecUInt::new(
-                    "speed",
-                    "Speed",
-                    "Scroll image number of pixels per frame",
-                    u32::MIN,
+                    "mark-frames",
+                    "Mark Frames",
+                    "Number of frames marked after a discontinuity with mark-discont",
+                    0,
                     u32::MAX,
-                    DEFAULT_SPEED,
-                    glib::ParamFlags::READWRITE,
+                    DEFAULT_MARK_FRAMES,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
                 ),
                 glib::ParamSpecUInt::new(
-                    "size",
-                    "size",
-                    "Vertical width of horizontal bar",
-                    u32::MIN,
+                    "foreground-color-2",
+                    "Foreground Color 2",
+                    "Color the foreground color fades to and back from with fade-period (big-endian ARGB)",
+                    0,
                     u32::MAX,
-                    DEFAULT_SPEED,
-                    glib::ParamFlags::READWRITE,
+                    DEFAULT_FOREGROUND_COLOR_2,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | glib::ParamFlags::CONTROLLABLE
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "fade-period",
+                    "Fade Period",
+                    "Nanoseconds of running time the foreground color takes to fade to foreground-color-2 and again back, 0 to disable",
+                    0,
+                    u64::MAX,
+                    DEFAULT_FADE_PERIOD,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "amplitude",
+                    "Amplitude",
+                    "Pixels the image swings away from the center with the oscillate wrap mode",
+                    0,
+                    u32::MAX,
+                    DEFAULT_AMPLITUDE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "period",
+                    "Period",
+                    "Nanoseconds of stream time one swing takes with the oscillate wrap mode, 0 to stay at the center",
+                    0,
+                    u64::MAX,
+                    DEFAULT_PERIOD,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "step-frames",
+                    "Step Frames",
+                    "Only advance the animation every this many frames, the frames in between repeat the previous one exactly",
+                    1,
+                    u32::MAX,
+                    DEFAULT_STEP_FRAMES,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "jitter",
+                    "Jitter",
+                    "Largest number of pixels the image is drawn off its position in either direction, picked at random for every frame",
+                    0,
+                    u32::MAX,
+                    DEFAULT_JITTER,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "jitter-seed",
+                    "Jitter Seed",
+                    "Seed of the jitter, which only depends on it and the frame index, 0 to use the seed property",
+                    0,
+                    u64::MAX,
+                    DEFAULT_JITTER_SEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "transition-duration",
+                    "Transition Duration",
+                    "Nanoseconds of running time the old pattern cross-fades into the new one over when the pattern changes, 0 to cut",
+                    0,
+                    u64::MAX,
+                    DEFAULT_TRANSITION_DURATION,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecFlags::new(
+                    "channels",
+                    "Channels",
+                    "Color components the pattern is drawn into, the others are filled from the background color",
+                    Channels::static_type(),
+                    DEFAULT_CHANNELS.bits(),
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "border-width",
+                    "Border Width",
+                    "Thickness in pixels of a border drawn along the frame edges, 0 for none",
+                    0,
+                    u32::MAX,
+                    DEFAULT_BORDER_WIDTH,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "border-color",
+                    "Border Color",
+                    "Color of the border (big-endian ARGB)",
+                    0,
+                    u32::MAX,
+                    DEFAULT_BORDER_COLOR,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecEnum::new(
+                    "background-pattern",
+                    "Background Pattern",
+                    "Pattern drawn under the bar instead of the plain background color, translucent bars are blended over it",
+                    BackgroundPattern::static_type(),
+                    DEFAULT_BACKGROUND_PATTERN as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "seed",
+                    "Seed",
+                    "Seed of the corruption, the snow, value noise, grain and rain patterns and the jitter, set at start. 0 derives one from the time, reading back gives the seed in use",
+                    0,
+                    u64::MAX,
+                    DEFAULT_SEED,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "is-live",
+                    "Is Live",
+                    "Whether to act as a live source",
+                    DEFAULT_IS_LIVE,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "mirror-x",
+                    "Mirror X",
+                    "Flip the frame horizontally, labels included",
+                    DEFAULT_MIRROR,
+                    glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "mirror-y",
+                    "Mirror Y",
+                    "Flip the frame vertically, labels included",
+                    DEFAULT_MIRROR,
+                    glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                ),
+                gst::ParamSpecArray::new(
+                    "objects",
+                    "Objects",
+                    "Moving objects drawn instead of the bar, each a structure with shape (bar, rect, ball), size or width/height, color, x/y, velocity-x/velocity-y and motion (wrap, bounce) fields",
+                    Some(&glib::ParamSpecBoxed::new(
+                        "object",
+                        "Object",
+                        "Description of one moving object",
+                        gst::Structure::static_type(),
+                        glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                    )),
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                gst::ParamSpecArray::new(
+                    "cues",
+                    "Cues",
+                    "Pattern changes at running times, each a structure with a time in nanoseconds and a pattern field, the last one not after a frame is in effect",
+                    Some(&glib::ParamSpecBoxed::new(
+                        "cue",
+                        "Cue",
+                        "Running time and the pattern to switch to",
+                        gst::Structure::static_type(),
+                        glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                    )),
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "viewport-x",
+                    "Viewport X",
+                    "Left edge of the area the pattern is confined to",
+                    0,
+                    u32::MAX,
+                    0,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "viewport-y",
+                    "Viewport Y",
+                    "Top edge of the area the pattern is confined to",
+                    0,
+                    u32::MAX,
+                    0,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "viewport-width",
+                    "Viewport Width",
+                    "Width of the area the pattern is confined to (0 = whole frame), rounded down to even for subsampled formats",
+                    0,
+                    u32::MAX,
+                    0,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt::new(
+                    "viewport-height",
+                    "Viewport Height",
+                    "Height of the area the pattern is confined to (0 = whole frame), rounded down to even for subsampled formats",
+                    0,
+                    u32::MAX,
+                    0,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "viewport-clear",
+                    "Viewport Clear",
+                    "Fill the frame outside of the viewport with the background color instead of leaving it untouched",
+                    DEFAULT_VIEWPORT_CLEAR,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "verification",
+                    "Verification",
+                    "Post the digest of every frame in an element message and compare it against expected-digests",
+                    DEFAULT_VERIFICATION,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                glib::ParamSpecString::new(
+                    "expected-digests",
+                    "Expected Digests",
+                    "File with the expected frame digests, one hexadecimal digest per line",
+                    None,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                glib::ParamSpecString::new(
+                    "location",
+                    "Location",
+                    "PNG or JPEG file the image pattern scrolls",
+                    None,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                glib::ParamSpecEnum::new(
+                    "mismatch-action",
+                    "Mismatch Action",
+                    "What to do when a frame digest doesn't match the expected one",
+                    MismatchAction::static_type(),
+                    DEFAULT_MISMATCH_ACTION as i32,
+                    glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "meta-pad",
+                    "Meta Pad",
+                    "Add a \"meta\" pad pushing the pattern state of every frame (applied when going to READY)",
+                    DEFAULT_META_PAD,
+                    glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                ),
+                gst::ParamSpecArray::new(
+                    "timeline",
+                    "Timeline",
+                    "Keyframes applied at the first frame reaching their running time, each a structure with a \"time\" field and property values",
+                    Some(&glib::ParamSpecBoxed::new(
+                        "keyframe",
+                        "Keyframe",
+                        "Running time and the property values to apply",
+                        gst::Structure::static_type(),
+                        glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                    )),
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoolean::new(
+                    "timeline-loop",
+                    "Timeline Loop",
+                    "Restart the timeline once the last keyframe was applied, using its time as the new origin",
+                    DEFAULT_TIMELINE_LOOP,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoxed::new(
+                    "config-a",
+                    "Config A",
+                    "Property values applied for the first of every two ab-interval frame ranges",
+                    gst::Structure::static_type(),
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecBoxed::new(
+                    "config-b",
+                    "Config B",
+                    "Property values applied for the second of every two ab-interval frame ranges",
+                    gst::Structure::static_type(),
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "ab-interval",
+                    "A/B Interval",
+                    "Frames between switches of config-a and config-b (0 = disabled), frames carry a timestamp/x-rstestpattern-config reference timestamp meta naming their configuration",
+                    0,
+                    u64::MAX,
+                    DEFAULT_AB_INTERVAL,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                gst::ParamSpecArray::new(
+                    "cycle-resolutions",
+                    "Cycle Resolutions",
+                    "Resolutions to renegotiate to in turn, each a \"WxH@N/D\" string. Entries downstream refuses are skipped.",
+                    Some(&glib::ParamSpecString::new(
+                        "resolution",
+                        "Resolution",
+                        "Width, height and framerate as WxH@N/D",
+                        None,
+                        glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
+                    )),
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "cycle-interval",
+                    "Cycle Interval",
+                    "Frames between switches to the next of cycle-resolutions (0 = disabled)",
+                    0,
+                    u64::MAX,
+                    DEFAULT_CYCLE_INTERVAL,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_READY,
+                ),
+                glib::ParamSpecDouble::new(
+                    "corruption-probability",
+                    "Corruption Probability",
+                    "Probability of a frame being damaged on purpose and flagged as corrupted",
+                    0.0,
+                    1.0,
+                    DEFAULT_CORRUPTION_PROBABILITY,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecEnum::new(
+                    "corruption-mode",
+                    "Corruption Mode",
+                    "How frames are damaged",
+                    CorruptionMode::static_type(),
+                    DEFAULT_CORRUPTION_MODE as i32,
+                    glib::ParamFlags::READWRITE
+                        | glib::ParamFlags::EXPLICIT_NOTIFY
+                        | gst::PARAM_FLAG_MUTABLE_PLAYING,
+                ),
+                glib::ParamSpecUInt64::new(
+                    "corrupted-frames",
+                    "Corrupted Frames",
+                    "Number of frames damaged since the start",
+                    0,
+                    u64::MAX,
+                    0,
+                    glib::ParamFlags::READABLE,
+                ),
+                glib::ParamSpecBoxed::new(
+                    "state",
+                    "State",
+                    "Frame counters and animation position (reading), state to continue from on the next start (writing)",
+                    gst::Structure::static_type(),
+                    glib::ParamFlags::READWRITE | glib::ParamFlags::EXPLICIT_NOTIFY,
                 ),
             ]
         });
@@ -183,49 +3778,254 @@ impl ObjectImpl for TestPatternSrc {
 
     fn set_property(
         &self,
-        _obj: &Self::Type,
-        _id: usize,
+        obj: &Self::Type,
+        id: usize,
         value: &glib::Value,
         pspec: &glib::ParamSpec,
     ) {
-        let mut settings = self.settings.lock().unwrap();
+        // Properties changing along with this one
+        let linked: &[&str] = match pspec.name() {
+            "foreground-color" => &["foreground-color-string"],
+            "foreground-color-string" => &["foreground-color"],
+            "background-color" => &["background-color-string"],
+            "background-color-string" => &["background-color"],
+            "cues" => &["pattern"],
+            _ => &[],
+        };
+        let pspecs = std::iter::once(pspec.clone())
+            .chain(linked.iter().filter_map(|name| obj.find_property(name)))
+            .collect::<Vec<_>>();
+        let old = pspecs
+            .iter()
+            .map(|pspec| self.property(obj, id, pspec))
+            .collect::<Vec<_>>();
 
-        match pspec.name() {
-            "foreground-color" => {
-                settings.foreground_color = value.get().expect("type checked upstream");
-            }
-            "background-color" => {
-                settings.background_color = value.get().expect("type checked upstream");
-            }
-            "speed" => {
-                settings.speed = value.get().expect("type checked upstream");
-            }
-            "size" => {
-                settings.size = value.get().expect("type checked upstream");
+        self.update_property(obj, value, pspec);
+
+        // Only once the settings are unlocked again, handlers may read other
+        // properties
+        for (pspec, old) in pspecs.iter().zip(old) {
+            let new = self.property(obj, id, pspec);
+            let changed = unsafe {
+                glib::gobject_ffi::g_param_values_cmp(
+                    pspec.to_glib_none().0,
+                    old.to_glib_none().0,
+                    new.to_glib_none().0,
+                ) != 0
+            };
+            if changed {
+                obj.notify_by_pspec(pspec);
             }
-            _ => unimplemented!(),
         }
     }
 
     fn property(&self, _obj: &Self::Type, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
         let settings = self.settings.lock().unwrap();
         match pspec.name() {
+            "pattern" => settings.pattern.to_value(),
             "foreground-color" => {
                 settings.foreground_color.to_value()
             }
             "background-color" => {
                 settings.background_color.to_value()
             }
+            "foreground-color-string" => format!("#{:08x}", settings.foreground_color).to_value(),
+            "background-color-string" => format!("#{:08x}", settings.background_color).to_value(),
             "speed" => {
                 settings.speed.to_value()
             }
             "size" => {
                 settings.size.to_value()
             }
+            "checker-size" => settings.checker_size.to_value(),
+            "gradient-angle" => settings.gradient_angle.to_value(),
+            "octaves" => settings.octaves.to_value(),
+            "ball-radius" => settings.ball_radius.to_value(),
+            "gravity" => settings.gravity.to_value(),
+            "elasticity" => settings.elasticity.to_value(),
+            "particle-count" => settings.particle_count.to_value(),
+            "particle-min-speed" => settings.particle_min_speed.to_value(),
+            "particle-max-speed" => settings.particle_max_speed.to_value(),
+            "streak-length" => settings.streak_length.to_value(),
+            "kx" => settings.zone_plate.kx.to_value(),
+            "ky" => settings.zone_plate.ky.to_value(),
+            "kt" => settings.zone_plate.kt.to_value(),
+            "kx2" => settings.zone_plate.kx2.to_value(),
+            "ky2" => settings.zone_plate.ky2.to_value(),
+            "spokes" => settings.spokes.to_value(),
+            "star-radius" => settings.star_radius.to_value(),
+            "blink-interval" => settings.blink_interval.to_value(),
+            "hue-speed" => settings.hue_speed.to_value(),
+            "bar-count" => settings.bar_count.to_value(),
+            "bar-spacing" => settings.bar_spacing.to_value(),
+            "bar-colors" => gst::Array::from_values(
+                settings
+                    .bar_colors
+                    .iter()
+                    .map(|color| color.to_send_value()),
+            )
+            .to_value(),
+            "grid-spacing" => settings.grid_spacing.to_value(),
+            "line-width" => settings.line_width.to_value(),
+            "ring-width" => settings.ring_width.to_value(),
+            "frame-counter" => settings.frame_counter.to_value(),
+            "show-timecode" => settings.show_timecode.to_value(),
+            "code-size" => settings.code_size.to_value(),
+            "code-x" => settings.code_x.to_value(),
+            "code-y" => settings.code_y.to_value(),
+            "timestamp-stripe" => settings.timestamp_stripe.to_value(),
+            "stripe-height" => settings.stripe_height.to_value(),
+            "steps" => settings.steps.to_value(),
+            "step-rows" => settings.step_rows.to_value(),
+            "dither" => settings.dither.to_value(),
+            "action-safe" => settings.action_safe.to_value(),
+            "title-safe" => settings.title_safe.to_value(),
+            "hold-frames" => settings.hold_frames.to_value(),
+            "iterations" => settings.iterations.to_value(),
+            "arms" => settings.arms.to_value(),
+            "rotation-speed" => settings.rotation_speed.to_value(),
+            "trail-length" => settings.trail_length.to_value(),
+            "box-width" => settings.box_width.to_value(),
+            "box-height" => settings.box_height.to_value(),
+            "speed-x" => settings.speed_x.to_value(),
+            "speed-y" => settings.speed_y.to_value(),
+            "hue-step" => settings.hue_step.to_value(),
+            "hue-quantize" => settings.hue_quantize.to_value(),
+            "grain-intensity" => settings.grain_intensity.to_value(),
+            "grain-size" => settings.grain_size.to_value(),
+            "text" => settings.text.to_value(),
+            "text-x" => settings.text_x.to_value(),
+            "text-y" => settings.text_y.to_value(),
+            "text-scale" => settings.text_scale.to_value(),
+            "flash-period" => settings.flash_period.to_value(),
+            "pattern-left" => settings.pattern_left.to_value(),
+            "pattern-right" => settings.pattern_right.to_value(),
+            "margin-top" => settings.margin_top.to_value(),
+            "margin-bottom" => settings.margin_bottom.to_value(),
+            "orientation" => settings.orientation.to_value(),
+            "speed-float" => settings.speed_float.to_value(),
+            "velocity" => settings.velocity.to_value(),
+            "offset" => match settings.info {
+                Some(ref info) => settings
+                    .params()
+                    .fold_offset(settings.offset, info.width(), info.height())
+                    .to_value(),
+                None => settings.offset.to_value(),
+            },
+            "animation-mode" => settings.animation_mode.to_value(),
+            "wrap-mode" => settings.wrap_mode.to_value(),
+            "softness" => settings.softness.to_value(),
+            "transparent-background" => settings.transparent_background.to_value(),
+            "relative-size" => settings.relative_size.to_value(),
+            "pause-motion" => settings.pause_motion.to_value(),
+            "loop-duration" => settings.loop_duration.to_value(),
+            "acceleration" => settings.acceleration.to_value(),
+            "max-speed" => settings.max_speed.to_value(),
+            "mark-discont" => settings.mark_discont.to_value(),
+            "mark-frames" => settings.mark_frames.to_value(),
+            "foreground-color-2" => settings.foreground_color_2.to_value(),
+            "fade-period" => settings.fade_period.to_value(),
+            "amplitude" => settings.amplitude.to_value(),
+            "period" => settings.period.to_value(),
+            "step-frames" => settings.step_frames.to_value(),
+            "jitter" => settings.jitter.to_value(),
+            "jitter-seed" => settings.jitter_seed.to_value(),
+            "transition-duration" => settings.transition_duration.to_value(),
+            "channels" => settings.channels.to_value(),
+            "border-width" => settings.border_width.to_value(),
+            "border-color" => settings.border_color.to_value(),
+            "background-pattern" => settings.background_pattern.to_value(),
+            "seed" => settings.active_seed.to_value(),
+            "is-live" => settings.is_live.to_value(),
+            "mirror-x" => settings.mirror_x.to_value(),
+            "mirror-y" => settings.mirror_y.to_value(),
+            "objects" => gst::Array::from_values(
+                settings
+                    .object_structures
+                    .iter()
+                    .map(|structure| structure.to_send_value()),
+            )
+            .to_value(),
+            "cues" => {
+                gst::Array::from_values(settings.cues.iter().map(|(_, _, cue)| cue.to_send_value()))
+                    .to_value()
+            }
+            "viewport-x" => settings.viewport.x.to_value(),
+            "viewport-y" => settings.viewport.y.to_value(),
+            "viewport-width" => settings.viewport.width.to_value(),
+            "viewport-height" => settings.viewport.height.to_value(),
+            "viewport-clear" => settings.viewport_clear.to_value(),
+            "verification" => settings.verification.to_value(),
+            "expected-digests" => settings.expected_digests.to_value(),
+            "location" => settings.location.to_value(),
+            "mismatch-action" => settings.mismatch_action.to_value(),
+            "meta-pad" => settings.meta_enabled.to_value(),
+            "timeline" => gst::Array::from_values(
+                settings
+                    .timeline
+                    .iter()
+                    .map(|(_, keyframe)| keyframe.to_send_value()),
+            )
+            .to_value(),
+            "timeline-loop" => settings.timeline_loop.to_value(),
+            "config-a" => settings.config_a.to_value(),
+            "config-b" => settings.config_b.to_value(),
+            "ab-interval" => settings.ab_interval.to_value(),
+            "cycle-resolutions" => gst::Array::from_values(
+                settings
+                    .cycle_resolutions
+                    .iter()
+                    .map(|(s, _)| s.to_send_value()),
+            )
+            .to_value(),
+            "cycle-interval" => settings.cycle_interval.to_value(),
+            "corruption-probability" => settings.corruption_probability.to_value(),
+            "corruption-mode" => settings.corruption_mode.to_value(),
+            "corrupted-frames" => settings.corrupted_frames.to_value(),
+            "state" => settings.save_state().to_value(),
             _ => unimplemented!(),
         }
     }
 
+    // "draw" is emitted on the streaming thread for every frame of the
+    // custom pattern, after the frame was filled with black. The arguments
+    // are the PTS in nanoseconds, the width and height, the stride of the
+    // first plane and a pointer to its data, which is only valid during the
+    // emission.
+    //
+    // The "reset" action brings the offset, the balls and the noise back to
+    // their initial state from the next frame on, timestamps and frame
+    // numbers continue.
+    fn signals() -> &'static [glib::subclass::Signal] {
+        static SIGNALS: Lazy<Vec<glib::subclass::Signal>> = Lazy::new(|| {
+            vec![
+                glib::subclass::Signal::builder(
+                    "draw",
+                    &[
+                        u64::static_type().into(),
+                        u32::static_type().into(),
+                        u32::static_type().into(),
+                        i32::static_type().into(),
+                        glib::Pointer::static_type().into(),
+                    ],
+                    glib::Type::UNIT.into(),
+                )
+                .build(),
+                glib::subclass::Signal::builder("reset", &[], glib::Type::UNIT.into())
+                    .action()
+                    .class_handler(|_, args| {
+                        let element = args[0].get::<super::TestPatternSrc>().expect("signal arg");
+                        let src = TestPatternSrc::from_instance(&element);
+                        src.settings.lock().unwrap().reset_pending = true;
+                        None
+                    })
+                    .build(),
+            ]
+        });
+
+        SIGNALS.as_ref()
+    }
+
     fn constructed(&self, obj: &Self::Type) {
         self.parent_constructed(obj);
 
@@ -238,7 +4038,21 @@ impl ObjectImpl for TestPatternSrc {
 
         // we operate in time
         obj.set_format(gst::Format::Time);
-        obj.set_live(false);
+        obj.set_live(DEFAULT_IS_LIVE);
+
+        let srcpad = obj.static_pad("src").unwrap();
+        let element_weak = obj.downgrade();
+        srcpad.add_probe(
+            gst::PadProbeType::BUFFER
+                | gst::PadProbeType::EVENT_DOWNSTREAM
+                | gst::PadProbeType::EVENT_FLUSH,
+            move |_pad, info| {
+                if let Some(element) = element_weak.upgrade() {
+                    TestPatternSrc::from_instance(&element).forward_to_meta_pad(&element, info);
+                }
+                gst::PadProbeReturn::Ok
+            },
+        );
     }
 }
 
@@ -262,12 +4076,47 @@ impl ElementImpl for TestPatternSrc {
         Some(&*ELEMENT_METADATA)
     }
 
+    // The base class drops custom events sent to the element
+    fn send_event(&self, element: &Self::Type, event: gst::Event) -> bool {
+        match event.structure() {
+            Some(structure) if structure.name() == "set-test-pattern" => {
+                self.queue_change(element, structure)
+            }
+            _ => self.parent_send_event(element, event),
+        }
+    }
+
+    fn change_state(
+        &self,
+        element: &Self::Type,
+        transition: gst::StateChange,
+    ) -> Result<gst::StateChangeSuccess, gst::StateChangeError> {
+        match transition {
+            gst::StateChange::NullToReady => self.add_meta_pad(element)?,
+            gst::StateChange::ReadyToPaused => self.activate_meta_pad(true)?,
+            _ => (),
+        }
+
+        let ret = self.parent_change_state(element, transition)?;
+
+        match transition {
+            gst::StateChange::PausedToReady => self.activate_meta_pad(false)?,
+            gst::StateChange::ReadyToNull => self.remove_meta_pad(element),
+            _ => (),
+        }
+
+        Ok(ret)
+    }
+
     fn pad_templates() -> &'static [gst::PadTemplate] {
         static PAD_TEMPLATES: Lazy<Vec<gst::PadTemplate>> = Lazy::new(|| {
             let caps_raw = gst::Caps::builder("video/x-raw")
                 .field(
                     "format",
-                    gst_video::VideoFormat::Bgrx.to_str(),
+                    gst::List::new(&[
+                        &gst_video::VideoFormat::Bgrx.to_str(),
+                        &gst_video::VideoFormat::Bgra.to_str(),
+                    ]),
                 )
                 .field("width", gst::IntRange::new(0, i32::MAX))
                 .field("height", gst::IntRange::new(0, i32::MAX))
@@ -278,18 +4127,39 @@ impl ElementImpl for TestPatternSrc {
                         gst::Fraction::new(i32::MAX, 1),
                     ),
                 )
+                .field(
+                    "interlace-mode",
+                    gst::List::new(&[&"progressive", &"interleaved"]),
+                )
                 .build();
+
+            // Downstream can also composite the border, labels and codes
+            // itself, drawing them into the frame is preferred
+            let features = gst::CapsFeatures::new(&[OVERLAY_COMPOSITION_FEATURE]);
+            let mut composited = caps_raw.copy();
+            composited.make_mut().set_features_simple(Some(features));
+            let mut src_caps = caps_raw.copy();
+            src_caps.make_mut().append(composited);
+
             // The src pad template must be named "src" for pushsrc
             // and specific a pad that is always there
             let src_pad_template = gst::PadTemplate::new(
                 "src",
                 gst::PadDirection::Src,
                 gst::PadPresence::Always,
-                &caps_raw,
+                &src_caps,
+            )
+            .unwrap();
+
+            let meta_pad_template = gst::PadTemplate::new(
+                "meta",
+                gst::PadDirection::Src,
+                gst::PadPresence::Sometimes,
+                &gst::Caps::builder("application/x-pattern-state").build(),
             )
             .unwrap();
 
-            vec![src_pad_template]
+            vec![src_pad_template, meta_pad_template]
         });
 
         PAD_TEMPLATES.as_ref()
@@ -297,7 +4167,7 @@ impl ElementImpl for TestPatternSrc {
 }
 
 impl BaseSrcImpl for TestPatternSrc {
-    fn set_caps(&self, _element: &Self::Type, caps: &gst::Caps) -> Result<(), gst::LoggableError> {
+    fn set_caps(&self, element: &Self::Type, caps: &gst::Caps) -> Result<(), gst::LoggableError> {
         let mut settings = self.settings.lock().unwrap();
         let structure = caps.structure(0).unwrap();
 
@@ -306,13 +4176,101 @@ impl BaseSrcImpl for TestPatternSrc {
         } else {
             return Err(gst::loggable_error!(CAT, "unsupported caps: {}", caps));
         };
+
+        if settings.has_alpha() && !info.format_info().has_alpha() {
+            gst::info!(
+                CAT,
+                obj: element,
+                "Downstream doesn't accept alpha, blending against the background"
+            );
+        }
+
+        if let Some(target) = settings.cycle_target.take() {
+            let negotiated = Resolution {
+                width: info.width() as i32,
+                height: info.height() as i32,
+                fps: info.fps(),
+            };
+            if negotiated != target {
+                gst::warning!(
+                    CAT,
+                    obj: element,
+                    "Downstream refused {:?}, skipping to {:?}",
+                    target,
+                    negotiated
+                );
+            }
+        }
+
+        // Keep the bar at the same relative position: the motion continues
+        // from where it would have been in the next frame, scaled to the
+        // new size
+        let old_size = settings
+            .info
+            .as_ref()
+            .map(|old| (old.width(), old.height()));
+        if let Some((old_width, old_height)) = old_size {
+            let (length, old_length) = match settings.orientation {
+                Orientation::Vertical => (info.height(), old_height),
+                Orientation::Horizontal => (info.width(), old_width),
+            };
+            settings.anchor();
+            if old_length > 0 {
+                settings.origin.2 =
+                    (settings.origin.2 as u64 * length as u64 / old_length as u64) as u32;
+            }
+            settings.offset = settings.origin.2;
+        }
+        let period = settings.params().motion_period(info.width(), info.height());
+        settings.offset %= period;
+        settings.origin.2 %= period;
+        if settings.animation_rate.is_none() && info.fps().numer() > 0 {
+            settings.animation_rate = Some(info.fps());
+        }
+        settings.field_order = match info.interlace_mode() {
+            gst_video::VideoInterlaceMode::Interleaved => {
+                match structure.get::<&str>("field-order") {
+                    Ok("bottom-field-first") => Some(pattern::FieldOrder::BottomFirst),
+                    _ => Some(pattern::FieldOrder::TopFirst),
+                }
+            }
+            _ => None,
+        };
+        if settings.channels != Channels::all() && !info.format_info().is_rgb() {
+            gst::warning!(
+                CAT,
+                obj: element,
+                "Channels {:?} only apply to RGB formats, drawing into all of {}",
+                settings.channels,
+                info.format()
+            );
+        }
+        settings.attach_composition = caps.features(0).map_or(false, |features| {
+            features.contains(OVERLAY_COMPOSITION_FEATURE)
+        });
+        gst::debug!(
+            CAT,
+            obj: element,
+            "Attaching overlay composition: {}",
+            settings.attach_composition
+        );
+        // Tables and static patterns are built now instead of delaying the
+        // first frame
+        if let Some(format) = video::pattern_format(info.format()) {
+            let layout = pattern::FrameLayout::new(format, info.width(), info.height());
+            let params = settings.frame_params(&info, settings.accum_rtime + settings.running_time);
+            self.renderer.lock().unwrap().prepare(&params, &layout);
+        }
         settings.info = Some(info);
 
-        settings.accum_rtime = settings.running_time;
-        settings.accum_frames = settings.n_frames;
+        settings.accum_rtime += settings.running_time;
+        settings.accum_frames += settings.n_frames;
 
         settings.running_time = gst::ClockTime::ZERO;
         settings.n_frames = 0;
+        settings.marks_left = settings.mark_frames;
+        // The old pattern can't be blended into frames of another size
+        settings.transition = None;
 
         Ok(())
     }
@@ -320,24 +4278,46 @@ impl BaseSrcImpl for TestPatternSrc {
     fn fixate(&self, element: &Self::Type, mut caps: gst::Caps) -> gst::Caps {
         let settings = self.settings.lock().unwrap();
 
-        /* Check if foreground color has alpha, if it is the case,
-         * force color format with an alpha channel downstream */
-        if settings.foreground_color >> 24 != 255 {
-            gst::loggable_error!(CAT, "foreground + alpha not (yet) supported");
-            return caps;
-        }
+        // Translucent colors prefer a format with an alpha channel, without
+        // one they are blended against the background
+        let format = if settings.has_alpha() {
+            gst_video::VideoFormat::Bgra
+        } else {
+            gst_video::VideoFormat::Bgrx
+        };
+        let cycle_target = settings.cycle_target;
         drop(settings);
 
         {
             let caps = caps.make_mut();
             let s = caps.structure_mut(0).unwrap();
-            s.fixate_field_nearest_int("width", 320);
-            s.fixate_field_nearest_int("height", 240);
+            let (width, height, fps) = match cycle_target {
+                Some(target) => (target.width, target.height, target.fps),
+                None => (320, 240, gst::Fraction::new(30, 1)),
+            };
+            s.fixate_field_str("format", format.to_str());
+            s.fixate_field_nearest_int("width", width);
+            s.fixate_field_nearest_int("height", height);
 
             if s.has_field("framerate") {
-                s.fixate_field_nearest_fraction("framerate", gst::Fraction::new(30, 1));
+                s.fixate_field_nearest_fraction("framerate", fps);
+            } else {
+                s.set("framerate", fps);
+            }
+
+            // Interlaced only if downstream asks for it, top field first
+            // unless it says otherwise
+            if s.has_field("interlace-mode") {
+                s.fixate_field_str("interlace-mode", "progressive");
+            }
+            if matches!(s.get::<&str>("interlace-mode"), Ok("interleaved")) {
+                if s.has_field("field-order") {
+                    s.fixate_field_str("field-order", "top-field-first");
+                } else {
+                    s.set("field-order", "top-field-first");
+                }
             } else {
-                s.set("framerate", gst::Fraction::new(30, 1));
+                s.remove_field("field-order");
             }
 
             // if s.has_field("pixel-aspect-ratio") {
@@ -350,12 +4330,43 @@ impl BaseSrcImpl for TestPatternSrc {
         self.parent_fixate(element, caps)
     }
 
-    fn start(&self, _element: &Self::Type) -> Result<(), gst::ErrorMessage> {
+    fn start(&self, element: &Self::Type) -> Result<(), gst::ErrorMessage> {
         let mut settings = self.settings.lock().unwrap();
         settings.running_time = gst::ClockTime::ZERO;
         settings.n_frames = 0;
         settings.accum_frames = 0;
+        settings.offset = settings.start_offset;
+        settings.origin = (0, gst::ClockTime::ZERO, settings.start_offset);
+        settings.animation_rate = None;
         settings.accum_rtime = gst::ClockTime::ZERO;
+        settings.timeline_pos = 0;
+        settings.timeline_origin = gst::ClockTime::ZERO;
+        settings.corrupted_frames = 0;
+        settings.active_seed = match settings.seed {
+            0 => std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(1, |time| (time.as_nanos() as u64).max(1)),
+            seed => seed,
+        };
+        gst::info!(CAT, obj: element, "Using seed {}", settings.active_seed);
+        settings.rng = pattern::Rng::new(settings.active_seed);
+        settings.noise_rng = settings.initial_noise_rng();
+        settings.reset_motion();
+        settings.particles_spawned = None;
+        settings.marks_left = settings.mark_frames;
+        settings.transition = None;
+        settings.cycle_pos = 0;
+        settings.cycle_target = None;
+        settings.ab_active = None;
+
+        if let Some(state) = settings.restore_state.take() {
+            settings.apply_state(&state).map_err(|err| {
+                gst::error_msg!(
+                    gst::LibraryError::Settings,
+                    ["Invalid state {}: {}", state, err]
+                )
+            })?;
+        }
 
         let info = gst_video::VideoInfo::builder(gst_video::VideoFormat::Rgba, 320, 240)
             .views(1)
@@ -367,9 +4378,99 @@ impl BaseSrcImpl for TestPatternSrc {
             .unwrap();
 
         settings.info = Some(info);
+
+        settings.expected = match settings.expected_digests {
+            Some(ref location) => Self::load_digests(location)?,
+            None => Vec::new(),
+        };
+        settings.image = match settings.location {
+            Some(ref location) => Some(Arc::new(Self::load_image(location)?)),
+            None => None,
+        };
         Ok(())
     }
 
+    fn event(&self, element: &Self::Type, event: &gst::Event) -> bool {
+        match event.view() {
+            gst::EventView::CustomUpstream(_) | gst::EventView::CustomBoth(_) => {
+                if let Some(structure) = event
+                    .structure()
+                    .filter(|structure| structure.name() == "set-test-pattern")
+                {
+                    return self.queue_change(element, structure);
+                }
+            }
+            gst::EventView::FlushStop(_) => {
+                let mut settings = self.settings.lock().unwrap();
+                settings.reset_motion();
+                settings.marks_left = settings.mark_frames;
+            }
+            _ => (),
+        }
+
+        self.parent_event(element, event)
+    }
+
+    fn is_seekable(&self, _element: &Self::Type) -> bool {
+        true
+    }
+
+    // The base class sends the flush and segment events carrying the seqnum
+    // of the seek, here only the frame counters are moved to the new position
+    fn do_seek(&self, element: &Self::Type, segment: &mut gst::Segment) -> bool {
+        let segment = match segment.downcast_mut::<gst::ClockTime>() {
+            Some(segment) => segment,
+            None => return false,
+        };
+        segment.set_time(segment.start());
+        let position = segment.position().unwrap_or(gst::ClockTime::ZERO);
+
+        let mut settings = self.settings.lock().unwrap();
+        let info = settings.info.to_owned().unwrap();
+        let fps = info.fps();
+
+        settings.accum_frames = 0;
+        settings.accum_rtime = gst::ClockTime::ZERO;
+        if fps.numer() > 0 {
+            settings.n_frames = unsafe {
+                ffi::gst_util_uint64_scale(
+                    position.nseconds(),
+                    fps.numer() as u64,
+                    fps.denom() as u64 * gst::ClockTime::SECOND.nseconds(),
+                )
+            };
+            settings.running_time = gst::ClockTime::from_nseconds(unsafe {
+                ffi::gst_util_uint64_scale(
+                    settings.n_frames,
+                    fps.denom() as u64 * gst::ClockTime::SECOND.nseconds(),
+                    fps.numer() as u64,
+                )
+            });
+        } else {
+            settings.n_frames = 0;
+            settings.running_time = gst::ClockTime::ZERO;
+        }
+
+        // Place the pattern where an uninterrupted stream would have it
+        if info.height() > 0 {
+            let period = settings.params().motion_period(info.width(), info.height());
+            let (frame_index, time) = (settings.n_frames, settings.running_time);
+            if let Some((offset, _)) = settings.position(frame_index, time, period) {
+                settings.offset = offset;
+            }
+        }
+        settings.reset_motion();
+
+        gst::debug!(
+            CAT,
+            obj: element,
+            "Seeked to {} (frame {})",
+            position,
+            settings.n_frames
+        );
+        true
+    }
+
     fn decide_allocation(
         &self,
         element: &Self::Type,
@@ -439,6 +4540,29 @@ impl BaseSrcImpl for TestPatternSrc {
             None => gst::Buffer::with_size((info.width() * info.height() * 4) as usize).map_err(|_| gst::FlowError::Error),
         }
     }
+
+    // Buffers cover the time from their PTS until PTS + duration. In live
+    // mode the PTS is the running time the frame was captured at, so the
+    // base class waits for it and the end before pushing, in non-live mode
+    // the same times let downstream sync on the stream position.
+    fn times(
+        &self,
+        element: &Self::Type,
+        buffer: &gst::BufferRef,
+    ) -> (Option<gst::ClockTime>, Option<gst::ClockTime>) {
+        let start = match buffer.pts() {
+            Some(start) => start,
+            None => return (gst::ClockTime::NONE, gst::ClockTime::NONE),
+        };
+        let end = buffer.duration().map(|duration| start + duration);
+
+        if element.is_live() {
+            // Without a duration a live frame is only complete at its start
+            (Some(start), end.or(Some(start)))
+        } else {
+            (Some(start), end)
+        }
+    }
 }
 
 impl PushSrcImpl for TestPatternSrc {
@@ -447,20 +4571,84 @@ impl PushSrcImpl for TestPatternSrc {
         element: &Self::Type,
         buffer: &mut gst::BufferRef,
     ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let (pts, keyframes) = {
+            let mut settings = self.settings.lock().unwrap();
+            let pts = settings.accum_rtime + settings.running_time;
+            let frame_index = settings.accum_frames + settings.n_frames;
+            settings.apply_cues(pts);
+
+            let mut keyframes = settings.due_keyframes(pts);
+            // Last, so the configuration wins over keyframes of the same frame
+            keyframes.extend(settings.ab_switch(frame_index));
+            (pts, keyframes)
+        };
+
+        // Both go through set_property(), so the settings must not be locked.
+        // They have to run before the settings are read for this frame, so
+        // controlled values apply to the buffer at their own PTS.
+        element.sync_values(pts).unwrap();
+        for keyframe in &keyframes {
+            self.apply_keyframe(element, keyframe);
+        }
+
         let mut settings = self.settings.lock().unwrap();
         let info = settings.info.to_owned().unwrap();
-        let pts = settings.accum_rtime + settings.running_time;
-        element.sync_values(pts).unwrap();
+        let now = match settings.animation_mode {
+            AnimationMode::WallTime => element.clock().and_then(|clock| clock.time()),
+            _ => Some(pts),
+        };
 
+        let frame_index = settings.accum_frames + settings.n_frames;
         buffer.set_pts(pts);
-        self.fill_image(buffer, &mut settings)?;
+        self.fill_image(buffer, now, &mut settings)?;
+        // After rendering, which positions the bar for this frame
+        if settings.meta_enabled {
+            settings.meta_state = Some(settings.pattern_state(frame_index));
+        }
+        if settings.pattern == Pattern::Custom {
+            // Handlers may access the properties
+            drop(settings);
+            Self::emit_draw(element, buffer, &info)?;
+            settings = self.settings.lock().unwrap();
+        }
+        if let Some(field_order) = settings.field_order {
+            let mut flags = gst_video::VideoBufferFlags::INTERLACED;
+            if field_order == pattern::FieldOrder::TopFirst {
+                flags |= gst_video::VideoBufferFlags::TFF;
+            }
+            buffer.set_flags(gst::BufferFlags::from_bits_truncate(flags.bits()));
+        }
+
+        let fps = info.fps();
+        let flash = settings.pattern == Pattern::Flash
+            && pattern::is_flash_frame(
+                pts.nseconds(),
+                (fps.numer() as u32, fps.denom() as u32),
+                settings.flash_period,
+            );
+
+        let digest = if settings.verification {
+            Some(Self::frame_digest(buffer, &info)?)
+        } else {
+            None
+        };
+        Self::corrupt(element, buffer, &mut settings)?;
+
+        if let Some(config) = settings.ab_active {
+            let reference = gst::Caps::builder("timestamp/x-rstestpattern-config")
+                .field("config", config)
+                .build();
+            gst::ReferenceTimestampMeta::add(buffer, &reference, pts, gst::ClockTime::NONE);
+        }
+
+        let expected = settings.expected.get(frame_index as usize).copied();
+        let mismatch_action = settings.mismatch_action;
 
         buffer.set_dts(gst::ClockTime::NONE);
-        buffer.set_offset(settings.accum_frames + settings.n_frames);
+        buffer.set_offset(frame_index);
         settings.n_frames += 1;
         buffer.set_offset_end(buffer.offset() + 1);
 
-        let fps = info.fps();
                 let next_time = unsafe {
             ffi::gst_util_uint64_scale(
                 settings.n_frames,
@@ -472,6 +4660,32 @@ impl PushSrcImpl for TestPatternSrc {
         buffer.set_duration(next_time - settings.running_time);
         settings.running_time = next_time;
 
+        // Renegotiates before the next frame, the counters are accumulated
+        // in set_caps() so timestamps continue
+        let n_resolutions = settings.cycle_resolutions.len();
+        if settings.cycle_interval > 0
+            && n_resolutions > 0
+            && (frame_index + 1) % settings.cycle_interval == 0
+        {
+            let target = settings.cycle_resolutions[settings.cycle_pos % n_resolutions].1;
+            gst::debug!(CAT, obj: element, "Switching to {:?}", target);
+            settings.cycle_target = Some(target);
+            settings.cycle_pos = (settings.cycle_pos + 1) % n_resolutions;
+            element.src_pad().mark_reconfigure();
+        }
+        drop(settings);
+
+        if flash {
+            let s = gst::Structure::builder("rstestpattern-flash")
+                .field("frame-index", frame_index)
+                .field("pts", pts)
+                .build();
+            let _ = element.post_message(gst::message::Element::builder(s).src(element).build());
+        }
+        if let Some(digest) = digest {
+            self.verify_frame(element, frame_index, pts, digest, expected, mismatch_action)?;
+        }
+
         Ok(gst::FlowSuccess::Ok)
     }
 }
@@ -11,16 +11,347 @@
 use gst::glib;
 use gst::prelude::*;
 
+use crate::pattern;
+
+mod colorbalance;
 mod imp;
+mod orientation;
+
+// Picture drawn into every frame
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstRsTestPatternPattern")]
+pub enum Pattern {
+    #[enum_value(name = "Scrolling horizontal bar", nick = "bar")]
+    Bar = 0,
+    #[enum_value(name = "SMPTE 75% color bars", nick = "smpte")]
+    Smpte = 1,
+    #[enum_value(name = "EBU 100/0/75/0 color bars", nick = "ebu-75")]
+    Ebu75 = 2,
+    #[enum_value(name = "EBU 100/0/100/0 color bars", nick = "ebu-100")]
+    Ebu100 = 3,
+    #[enum_value(name = "SMPTE RP 219 HD color bars", nick = "smpte-rp219")]
+    Rp219 = 4,
+    #[enum_value(name = "Checkerboard", nick = "checkers")]
+    Checkers = 5,
+    #[enum_value(name = "Horizontal gradient", nick = "gradient-horizontal")]
+    HorizontalGradient = 6,
+    #[enum_value(name = "Vertical gradient", nick = "gradient-vertical")]
+    VerticalGradient = 7,
+    #[enum_value(name = "Gradient along gradient-angle", nick = "gradient-angled")]
+    AngledGradient = 8,
+    #[enum_value(name = "Random noise", nick = "snow")]
+    Snow = 9,
+    #[enum_value(name = "Smooth animated noise", nick = "value-noise")]
+    ValueNoise = 10,
+    #[enum_value(name = "Ball moving on a Lissajous path", nick = "ball")]
+    Ball = 11,
+    #[enum_value(name = "Ball bouncing under gravity", nick = "bouncing-ball")]
+    BouncingBall = 12,
+    #[enum_value(name = "Falling rain streaks", nick = "rain")]
+    Rain = 13,
+    #[enum_value(name = "Zone plate", nick = "zone-plate")]
+    ZonePlate = 14,
+    #[enum_value(name = "Siemens star", nick = "siemens-star")]
+    SiemensStar = 15,
+    #[enum_value(
+        name = "Frames alternating between foreground and background color",
+        nick = "blink"
+    )]
+    Blink = 16,
+    #[enum_value(name = "Crosshatch of foreground colored lines", nick = "grid")]
+    Grid = 17,
+    #[enum_value(name = "Concentric rings", nick = "circles")]
+    Circles = 18,
+    #[enum_value(name = "Gray steps from black to white", nick = "staircase")]
+    Staircase = 19,
+    #[enum_value(name = "Grid of ColorChecker reference colors", nick = "color-checker")]
+    ColorChecker = 20,
+    #[enum_value(name = "Smooth gray ramp", nick = "ramp")]
+    Ramp = 21,
+    #[enum_value(name = "Line pairs of increasing frequency", nick = "line-pairs")]
+    LinePairs = 22,
+    #[enum_value(name = "Action and title safe area markers", nick = "safe-area")]
+    SafeArea = 23,
+    #[enum_value(name = "Single pixel moving in raster order", nick = "moving-pixel")]
+    MovingPixel = 24,
+    #[enum_value(name = "Full frame color cycle", nick = "color-cycle")]
+    ColorCycle = 25,
+    #[enum_value(
+        name = "Field order identification for interlaced output",
+        nick = "field-identification"
+    )]
+    FieldIdentification = 26,
+    #[enum_value(name = "Checkerboard of single red and blue pixels", nick = "chroma-checker")]
+    ChromaChecker = 27,
+    #[enum_value(name = "Vertical edge moving at constant speed", nick = "moving-edge")]
+    MovingEdge = 28,
+    #[enum_value(name = "Animated plasma", nick = "plasma")]
+    Plasma = 29,
+    #[enum_value(name = "Zooming Mandelbrot set", nick = "mandelbrot")]
+    Mandelbrot = 30,
+    #[enum_value(name = "Rotating Archimedean spiral", nick = "spiral")]
+    Spiral = 31,
+    #[enum_value(name = "Moving box bouncing off the frame edges", nick = "moving-box")]
+    MovingBox = 32,
+    #[enum_value(name = "Image loaded from the location property", nick = "image")]
+    Image = 33,
+    #[enum_value(name = "Drawn by a draw signal handler", nick = "custom")]
+    Custom = 34,
+    #[enum_value(
+        name = "Single saturated color rotating around the vectorscope",
+        nick = "color-vectors"
+    )]
+    ColorVectors = 35,
+    #[enum_value(name = "Film grain on the foreground color", nick = "grain")]
+    Grain = 36,
+    #[enum_value(name = "PM5544 style monitor test card", nick = "test-card")]
+    TestCard = 37,
+    #[enum_value(name = "Single white flash frame per period for A/V sync", nick = "flash")]
+    Flash = 38,
+    #[enum_value(name = "Two patterns side by side", nick = "split")]
+    Split = 39,
+    #[enum_value(name = "Rotating wireframe cube", nick = "cube")]
+    Cube = 40,
+}
+
+impl From<Pattern> for pattern::Pattern {
+    fn from(pattern: Pattern) -> Self {
+        match pattern {
+            Pattern::Bar => pattern::Pattern::Bar,
+            Pattern::Smpte => pattern::Pattern::Smpte,
+            Pattern::Ebu75 => pattern::Pattern::Ebu75,
+            Pattern::Ebu100 => pattern::Pattern::Ebu100,
+            Pattern::Rp219 => pattern::Pattern::Rp219,
+            Pattern::Checkers => pattern::Pattern::Checkers,
+            Pattern::HorizontalGradient => pattern::Pattern::HorizontalGradient,
+            Pattern::VerticalGradient => pattern::Pattern::VerticalGradient,
+            Pattern::AngledGradient => pattern::Pattern::AngledGradient,
+            Pattern::Snow => pattern::Pattern::Snow,
+            Pattern::ValueNoise => pattern::Pattern::ValueNoise,
+            Pattern::Ball => pattern::Pattern::Ball,
+            Pattern::BouncingBall => pattern::Pattern::BouncingBall,
+            Pattern::Rain => pattern::Pattern::Rain,
+            Pattern::ZonePlate => pattern::Pattern::ZonePlate,
+            Pattern::SiemensStar => pattern::Pattern::SiemensStar,
+            Pattern::Blink => pattern::Pattern::Blink,
+            Pattern::Grid => pattern::Pattern::Grid,
+            Pattern::Circles => pattern::Pattern::Circles,
+            Pattern::Staircase => pattern::Pattern::Staircase,
+            Pattern::ColorChecker => pattern::Pattern::ColorChecker,
+            Pattern::Ramp => pattern::Pattern::Ramp,
+            Pattern::LinePairs => pattern::Pattern::LinePairs,
+            Pattern::SafeArea => pattern::Pattern::SafeArea,
+            Pattern::MovingPixel => pattern::Pattern::MovingPixel,
+            Pattern::ColorCycle => pattern::Pattern::ColorCycle,
+            Pattern::FieldIdentification => pattern::Pattern::FieldIdentification,
+            Pattern::ChromaChecker => pattern::Pattern::ChromaChecker,
+            Pattern::MovingEdge => pattern::Pattern::MovingEdge,
+            Pattern::Plasma => pattern::Pattern::Plasma,
+            Pattern::Mandelbrot => pattern::Pattern::Mandelbrot,
+            Pattern::Spiral => pattern::Pattern::Spiral,
+            Pattern::MovingBox => pattern::Pattern::MovingBox,
+            Pattern::Image => pattern::Pattern::Image,
+            Pattern::Custom => pattern::Pattern::Custom,
+            Pattern::ColorVectors => pattern::Pattern::ColorVectors,
+            Pattern::Grain => pattern::Pattern::Grain,
+            Pattern::TestCard => pattern::Pattern::TestCard,
+            Pattern::Flash => pattern::Pattern::Flash,
+            Pattern::Split => pattern::Pattern::Split,
+            Pattern::Cube => pattern::Pattern::Cube,
+        }
+    }
+}
+
+// Corner a burned-in label is drawn into
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstRsTestPatternCorner")]
+pub enum Corner {
+    #[enum_value(name = "Not drawn", nick = "none")]
+    None = 0,
+    #[enum_value(name = "Top left corner", nick = "top-left")]
+    TopLeft = 1,
+    #[enum_value(name = "Top right corner", nick = "top-right")]
+    TopRight = 2,
+    #[enum_value(name = "Bottom left corner", nick = "bottom-left")]
+    BottomLeft = 3,
+    #[enum_value(name = "Bottom right corner", nick = "bottom-right")]
+    BottomRight = 4,
+}
+
+impl From<Corner> for Option<pattern::Corner> {
+    fn from(corner: Corner) -> Self {
+        match corner {
+            Corner::None => None,
+            Corner::TopLeft => Some(pattern::Corner::TopLeft),
+            Corner::TopRight => Some(pattern::Corner::TopRight),
+            Corner::BottomLeft => Some(pattern::Corner::BottomLeft),
+            Corner::BottomRight => Some(pattern::Corner::BottomRight),
+        }
+    }
+}
+
+// Pattern the bar scrolls over
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstRsTestPatternBackgroundPattern")]
+pub enum BackgroundPattern {
+    #[enum_value(name = "Plain background color", nick = "none")]
+    None = 0,
+    #[enum_value(name = "SMPTE 75% color bars", nick = "smpte")]
+    Smpte = 1,
+    #[enum_value(name = "EBU 100/0/75/0 color bars", nick = "ebu-75")]
+    Ebu75 = 2,
+    #[enum_value(name = "EBU 100/0/100/0 color bars", nick = "ebu-100")]
+    Ebu100 = 3,
+    #[enum_value(name = "SMPTE RP 219 HD color bars", nick = "smpte-rp219")]
+    Rp219 = 4,
+    #[enum_value(name = "Checkerboard", nick = "checkers")]
+    Checkers = 5,
+    #[enum_value(name = "Horizontal gradient", nick = "gradient-horizontal")]
+    HorizontalGradient = 6,
+    #[enum_value(name = "Vertical gradient", nick = "gradient-vertical")]
+    VerticalGradient = 7,
+    #[enum_value(name = "Random noise", nick = "snow")]
+    Snow = 8,
+    #[enum_value(name = "Zone plate", nick = "zone-plate")]
+    ZonePlate = 9,
+    #[enum_value(name = "Gray steps from black to white", nick = "staircase")]
+    Staircase = 10,
+    #[enum_value(name = "Grid of ColorChecker reference colors", nick = "color-checker")]
+    ColorChecker = 11,
+    #[enum_value(name = "PM5544 style monitor test card", nick = "test-card")]
+    TestCard = 12,
+}
+
+impl From<BackgroundPattern> for Option<pattern::Pattern> {
+    fn from(pattern: BackgroundPattern) -> Self {
+        match pattern {
+            BackgroundPattern::None => None,
+            BackgroundPattern::Smpte => Some(pattern::Pattern::Smpte),
+            BackgroundPattern::Ebu75 => Some(pattern::Pattern::Ebu75),
+            BackgroundPattern::Ebu100 => Some(pattern::Pattern::Ebu100),
+            BackgroundPattern::Rp219 => Some(pattern::Pattern::Rp219),
+            BackgroundPattern::Checkers => Some(pattern::Pattern::Checkers),
+            BackgroundPattern::HorizontalGradient => Some(pattern::Pattern::HorizontalGradient),
+            BackgroundPattern::VerticalGradient => Some(pattern::Pattern::VerticalGradient),
+            BackgroundPattern::Snow => Some(pattern::Pattern::Snow),
+            BackgroundPattern::ZonePlate => Some(pattern::Pattern::ZonePlate),
+            BackgroundPattern::Staircase => Some(pattern::Pattern::Staircase),
+            BackgroundPattern::ColorChecker => Some(pattern::Pattern::ColorChecker),
+            BackgroundPattern::TestCard => Some(pattern::Pattern::TestCard),
+        }
+    }
+}
+
+// Direction the bar scrolls in
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstRsTestPatternOrientation")]
+pub enum Orientation {
+    #[enum_value(name = "Horizontal bar scrolling down", nick = "vertical")]
+    Vertical = 0,
+    #[enum_value(name = "Vertical bar scrolling right", nick = "horizontal")]
+    Horizontal = 1,
+}
+
+impl From<Orientation> for pattern::Orientation {
+    fn from(orientation: Orientation) -> Self {
+        match orientation {
+            Orientation::Vertical => pattern::Orientation::Vertical,
+            Orientation::Horizontal => pattern::Orientation::Horizontal,
+        }
+    }
+}
+
+// What the scrolling motion advances with
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstRsTestPatternAnimationMode")]
+pub enum AnimationMode {
+    #[enum_value(name = "Move with every produced frame", nick = "frames")]
+    Frames = 0,
+    #[enum_value(name = "Move with the buffer running time", nick = "running-time")]
+    RunningTime = 1,
+    #[enum_value(name = "Move with the time of the element clock", nick = "wall-time")]
+    WallTime = 2,
+}
+
+// What the image does at the end of the scroll period
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstRsTestPatternWrapMode")]
+pub enum WrapMode {
+    #[enum_value(name = "Wrap around to the start", nick = "wrap")]
+    Wrap = 0,
+    #[enum_value(name = "Reverse the direction at the edges", nick = "bounce")]
+    Bounce = 1,
+    #[enum_value(name = "Swing sinusoidally around the center", nick = "oscillate")]
+    Oscillate = 2,
+}
+
+impl From<WrapMode> for pattern::Motion {
+    fn from(mode: WrapMode) -> Self {
+        match mode {
+            WrapMode::Wrap => pattern::Motion::Wrap,
+            WrapMode::Bounce => pattern::Motion::Bounce,
+            // The position is computed from the time up front and never
+            // reaches an edge
+            WrapMode::Oscillate => pattern::Motion::Wrap,
+        }
+    }
+}
+
+// What to do when a frame doesn't match its expected digest
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstRsTestPatternMismatchAction")]
+pub enum MismatchAction {
+    #[enum_value(name = "Post a warning and continue", nick = "warning")]
+    Warning = 0,
+    #[enum_value(name = "Post an error and stop streaming", nick = "error")]
+    Error = 1,
+}
+
+// How frames are damaged when corruption is injected
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "GstRsTestPatternCorruptionMode")]
+pub enum CorruptionMode {
+    #[enum_value(name = "Flip random bits of the frame", nick = "bit-flips")]
+    BitFlips = 0,
+    #[enum_value(name = "Zero a band of lines", nick = "zeroed-lines")]
+    ZeroedLines = 1,
+    #[enum_value(name = "Cut the buffer to half its size", nick = "truncated-buffer")]
+    TruncatedBuffer = 2,
+    #[enum_value(name = "Video meta with a wrong stride", nick = "wrong-stride")]
+    WrongStride = 3,
+}
+
+// Color components of the output the pattern is drawn into
+#[glib::flags(name = "GstRsTestPatternChannels")]
+pub enum Channels {
+    #[flags_value(name = "Red", nick = "r")]
+    RED = 0b001,
+    #[flags_value(name = "Green", nick = "g")]
+    GREEN = 0b010,
+    #[flags_value(name = "Blue", nick = "b")]
+    BLUE = 0b100,
+}
 
 glib::wrapper! {
-    pub struct TestPatternSrc(ObjectSubclass<imp::TestPatternSrc>) @extends gst_base::PushSrc, gst_base::BaseSrc, gst::Element, gst::Object;
+    pub struct TestPatternSrc(ObjectSubclass<imp::TestPatternSrc>) @extends gst_base::PushSrc, gst_base::BaseSrc, gst::Element, gst::Object,
+        @implements gst_video::ColorBalance, gst_video::VideoOrientation;
 }
 
 // Registers the type for our element, and then registers in GStreamer under
 // the name "rstestpattern" for being able to instantiate it via e.g.
 // gst::ElementFactory::make().
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
+    colorbalance::add_interface(TestPatternSrc::static_type());
+    orientation::add_interface(TestPatternSrc::static_type());
+
     gst::Element::register(
         Some(plugin),
         "rstestpattern",
@@ -0,0 +1,101 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// GstVideoOrientation implementation of the test pattern source, added
+// through the C API like the color balance. Flipping maps onto the
+// mirror-x and mirror-y properties, so setting through the interface
+// emits the same notifications as setting the properties. Centering is not
+// supported and left to the interface defaults.
+
+use gst::glib;
+use gst::glib::translate::*;
+use gst::prelude::*;
+
+use glib::ffi as glib_ffi;
+use glib::gobject_ffi;
+use gst_video::ffi as video_ffi;
+
+use std::ptr;
+use std::sync::Once;
+
+// Adds the interface to the element type, has to happen before the first
+// instance is created
+pub fn add_interface(type_: glib::Type) {
+    static ADD: Once = Once::new();
+
+    ADD.call_once(|| unsafe {
+        let info = gobject_ffi::GInterfaceInfo {
+            interface_init: Some(interface_init),
+            interface_finalize: None,
+            interface_data: ptr::null_mut(),
+        };
+        gobject_ffi::g_type_add_interface_static(
+            type_.into_glib(),
+            video_ffi::gst_video_orientation_get_type(),
+            &info,
+        );
+    });
+}
+
+unsafe extern "C" fn interface_init(iface: glib_ffi::gpointer, _data: glib_ffi::gpointer) {
+    let iface = &mut *(iface as *mut video_ffi::GstVideoOrientationInterface);
+    iface.get_hflip = Some(get_hflip);
+    iface.get_vflip = Some(get_vflip);
+    iface.set_hflip = Some(set_hflip);
+    iface.set_vflip = Some(set_vflip);
+}
+
+unsafe fn get_flip(
+    orientation: *mut video_ffi::GstVideoOrientation,
+    property: &str,
+    flip: *mut glib_ffi::gboolean,
+) -> glib_ffi::gboolean {
+    let obj: Borrowed<glib::Object> = from_glib_borrow(orientation as *mut gobject_ffi::GObject);
+    *flip = obj.property::<bool>(property).into_glib();
+    glib_ffi::GTRUE
+}
+
+unsafe fn set_flip(
+    orientation: *mut video_ffi::GstVideoOrientation,
+    property: &str,
+    flip: glib_ffi::gboolean,
+) -> glib_ffi::gboolean {
+    let obj: Borrowed<glib::Object> = from_glib_borrow(orientation as *mut gobject_ffi::GObject);
+    obj.set_property(property, flip != glib_ffi::GFALSE);
+    glib_ffi::GTRUE
+}
+
+unsafe extern "C" fn get_hflip(
+    orientation: *mut video_ffi::GstVideoOrientation,
+    flip: *mut glib_ffi::gboolean,
+) -> glib_ffi::gboolean {
+    get_flip(orientation, "mirror-x", flip)
+}
+
+unsafe extern "C" fn get_vflip(
+    orientation: *mut video_ffi::GstVideoOrientation,
+    flip: *mut glib_ffi::gboolean,
+) -> glib_ffi::gboolean {
+    get_flip(orientation, "mirror-y", flip)
+}
+
+unsafe extern "C" fn set_hflip(
+    orientation: *mut video_ffi::GstVideoOrientation,
+    flip: glib_ffi::gboolean,
+) -> glib_ffi::gboolean {
+    set_flip(orientation, "mirror-x", flip)
+}
+
+unsafe extern "C" fn set_vflip(
+    orientation: *mut video_ffi::GstVideoOrientation,
+    flip: glib_ffi::gboolean,
+) -> glib_ffi::gboolean {
+    set_flip(orientation, "mirror-y", flip)
+}
@@ -0,0 +1,463 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gst_base::subclass::prelude::*;
+use gst_video::subclass::prelude::*;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::pattern;
+use crate::video;
+
+// This module contains the private implementation details of our element
+//
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "rstestpatternanalyze",
+        gst::DebugColorFlags::empty(),
+        Some("Test Pattern Analyzer"),
+    )
+});
+
+// Default values of properties
+const DEFAULT_FOREGROUND_COLOR: u32 = 0xffffffff;
+const DEFAULT_BACKGROUND_COLOR: u32 = 0xff000000;
+const DEFAULT_SPEED: i32 = 5;
+const DEFAULT_SIZE: u32 = 50;
+const DEFAULT_TOLERANCE: u32 = 8;
+const DEFAULT_SUMMARY_INTERVAL: u32 = 300;
+
+// Property value storage, describing the stream produced by rstestpattern.
+// Only the plain bar pattern is modeled, anything else on top of it shows up
+// as mismatches.
+#[derive(Debug, Clone)]
+struct Settings {
+    foreground_color: u32,
+    background_color: u32,
+    size: u32,
+    speed: i32,
+    tolerance: u32,
+    summary_interval: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            foreground_color: DEFAULT_FOREGROUND_COLOR,
+            background_color: DEFAULT_BACKGROUND_COLOR,
+            size: DEFAULT_SIZE,
+            speed: DEFAULT_SPEED,
+            tolerance: DEFAULT_TOLERANCE,
+            summary_interval: DEFAULT_SUMMARY_INTERVAL,
+        }
+    }
+}
+
+impl Settings {
+    // Parameters rstestpattern rendered frame number `frame_index` with
+    fn params(&self, frame_index: u64, height: u32) -> pattern::PatternParams {
+        let offset = if height > 0 {
+            (frame_index as i128 * self.speed as i128).rem_euclid(height as i128) as u32
+        } else {
+            0
+        };
+
+        pattern::PatternParams {
+            foreground_color: self.foreground_color,
+            background_color: Some(self.background_color),
+            offset,
+            size: self.size,
+            ..Default::default()
+        }
+    }
+}
+
+// Analysis results and scratch memory
+#[derive(Debug, Default)]
+struct State {
+    renderer: pattern::Renderer,
+    expected: Vec<Vec<u8>>,
+
+    frames: u64,
+    mismatches: u64,
+    dropped: u64,
+    duplicated: u64,
+    max_difference: u8,
+    last_index: Option<u64>,
+}
+
+impl State {
+    fn summary(&self, eos: bool) -> gst::Structure {
+        gst::Structure::builder("rstestpatternanalyze-summary")
+            .field("frames", self.frames)
+            .field("mismatches", self.mismatches)
+            .field("dropped", self.dropped)
+            .field("duplicated", self.duplicated)
+            .field("max-difference", self.max_difference as u32)
+            .field("eos", eos)
+            .build()
+    }
+}
+
+// Struct containing all the element data
+#[derive(Default)]
+pub struct TestPatternAnalyze {
+    settings: Mutex<Settings>,
+    state: Mutex<State>,
+}
+
+impl TestPatternAnalyze {
+    // Index of the frame within the pattern stream: rstestpattern puts it
+    // into the buffer offset, elements not preserving that leave the PTS
+    fn frame_index(buffer: &gst::BufferRef, info: &gst_video::VideoInfo) -> Option<u64> {
+        if buffer.offset() != gst::BUFFER_OFFSET_NONE {
+            return Some(buffer.offset());
+        }
+
+        let pts = buffer.pts()?;
+        let fps = info.fps();
+        if fps.numer() <= 0 || fps.denom() <= 0 {
+            return None;
+        }
+
+        Some(unsafe {
+            ffi::gst_util_uint64_scale_round(
+                pts.nseconds(),
+                fps.numer() as u64,
+                fps.denom() as u64 * gst::ClockTime::SECOND.nseconds(),
+            )
+        })
+    }
+
+    fn post_summary(&self, element: &super::TestPatternAnalyze, summary: gst::Structure) {
+        gst::info!(CAT, obj: element, "{}", summary);
+        let _ = element.post_message(gst::message::Element::builder(summary).src(element).build());
+    }
+}
+
+// This trait registers our type with the GObject object system and
+// provides the entry points for creating a new instance and setting
+// up the class data
+#[glib::object_subclass]
+impl ObjectSubclass for TestPatternAnalyze {
+    const NAME: &'static str = "TestPatternAnalyze";
+    type Type = super::TestPatternAnalyze;
+    type ParentType = gst_video::VideoFilter;
+}
+
+// Implementation of glib::Object virtual methods
+impl ObjectImpl for TestPatternAnalyze {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecUInt::new(
+                    "foreground-color",
+                    "Foreground Color",
+                    "Foreground color the pattern was produced with (big-endian ARGB)",
+                    0,
+                    u32::MAX,
+                    DEFAULT_FOREGROUND_COLOR,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpecUInt::new(
+                    "background-color",
+                    "Background Color",
+                    "Background color the pattern was produced with (big-endian ARGB)",
+                    0,
+                    u32::MAX,
+                    DEFAULT_BACKGROUND_COLOR,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpecInt::new(
+                    "speed",
+                    "Speed",
+                    "Scroll speed the pattern was produced with in pixels per frame, negative moving up",
+                    i32::MIN,
+                    i32::MAX,
+                    DEFAULT_SPEED,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpecUInt::new(
+                    "size",
+                    "size",
+                    "Bar size the pattern was produced with",
+                    u32::MIN,
+                    u32::MAX,
+                    DEFAULT_SIZE,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpecUInt::new(
+                    "tolerance",
+                    "Tolerance",
+                    "Largest difference of a byte to the expected frame still considered a match",
+                    0,
+                    255,
+                    DEFAULT_TOLERANCE,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpecUInt::new(
+                    "summary-interval",
+                    "Summary Interval",
+                    "Post a summary message every this many frames (0 = only at EOS)",
+                    0,
+                    u32::MAX,
+                    DEFAULT_SUMMARY_INTERVAL,
+                    glib::ParamFlags::READWRITE,
+                ),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(
+        &self,
+        _obj: &Self::Type,
+        _id: usize,
+        value: &glib::Value,
+        pspec: &glib::ParamSpec,
+    ) {
+        let mut settings = self.settings.lock().unwrap();
+
+        match pspec.name() {
+            "foreground-color" => {
+                settings.foreground_color = value.get().expect("type checked upstream");
+            }
+            "background-color" => {
+                settings.background_color = value.get().expect("type checked upstream");
+            }
+            "speed" => {
+                settings.speed = value.get().expect("type checked upstream");
+            }
+            "size" => {
+                settings.size = value.get().expect("type checked upstream");
+            }
+            "tolerance" => {
+                settings.tolerance = value.get().expect("type checked upstream");
+            }
+            "summary-interval" => {
+                settings.summary_interval = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _obj: &Self::Type, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "foreground-color" => settings.foreground_color.to_value(),
+            "background-color" => settings.background_color.to_value(),
+            "speed" => settings.speed.to_value(),
+            "size" => settings.size.to_value(),
+            "tolerance" => settings.tolerance.to_value(),
+            "summary-interval" => settings.summary_interval.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl GstObjectImpl for TestPatternAnalyze {}
+
+impl ElementImpl for TestPatternAnalyze {
+    // Set the element specific metadata. This information is what
+    // is visible from gst-inspect-1.0 and can also be programatically
+    // retrieved from the gst::Registry after initial registration
+    // without having to load the plugin in memory.
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Test pattern analyzer",
+                "Filter/Analyzer/Video",
+                "Checks a progressive video stream of the rstestpattern bar pattern against the expected frames. Other patterns, margins, soft edges, effects and non-default motion are not modeled.",
+                "Alexander Stein <alexander.stein@mailbox.org>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: Lazy<Vec<gst::PadTemplate>> = Lazy::new(|| {
+            let caps = gst::Caps::builder("video/x-raw")
+                .field(
+                    "format",
+                    gst::List::new(&[
+                        &gst_video::VideoFormat::Bgrx.to_str(),
+                        &gst_video::VideoFormat::Nv12.to_str(),
+                    ]),
+                )
+                .field("width", gst::IntRange::new(0, i32::MAX))
+                .field("height", gst::IntRange::new(0, i32::MAX))
+                .field(
+                    "framerate",
+                    gst::FractionRange::new(
+                        gst::Fraction::new(0, 1),
+                        gst::Fraction::new(i32::MAX, 1),
+                    ),
+                )
+                .build();
+
+            let src_pad_template = gst::PadTemplate::new(
+                "src",
+                gst::PadDirection::Src,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            let sink_pad_template = gst::PadTemplate::new(
+                "sink",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![src_pad_template, sink_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+}
+
+impl BaseTransformImpl for TestPatternAnalyze {
+    const MODE: gst_base::subclass::BaseTransformMode =
+        gst_base::subclass::BaseTransformMode::AlwaysInPlace;
+    const PASSTHROUGH_ON_SAME_CAPS: bool = true;
+    const TRANSFORM_IP_ON_PASSTHROUGH: bool = true;
+
+    fn start(&self, _element: &Self::Type) -> Result<(), gst::ErrorMessage> {
+        *self.state.lock().unwrap() = State::default();
+        Ok(())
+    }
+
+    fn sink_event(&self, element: &Self::Type, event: gst::Event) -> bool {
+        if let gst::EventView::Eos(_) = event.view() {
+            let summary = self.state.lock().unwrap().summary(true);
+            self.post_summary(element, summary);
+        }
+
+        self.parent_sink_event(element, event)
+    }
+}
+
+impl VideoFilterImpl for TestPatternAnalyze {
+    fn set_info(
+        &self,
+        _element: &Self::Type,
+        _incaps: &gst::Caps,
+        in_info: &gst_video::VideoInfo,
+        _outcaps: &gst::Caps,
+        _out_info: &gst_video::VideoInfo,
+    ) -> Result<(), gst::LoggableError> {
+        // rstestpattern renders the fields of interlaced frames separately
+        if in_info.interlace_mode() != gst_video::VideoInterlaceMode::Progressive {
+            return Err(gst::loggable_error!(
+                CAT,
+                "Interlaced streams are not supported"
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn transform_frame_ip_passthrough(
+        &self,
+        element: &Self::Type,
+        frame: &gst_video::VideoFrameRef<&gst::BufferRef>,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let settings = self.settings.lock().unwrap().clone();
+        let mut state = self.state.lock().unwrap();
+
+        let layout = video::frame_layout(frame).ok_or(gst::FlowError::NotNegotiated)?;
+        let frame_index = match Self::frame_index(frame.buffer(), frame.info()) {
+            Some(frame_index) => frame_index,
+            None => {
+                gst::warning!(CAT, obj: element, "Can't determine frame index, skipping");
+                return Ok(gst::FlowSuccess::Ok);
+            }
+        };
+
+        match state.last_index {
+            Some(last) if frame_index == last => state.duplicated += 1,
+            Some(last) if frame_index > last + 1 => {
+                gst::debug!(
+                    CAT,
+                    obj: element,
+                    "{} frames dropped before frame {}",
+                    frame_index - last - 1,
+                    frame_index
+                );
+                state.dropped += frame_index - last - 1;
+            }
+            _ => (),
+        }
+        state.last_index = Some(frame_index);
+        state.frames += 1;
+
+        // Recreate the frame rstestpattern produced with the same strides
+        let params = settings.params(frame_index, layout.height);
+        let State {
+            ref mut renderer,
+            ref mut expected,
+            ..
+        } = *state;
+        expected.resize(layout.planes.len(), Vec::new());
+        for (idx, plane) in expected.iter_mut().enumerate() {
+            plane.resize(layout.planes[idx].stride * layout.plane_height(idx), 0);
+        }
+        let mut expected_planes = expected
+            .iter_mut()
+            .map(|plane| plane.as_mut_slice())
+            .collect::<Vec<_>>();
+        renderer.render_planes(frame_index, &params, &layout, &mut expected_planes);
+
+        let actual_planes = (0..layout.planes.len())
+            .map(|plane| frame.plane_data(plane as u32))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| gst::FlowError::Error)?;
+        let expected_planes = expected_planes
+            .iter()
+            .map(|plane| &**plane)
+            .collect::<Vec<_>>();
+
+        let difference = pattern::max_difference(&layout, &actual_planes, &expected_planes);
+        state.max_difference = state.max_difference.max(difference);
+        if difference as u32 > settings.tolerance {
+            gst::debug!(
+                CAT,
+                obj: element,
+                "Frame {} differs by up to {} from the expected pattern",
+                frame_index,
+                difference
+            );
+            state.mismatches += 1;
+        }
+
+        let summary = if settings.summary_interval > 0
+            && state.frames % settings.summary_interval as u64 == 0
+        {
+            Some(state.summary(false))
+        } else {
+            None
+        };
+        drop(state);
+
+        if let Some(summary) = summary {
+            self.post_summary(element, summary);
+        }
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+}
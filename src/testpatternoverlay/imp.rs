@@ -0,0 +1,413 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use gst::glib;
+use gst::subclass::prelude::*;
+use gst_base::prelude::*;
+use gst_base::subclass::prelude::*;
+use gst_video::subclass::prelude::*;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::pattern;
+use crate::video;
+
+// This module contains the private implementation details of our element
+//
+static CAT: Lazy<gst::DebugCategory> = Lazy::new(|| {
+    gst::DebugCategory::new(
+        "rstestpatternoverlay",
+        gst::DebugColorFlags::empty(),
+        Some("Test Pattern Overlay"),
+    )
+});
+
+// Default values of properties
+const DEFAULT_ENABLED: bool = true;
+const DEFAULT_FOREGROUND_COLOR: u32 = 0xffffffff;
+const DEFAULT_SPEED: u32 = 5;
+const DEFAULT_SIZE: u32 = 50;
+
+const OVERLAY_COMPOSITION_FEATURE: &str = "meta:GstVideoOverlayComposition";
+
+// Property value storage
+#[derive(Debug, Clone)]
+struct Settings {
+    enabled: bool,
+    foreground_color: u32,
+    size: u32,
+    offset: u32,
+    speed: u32,
+    // Downstream composites the pattern, attach it as meta instead of
+    // blending
+    attach_composition: bool,
+
+    n_frames: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            enabled: DEFAULT_ENABLED,
+            foreground_color: DEFAULT_FOREGROUND_COLOR,
+            size: DEFAULT_SIZE,
+            offset: 0,
+            speed: DEFAULT_SPEED,
+            attach_composition: false,
+
+            n_frames: 0,
+        }
+    }
+}
+
+impl Settings {
+    fn params(&self) -> pattern::PatternParams {
+        pattern::PatternParams {
+            foreground_color: self.foreground_color,
+            background_color: None,
+            offset: self.offset,
+            size: self.size,
+            ..Default::default()
+        }
+    }
+}
+
+// Struct containing all the element data
+#[derive(Default)]
+pub struct TestPatternOverlay {
+    settings: Mutex<Settings>,
+    renderer: Mutex<pattern::Renderer>,
+}
+
+impl TestPatternOverlay {
+    // Renders the lines covered by the bar into a composition with a single
+    // rectangle, None if the bar is outside of the frame
+    fn composition(
+        &self,
+        settings: &Settings,
+        width: u32,
+        height: u32,
+    ) -> Result<Option<gst_video::VideoOverlayComposition>, gst::FlowError> {
+        let y = settings.offset.min(height);
+        let lines = settings.size.min(height - y);
+        if width == 0 || lines == 0 {
+            return Ok(None);
+        }
+
+        // Transparent background written as is, so only the bar is visible
+        let params = pattern::PatternParams {
+            background_color: Some(0),
+            offset: 0,
+            size: lines,
+            ..settings.params()
+        };
+        let layout = pattern::FrameLayout::new(pattern::Format::Bgra, width, lines);
+        let mut data = vec![0; layout.size()];
+        self.renderer
+            .lock()
+            .unwrap()
+            .render(settings.n_frames, &params, &layout, &mut data);
+
+        let mut buffer = gst::Buffer::from_mut_slice(data);
+        gst_video::VideoMeta::add(
+            buffer.get_mut().unwrap(),
+            gst_video::VideoFrameFlags::empty(),
+            gst_video::VideoFormat::Bgra,
+            width,
+            lines,
+        )
+        .map_err(|_| gst::FlowError::Error)?;
+
+        let rectangle = gst_video::VideoOverlayRectangle::new_raw(
+            &buffer,
+            0,
+            y as i32,
+            width,
+            lines,
+            gst_video::VideoOverlayFormatFlags::empty(),
+        );
+        gst_video::VideoOverlayComposition::new(Some(&rectangle))
+            .map(Some)
+            .map_err(|_| gst::FlowError::Error)
+    }
+}
+
+// This trait registers our type with the GObject object system and
+// provides the entry points for creating a new instance and setting
+// up the class data
+#[glib::object_subclass]
+impl ObjectSubclass for TestPatternOverlay {
+    const NAME: &'static str = "TestPatternOverlay";
+    type Type = super::TestPatternOverlay;
+    type ParentType = gst_video::VideoFilter;
+}
+
+// Implementation of glib::Object virtual methods
+impl ObjectImpl for TestPatternOverlay {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecBoolean::new(
+                    "enabled",
+                    "Enabled",
+                    "Draw the pattern, or pass buffers through untouched",
+                    DEFAULT_ENABLED,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpecUInt::new(
+                    "foreground-color",
+                    "Foreground Color",
+                    "Foreground color to use (big-endian ARGB)",
+                    0,
+                    u32::MAX,
+                    DEFAULT_FOREGROUND_COLOR,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpecUInt::new(
+                    "speed",
+                    "Speed",
+                    "Scroll image number of pixels per frame",
+                    u32::MIN,
+                    u32::MAX,
+                    DEFAULT_SPEED,
+                    glib::ParamFlags::READWRITE,
+                ),
+                glib::ParamSpecUInt::new(
+                    "size",
+                    "size",
+                    "Vertical width of horizontal bar",
+                    u32::MIN,
+                    u32::MAX,
+                    DEFAULT_SIZE,
+                    glib::ParamFlags::READWRITE,
+                ),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(
+        &self,
+        obj: &Self::Type,
+        _id: usize,
+        value: &glib::Value,
+        pspec: &glib::ParamSpec,
+    ) {
+        let mut settings = self.settings.lock().unwrap();
+
+        match pspec.name() {
+            "enabled" => {
+                settings.enabled = value.get().expect("type checked upstream");
+                let passthrough = !settings.enabled;
+                drop(settings);
+                obj.set_passthrough(passthrough);
+            }
+            "foreground-color" => {
+                settings.foreground_color = value.get().expect("type checked upstream");
+            }
+            "speed" => {
+                settings.speed = value.get().expect("type checked upstream");
+            }
+            "size" => {
+                settings.size = value.get().expect("type checked upstream");
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _obj: &Self::Type, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        let settings = self.settings.lock().unwrap();
+        match pspec.name() {
+            "enabled" => settings.enabled.to_value(),
+            "foreground-color" => settings.foreground_color.to_value(),
+            "speed" => settings.speed.to_value(),
+            "size" => settings.size.to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl GstObjectImpl for TestPatternOverlay {}
+
+impl ElementImpl for TestPatternOverlay {
+    // Set the element specific metadata. This information is what
+    // is visible from gst-inspect-1.0 and can also be programatically
+    // retrieved from the gst::Registry after initial registration
+    // without having to load the plugin in memory.
+    fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+        static ELEMENT_METADATA: Lazy<gst::subclass::ElementMetadata> = Lazy::new(|| {
+            gst::subclass::ElementMetadata::new(
+                "Test pattern overlay",
+                "Filter/Effect/Video",
+                "Draws the test pattern over a video stream",
+                "Alexander Stein <alexander.stein@mailbox.org>",
+            )
+        });
+
+        Some(&*ELEMENT_METADATA)
+    }
+
+    fn pad_templates() -> &'static [gst::PadTemplate] {
+        static PAD_TEMPLATES: Lazy<Vec<gst::PadTemplate>> = Lazy::new(|| {
+            let caps = gst::Caps::builder("video/x-raw")
+                .field(
+                    "format",
+                    gst::List::new(&[
+                        &gst_video::VideoFormat::Bgrx.to_str(),
+                        &gst_video::VideoFormat::Nv12.to_str(),
+                    ]),
+                )
+                .field("width", gst::IntRange::new(0, i32::MAX))
+                .field("height", gst::IntRange::new(0, i32::MAX))
+                .field(
+                    "framerate",
+                    gst::FractionRange::new(
+                        gst::Fraction::new(0, 1),
+                        gst::Fraction::new(i32::MAX, 1),
+                    ),
+                )
+                .build();
+
+            // Downstream can composite the pattern itself
+            let mut src_caps = caps.copy();
+            {
+                let src_caps = src_caps.get_mut().unwrap();
+                src_caps.set_features_simple(Some(gst::CapsFeatures::new(&[
+                    OVERLAY_COMPOSITION_FEATURE,
+                ])));
+                src_caps.append(caps.copy());
+            }
+
+            let src_pad_template = gst::PadTemplate::new(
+                "src",
+                gst::PadDirection::Src,
+                gst::PadPresence::Always,
+                &src_caps,
+            )
+            .unwrap();
+
+            let sink_pad_template = gst::PadTemplate::new(
+                "sink",
+                gst::PadDirection::Sink,
+                gst::PadPresence::Always,
+                &caps,
+            )
+            .unwrap();
+
+            vec![src_pad_template, sink_pad_template]
+        });
+
+        PAD_TEMPLATES.as_ref()
+    }
+}
+
+impl BaseTransformImpl for TestPatternOverlay {
+    const MODE: gst_base::subclass::BaseTransformMode =
+        gst_base::subclass::BaseTransformMode::AlwaysInPlace;
+    const PASSTHROUGH_ON_SAME_CAPS: bool = false;
+    const TRANSFORM_IP_ON_PASSTHROUGH: bool = false;
+
+    fn start(&self, element: &Self::Type) -> Result<(), gst::ErrorMessage> {
+        let mut settings = self.settings.lock().unwrap();
+        settings.offset = 0;
+        settings.n_frames = 0;
+        let passthrough = !settings.enabled;
+        drop(settings);
+
+        element.set_passthrough(passthrough);
+        Ok(())
+    }
+
+    // Offers the overlay composition feature downstream in addition to the
+    // input caps, preferring it. Upstream always gets plain caps.
+    fn transform_caps(
+        &self,
+        _element: &Self::Type,
+        direction: gst::PadDirection,
+        caps: &gst::Caps,
+        filter: Option<&gst::Caps>,
+    ) -> Option<gst::Caps> {
+        let mut transformed = gst::Caps::new_empty();
+        {
+            let transformed = transformed.get_mut().unwrap();
+            for (structure, features) in caps.iter_with_features() {
+                let mut plain = features.to_owned();
+                plain.remove(OVERLAY_COMPOSITION_FEATURE);
+
+                if direction == gst::PadDirection::Sink && !plain.is_any() {
+                    let mut overlay = plain.clone();
+                    overlay.add(OVERLAY_COMPOSITION_FEATURE);
+                    transformed.append_structure_full(structure.to_owned(), Some(overlay));
+                }
+                transformed.append_structure_full(structure.to_owned(), Some(plain));
+            }
+        }
+
+        match filter {
+            Some(filter) => {
+                Some(filter.intersect_with_mode(&transformed, gst::CapsIntersectMode::First))
+            }
+            None => Some(transformed),
+        }
+    }
+}
+
+impl VideoFilterImpl for TestPatternOverlay {
+    fn set_info(
+        &self,
+        element: &Self::Type,
+        _incaps: &gst::Caps,
+        _in_info: &gst_video::VideoInfo,
+        outcaps: &gst::Caps,
+        _out_info: &gst_video::VideoInfo,
+    ) -> Result<(), gst::LoggableError> {
+        let attach_composition = outcaps.features(0).map_or(false, |features| {
+            features.contains(OVERLAY_COMPOSITION_FEATURE)
+        });
+        gst::debug!(
+            CAT,
+            obj: element,
+            "Attaching overlay composition: {}",
+            attach_composition
+        );
+
+        self.settings.lock().unwrap().attach_composition = attach_composition;
+        Ok(())
+    }
+
+    fn transform_frame_ip(
+        &self,
+        element: &Self::Type,
+        frame: &mut gst_video::VideoFrameRef<&mut gst::BufferRef>,
+    ) -> Result<gst::FlowSuccess, gst::FlowError> {
+        let mut settings = self.settings.lock().unwrap();
+
+        if settings.attach_composition {
+            if let Some(composition) = self.composition(&settings, frame.width(), frame.height())? {
+                gst_video::VideoOverlayCompositionMeta::add(frame.buffer_mut(), &composition);
+            }
+        } else {
+            let mut renderer = self.renderer.lock().unwrap();
+            video::render_frame(&mut renderer, settings.n_frames, &settings.params(), frame)
+                .map_err(|err| {
+                    gst::error!(CAT, obj: element, "failed to render {:?} frame", frame.format());
+                    err
+                })?;
+        }
+
+        settings.n_frames += 1;
+        settings.offset += settings.speed;
+        settings.offset %= frame.height();
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Conversions between GStreamer video frames and the pattern renderer
+
+use crate::pattern;
+
+pub fn pattern_format(format: gst_video::VideoFormat) -> Option<pattern::Format> {
+    match format {
+        gst_video::VideoFormat::Bgrx => Some(pattern::Format::Bgrx),
+        gst_video::VideoFormat::Bgra => Some(pattern::Format::Bgra),
+        gst_video::VideoFormat::Nv12 => Some(pattern::Format::Nv12),
+        _ => None,
+    }
+}
+
+// Describes the memory of a mapped frame, taking the strides from the
+// frame itself as they might differ from the ones in the caps
+pub fn frame_layout<T>(frame: &gst_video::VideoFrameRef<T>) -> Option<pattern::FrameLayout> {
+    let format = pattern_format(frame.format())?;
+    let info = frame.info();
+
+    Some(pattern::FrameLayout {
+        format,
+        width: frame.width(),
+        height: frame.height(),
+        planes: (0..format.n_planes())
+            .map(|plane| pattern::PlaneLayout {
+                offset: info.offset()[plane],
+                stride: frame.plane_stride()[plane] as usize,
+            })
+            .collect(),
+    })
+}
+
+// Renders one frame of the pattern into a mapped video frame
+pub fn render_frame(
+    renderer: &mut pattern::Renderer,
+    frame_index: u64,
+    params: &pattern::PatternParams,
+    frame: &mut gst_video::VideoFrameRef<&mut gst::BufferRef>,
+) -> Result<(), gst::FlowError> {
+    let layout = frame_layout(frame).ok_or(gst::FlowError::NotNegotiated)?;
+
+    let mut planes = Vec::with_capacity(layout.planes.len());
+    for plane in 0..layout.planes.len() {
+        let data = frame
+            .plane_data_mut(plane as u32)
+            .map_err(|_| gst::FlowError::Error)?;
+        // SAFETY: the planes of a mapped frame don't overlap and stay mapped
+        // for as long as the frame is borrowed
+        planes.push(unsafe { std::slice::from_raw_parts_mut(data.as_mut_ptr(), data.len()) });
+    }
+
+    renderer.render_planes(frame_index, params, &layout, &mut planes);
+    Ok(())
+}
+
+// Digest of the visible content of a mapped video frame
+pub fn frame_digest<T>(frame: &gst_video::VideoFrameRef<T>) -> Option<u64> {
+    let layout = frame_layout(frame)?;
+    let planes = (0..layout.planes.len())
+        .map(|plane| frame.plane_data(plane as u32).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(pattern::frame_digest(&layout, &planes))
+}
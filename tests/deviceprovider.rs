@@ -0,0 +1,96 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use gst::prelude::*;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstrstestpattern::plugin_register_static().unwrap();
+    });
+}
+
+fn device(display_name: &str) -> gst::Device {
+    let factory = gst::DeviceProviderFactory::find("rstestpatterndeviceprovider").unwrap();
+    let provider = factory.get().unwrap();
+
+    provider
+        .devices()
+        .into_iter()
+        .find(|device| device.display_name() == display_name)
+        .expect("device not listed")
+}
+
+#[test]
+fn device_metadata() {
+    init();
+
+    let device = device("Test pattern: SMPTE bars 1080p30");
+    assert_eq!(device.device_class(), "Video/Source");
+
+    let caps = device.caps().unwrap();
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.get::<i32>("width").unwrap(), 1920);
+    assert_eq!(s.get::<i32>("height").unwrap(), 1080);
+    assert_eq!(
+        s.get::<gst::Fraction>("framerate").unwrap(),
+        gst::Fraction::new(30, 1)
+    );
+}
+
+#[test]
+fn created_element_uses_preset() {
+    init();
+
+    let device = device("Test pattern: SMPTE bars 1080p30");
+    let element = device.create_element(Some("camera")).unwrap();
+    assert_eq!(element.name(), "camera");
+
+    let bin = element.downcast_ref::<gst::Bin>().unwrap();
+    let src = bin
+        .iterate_elements()
+        .into_iter()
+        .map(Result::unwrap)
+        .find(|element| element.factory().unwrap().name() == "rstestpattern")
+        .unwrap();
+    let pattern = src.property_value("pattern").serialize().unwrap();
+    assert_eq!(pattern, "smpte");
+
+    // Negotiates the advertised caps even though fakesink accepts anything
+    let pipeline = gst::Pipeline::new(None);
+    let sink = gst::ElementFactory::make("fakesink", None).unwrap();
+    pipeline.add_many(&[&element, &sink]).unwrap();
+    element.link(&sink).unwrap();
+
+    pipeline.set_state(gst::State::Paused).unwrap();
+    let msg = pipeline
+        .bus()
+        .unwrap()
+        .timed_pop_filtered(
+            gst::ClockTime::NONE,
+            &[gst::MessageType::AsyncDone, gst::MessageType::Error],
+        )
+        .unwrap();
+    assert_eq!(msg.type_(), gst::MessageType::AsyncDone);
+
+    let caps = sink.static_pad("sink").unwrap().current_caps().unwrap();
+    let s = caps.structure(0).unwrap();
+    assert_eq!(s.get::<i32>("width").unwrap(), 1920);
+    assert_eq!(s.get::<i32>("height").unwrap(), 1080);
+    assert_eq!(
+        s.get::<gst::Fraction>("framerate").unwrap(),
+        gst::Fraction::new(30, 1)
+    );
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
@@ -0,0 +1,52 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Only test of this binary, so GStreamer is initialized in a fresh process
+// that never saw the plugin on the plugin path.
+
+use gst::prelude::*;
+use std::env;
+
+#[test]
+fn static_registration() {
+    let registry =
+        env::temp_dir().join(format!("rstestpattern-registry-{}.bin", std::process::id()));
+    env::set_var("GST_REGISTRY_1_0", &registry);
+    env::set_var("GST_PLUGIN_PATH_1_0", "");
+    env::set_var("GST_PLUGIN_SYSTEM_PATH_1_0", "");
+    gst::init().unwrap();
+
+    assert!(gst::ElementFactory::find("rstestpattern").is_none());
+    gstrstestpattern::plugin_register_static().unwrap();
+
+    let factory = gst::ElementFactory::find("rstestpattern").expect("not registered");
+    assert_eq!(factory.plugin_name().as_deref(), Some("rstestpattern"));
+
+    let pipeline = gst::Pipeline::new(None);
+    let src = gst::ElementFactory::make("rstestpattern", None).unwrap();
+    let sink = gst::ElementFactory::make("fakesink", None).unwrap();
+    src.set_property("num-buffers", 2i32);
+    pipeline.add_many(&[&src, &sink]).unwrap();
+    src.link(&sink).unwrap();
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+    let msg = pipeline
+        .bus()
+        .unwrap()
+        .timed_pop_filtered(
+            gst::ClockTime::NONE,
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        )
+        .unwrap();
+    assert_eq!(msg.type_(), gst::MessageType::Eos);
+    pipeline.set_state(gst::State::Null).unwrap();
+
+    let _ = std::fs::remove_file(registry);
+}
@@ -0,0 +1,1309 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use gst::glib;
+use gst::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstrstestpattern::plugin_register_static().unwrap();
+    });
+}
+
+// Waits for one of `types` on the bus of `pipeline`, failing on errors
+fn wait_for(pipeline: &gst::Element, types: &[gst::MessageType]) -> gst::Message {
+    let mut filter = types.to_vec();
+    filter.push(gst::MessageType::Error);
+
+    let msg = pipeline
+        .bus()
+        .unwrap()
+        .timed_pop_filtered(gst::ClockTime::from_seconds(10), &filter)
+        .expect("timed out");
+    if let gst::MessageView::Error(err) = msg.view() {
+        panic!("{} ({:?})", err.error(), err.debug());
+    }
+    msg
+}
+
+// Plays `pipeline` until EOS, returning how long that took
+fn run(pipeline: &gst::Element) -> Duration {
+    let start = Instant::now();
+    pipeline.set_state(gst::State::Playing).unwrap();
+    wait_for(pipeline, &[gst::MessageType::Eos]);
+    let elapsed = start.elapsed();
+    pipeline.set_state(gst::State::Null).unwrap();
+    elapsed
+}
+
+// Collects the buffers reaching the fakesink named "sink" of `pipeline`
+fn handoffs(pipeline: &gst::Element) -> Arc<Mutex<Vec<gst::Buffer>>> {
+    let sink = element(pipeline, "sink");
+    let buffers = Arc::new(Mutex::new(Vec::new()));
+
+    sink.set_property("signal-handoffs", true);
+    let handoff_buffers = buffers.clone();
+    sink.connect("handoff", false, move |args| {
+        let buffer = args[1].get::<gst::Buffer>().unwrap();
+        handoff_buffers.lock().unwrap().push(buffer);
+        None
+    });
+
+    buffers
+}
+
+// Plays `pipeline` until EOS and returns the buffers that reached its
+// fakesink named "sink"
+fn buffers(pipeline: &gst::Element) -> Vec<gst::Buffer> {
+    let buffers = handoffs(pipeline);
+    run(pipeline);
+    let buffers = std::mem::take(&mut *buffers.lock().unwrap());
+    buffers
+}
+
+fn element(pipeline: &gst::Element, name: &str) -> gst::Element {
+    pipeline
+        .downcast_ref::<gst::Bin>()
+        .unwrap()
+        .by_name(name)
+        .unwrap()
+}
+
+#[test]
+fn non_live_prerolls_and_syncs() {
+    init();
+
+    for &sync in &[true, false] {
+        let pipeline = gst::parse_launch(&format!(
+            "rstestpattern num-buffers=5 ! video/x-raw,width=64,height=48,framerate=25/1 \
+             ! fakesink name=sink sync={}",
+            sync
+        ))
+        .unwrap();
+        let buffers = handoffs(&pipeline);
+
+        assert_eq!(
+            pipeline.set_state(gst::State::Paused),
+            Ok(gst::StateChangeSuccess::Async)
+        );
+        wait_for(&pipeline, &[gst::MessageType::AsyncDone]);
+        // The last buffer ends at 200ms of running time, and the times
+        // reported for it are its PTS and end
+        assert!(
+            run(&pipeline) >= Duration::from_millis(160),
+            "sync={}",
+            sync
+        );
+
+        let buffers = buffers.lock().unwrap();
+        assert_eq!(buffers.len(), 5);
+        for (n, buffer) in buffers.iter().enumerate() {
+            assert_eq!(
+                buffer.pts(),
+                Some(gst::ClockTime::from_mseconds(40 * n as u64))
+            );
+            assert_eq!(buffer.duration(), Some(gst::ClockTime::from_mseconds(40)));
+        }
+    }
+}
+
+#[test]
+fn live_syncs_in_source() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern is-live=true num-buffers=5 ! video/x-raw,width=64,height=48,framerate=25/1 \
+         ! fakesink name=sink sync=false",
+    )
+    .unwrap();
+    let buffers = handoffs(&pipeline);
+
+    assert_eq!(
+        pipeline.set_state(gst::State::Paused),
+        Ok(gst::StateChangeSuccess::NoPreroll)
+    );
+    assert!(run(&pipeline) >= Duration::from_millis(160));
+
+    // Timestamped with the running time of the frames
+    let buffers = buffers.lock().unwrap();
+    assert_eq!(buffers.len(), 5);
+    assert!(
+        buffers
+            .windows(2)
+            .all(|pair| pair[0].pts().unwrap() + pair[0].duration().unwrap()
+                <= pair[1].pts().unwrap())
+    );
+}
+
+#[test]
+fn change_lands_at_running_time() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src num-buffers=10 foreground-color=0xff000000 background-color=0xff000000 \
+         ! video/x-raw,format=BGRx,width=16,height=16,framerate=10/1 ! fakesink name=sink",
+    )
+    .unwrap();
+
+    let change = gst::Structure::builder("set-test-pattern")
+        .field("foreground-color", 0xffff0000u32)
+        .field("background-color", 0xffff0000u32)
+        .field("running-time", 500_000_000u64)
+        .build();
+    let src = element(&pipeline, "src");
+    assert!(src.send_event(gst::event::CustomDownstream::new(change)));
+
+    let buffers = buffers(&pipeline);
+    assert_eq!(buffers.len(), 10);
+    for buffer in buffers {
+        let pts = buffer.pts().unwrap();
+        let map = buffer.map_readable().unwrap();
+        let expected = if pts >= gst::ClockTime::from_mseconds(500) {
+            [0x00, 0x00, 0xff]
+        } else {
+            [0x00, 0x00, 0x00]
+        };
+        assert_eq!(&map[..3], &expected, "frame at {}", pts);
+    }
+}
+
+#[test]
+fn chroma_checker_exact() {
+    init();
+
+    for &(format, width, height) in &[
+        (gst_video::VideoFormat::Bgrx, 4, 4),
+        (gst_video::VideoFormat::Bgrx, 7, 3),
+        (gst_video::VideoFormat::Bgra, 5, 2),
+        (gst_video::VideoFormat::Bgra, 1, 1),
+    ] {
+        let pipeline = gst::parse_launch(&format!(
+            "rstestpattern pattern=chroma-checker num-buffers=2 \
+             ! video/x-raw,format={},width={},height={},framerate=30/1 ! fakesink name=sink",
+            format.to_str(),
+            width,
+            height
+        ))
+        .unwrap();
+
+        let info = gst_video::VideoInfo::builder(format, width, height)
+            .build()
+            .unwrap();
+        for buffer in buffers(&pipeline) {
+            let frame = gst_video::VideoFrame::from_buffer_readable(buffer, &info).unwrap();
+            let stride = frame.plane_stride()[0] as usize;
+            let data = frame.plane_data(0).unwrap();
+
+            for y in 0..height as usize {
+                for x in 0..width as usize {
+                    let red = (x ^ y) & 1 == 0;
+                    let expected = if red {
+                        [0x00, 0x00, 0xff]
+                    } else {
+                        [0xff, 0x00, 0x00]
+                    };
+                    let pixel = &data[y * stride + x * 4..][..4];
+                    assert_eq!(
+                        &pixel[..3],
+                        &expected,
+                        "{}x{} at {},{}",
+                        width,
+                        height,
+                        x,
+                        y
+                    );
+                    if format == gst_video::VideoFormat::Bgra {
+                        assert_eq!(pixel[3], 0xff);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn moving_edge_position() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern pattern=moving-edge speed=3 num-buffers=300 \
+         ! video/x-raw,format=BGRx,width=64,height=2,framerate=30/1 ! fakesink name=sink",
+    )
+    .unwrap();
+
+    let buffers = buffers(&pipeline);
+    assert_eq!(buffers.len(), 300);
+    for (n_frames, buffer) in buffers.iter().enumerate() {
+        assert_eq!(buffer.offset(), n_frames as u64);
+
+        let map = buffer.map_readable().unwrap();
+        let edge = map[..64 * 4]
+            .chunks_exact(4)
+            .take_while(|pixel| pixel[..3] == [0xff, 0xff, 0xff])
+            .count();
+        assert_eq!(edge, (n_frames * 3) % 64, "frame {}", n_frames);
+        assert!(map[edge * 4..64 * 4]
+            .chunks_exact(4)
+            .all(|pixel| pixel[..3] == [0x00, 0x00, 0x00]));
+    }
+}
+
+#[test]
+fn custom_pattern_draw_signal() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src pattern=custom num-buffers=3 \
+         ! video/x-raw,format=BGRx,width=32,height=4,framerate=30/1 ! fakesink name=sink",
+    )
+    .unwrap();
+
+    let streaming_thread = Arc::new(Mutex::new(None));
+    let handler_thread = streaming_thread.clone();
+    element(&pipeline, "src").connect("draw", false, move |args| {
+        let width = args[2].get::<u32>().unwrap() as usize;
+        let height = args[3].get::<u32>().unwrap() as usize;
+        let stride = args[4].get::<i32>().unwrap() as usize;
+        let data = args[5].get::<glib::Pointer>().unwrap() as *mut u8;
+        *handler_thread.lock().unwrap() = Some(std::thread::current().id());
+
+        let data = unsafe { std::slice::from_raw_parts_mut(data, stride * height) };
+        for line in data.chunks_exact_mut(stride) {
+            for (x, pixel) in line[..width * 4].chunks_exact_mut(4).enumerate() {
+                pixel[..3].fill((x * 8) as u8);
+            }
+        }
+        None
+    });
+
+    let buffers = buffers(&pipeline);
+    assert_eq!(buffers.len(), 3);
+    for buffer in buffers {
+        let map = buffer.map_readable().unwrap();
+        for line in map.chunks_exact(32 * 4) {
+            for (x, pixel) in line.chunks_exact(4).enumerate() {
+                assert_eq!(pixel[..3], [(x * 8) as u8; 3]);
+            }
+        }
+    }
+
+    let streaming_thread = streaming_thread.lock().unwrap().expect("not drawn");
+    assert_ne!(streaming_thread, std::thread::current().id());
+}
+
+#[test]
+fn custom_pattern_without_handler_is_black() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern pattern=custom foreground-color=0xffffffff num-buffers=2 \
+         ! video/x-raw,format=BGRx,width=32,height=4,framerate=30/1 ! fakesink name=sink",
+    )
+    .unwrap();
+
+    for buffer in buffers(&pipeline) {
+        let map = buffer.map_readable().unwrap();
+        assert!(map.chunks_exact(4).all(|pixel| pixel[..3] == [0x00; 3]));
+    }
+}
+
+#[test]
+fn reversed_speed_returns_to_start() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src speed=5 size=10 num-buffers=21 \
+         ! video/x-raw,format=BGRx,width=4,height=100,framerate=10/1 ! fakesink name=sink",
+    )
+    .unwrap();
+
+    // 10 frames downwards, then 10 frames upwards
+    let change = gst::Structure::builder("set-test-pattern")
+        .field("speed", -5i32)
+        .field("running-time", 1_000_000_000u64)
+        .build();
+    assert!(element(&pipeline, "src").send_event(gst::event::CustomDownstream::new(change)));
+
+    let frames = buffers(&pipeline)
+        .iter()
+        .map(|buffer| buffer.map_readable().unwrap().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(frames.len(), 21);
+    assert_ne!(frames[10], frames[0]);
+    for n in 0..10 {
+        assert_eq!(frames[20 - n], frames[n], "frame {}", n);
+    }
+}
+
+// First line of the bar in a BGRx buffer `width` pixels wide
+fn bar_top(buffer: &gst::Buffer, width: usize) -> Option<usize> {
+    let map = buffer.map_readable().unwrap();
+    map.chunks_exact(width * 4)
+        .position(|line| line[..3] == [0xff, 0xff, 0xff])
+}
+
+#[test]
+fn velocity_independent_of_framerate() {
+    init();
+
+    for &fps in &[25, 60] {
+        let pipeline = gst::parse_launch(&format!(
+            "rstestpattern velocity=100 size=10 num-buffers={} \
+             ! video/x-raw,format=BGRx,width=4,height=400,framerate={}/1 ! fakesink name=sink",
+            fps + 1,
+            fps
+        ))
+        .unwrap();
+
+        let buffers = buffers(&pipeline);
+        let first = &buffers[0];
+        let last = &buffers[fps];
+        assert_eq!(last.pts(), Some(gst::ClockTime::SECOND));
+        assert_eq!(bar_top(first, 4), Some(0));
+        assert_eq!(bar_top(last, 4), Some(100), "{} fps", fps);
+    }
+}
+
+#[test]
+fn controlled_size() {
+    use gst_controller::prelude::*;
+
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src speed=0 num-buffers=11 \
+         ! video/x-raw,format=BGRx,width=4,height=100,framerate=10/1 ! fakesink name=sink",
+    )
+    .unwrap();
+
+    let src = element(&pipeline, "src");
+    let control_source = gst_controller::InterpolationControlSource::new();
+    control_source.set_mode(gst_controller::InterpolationMode::Linear);
+    control_source.set(gst::ClockTime::ZERO, 10.0);
+    control_source.set(gst::ClockTime::SECOND, 50.0);
+    let binding = gst_controller::DirectControlBinding::new_absolute(&src, "size", &control_source);
+    src.add_control_binding(&binding).unwrap();
+
+    let buffers = buffers(&pipeline);
+    assert_eq!(buffers.len(), 11);
+    for (n, buffer) in buffers.iter().enumerate() {
+        let map = buffer.map_readable().unwrap();
+        let size = map
+            .chunks_exact(4 * 4)
+            .filter(|line| line[..3] == [0xff, 0xff, 0xff])
+            .count();
+        assert_eq!(size, 10 + 4 * n, "frame {}", n);
+    }
+}
+
+#[test]
+fn same_pts_renders_identically() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern ! video/x-raw,format=BGRx,width=16,height=100,framerate=10/1 \
+         ! fakesink name=sink sync=false",
+    )
+    .unwrap();
+    let buffers = handoffs(&pipeline);
+
+    let seek = |start: gst::ClockTime| {
+        pipeline
+            .seek(
+                1.0,
+                gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+                gst::SeekType::Set,
+                start,
+                gst::SeekType::Set,
+                gst::ClockTime::SECOND,
+            )
+            .unwrap();
+    };
+
+    pipeline.set_state(gst::State::Paused).unwrap();
+    wait_for(&pipeline, &[gst::MessageType::AsyncDone]);
+    seek(gst::ClockTime::ZERO);
+    pipeline.set_state(gst::State::Playing).unwrap();
+    wait_for(&pipeline, &[gst::MessageType::Eos]);
+    let first_run = std::mem::take(&mut *buffers.lock().unwrap());
+
+    // Back into the middle, the frames in between were rendered before
+    seek(gst::ClockTime::from_mseconds(500));
+    wait_for(&pipeline, &[gst::MessageType::Eos]);
+    let second_run = std::mem::take(&mut *buffers.lock().unwrap());
+    pipeline.set_state(gst::State::Null).unwrap();
+
+    assert!(second_run.len() >= 5);
+    for buffer in second_run {
+        let pts = buffer.pts();
+        let earlier = first_run
+            .iter()
+            .find(|earlier| earlier.pts() == pts)
+            .expect("PTS not rendered before");
+        let map = buffer.map_readable().unwrap();
+        let earlier_map = earlier.map_readable().unwrap();
+        assert!(map.as_slice() == earlier_map.as_slice(), "{:?}", pts);
+    }
+}
+
+#[test]
+fn translucent_foreground() {
+    init();
+
+    let first_pixels = |format: &str| {
+        let pipeline = gst::parse_launch(&format!(
+            "rstestpattern foreground-color=0x80ffffff speed=0 size=10 num-buffers=1 \
+             ! video/x-raw,format={},width=4,height=20,framerate=30/1 ! fakesink name=sink",
+            format
+        ))
+        .unwrap();
+
+        let buffers = buffers(&pipeline);
+        let map = buffers[0].map_readable().unwrap();
+        // In the bar and below it
+        (map[..4].to_vec(), map[15 * 16..15 * 16 + 4].to_vec())
+    };
+
+    // Blended over the black background without an alpha channel
+    let (bar, background) = first_pixels("BGRx");
+    assert_eq!(bar[..3], [0x80, 0x80, 0x80]);
+    assert_eq!(background[..3], [0x00, 0x00, 0x00]);
+
+    // Written through otherwise
+    let (bar, background) = first_pixels("BGRA");
+    assert_eq!(bar, [0xff, 0xff, 0xff, 0x80]);
+    assert_eq!(background, [0x00, 0x00, 0x00, 0xff]);
+}
+
+#[test]
+fn loop_boundary_equals_first_frame() {
+    init();
+
+    for pattern in &["bar", "ball", "bouncing-ball"] {
+        let pipeline = gst::parse_launch(&format!(
+            "rstestpattern pattern={} loop-duration=1000000000 num-buffers=11 \
+             ! video/x-raw,format=BGRx,width=64,height=48,framerate=10/1 ! fakesink name=sink",
+            pattern
+        ))
+        .unwrap();
+
+        let buffers = buffers(&pipeline);
+        let first = buffers[0].map_readable().unwrap();
+        let boundary = buffers[10].map_readable().unwrap();
+        assert_eq!(buffers[10].pts(), Some(gst::ClockTime::SECOND));
+        assert!(first.as_slice() == boundary.as_slice(), "{}", pattern);
+        assert!(buffers[5].map_readable().unwrap().as_slice() != first.as_slice());
+    }
+}
+
+#[test]
+fn accelerated_displacement() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern speed=2 acceleration=0.5 size=10 num-buffers=41 \
+         ! video/x-raw,format=BGRx,width=2,height=1000,framerate=30/1 ! fakesink name=sink",
+    )
+    .unwrap();
+
+    let buffers = buffers(&pipeline);
+    assert_eq!(buffers.len(), 41);
+    for (n, buffer) in buffers.iter().enumerate() {
+        // s₀N + ½aN²
+        let n_frames = n as f64;
+        let displacement = 2.0 * n_frames + 0.5 * 0.5 * n_frames * n_frames;
+        assert_eq!(
+            bar_top(buffer, 2),
+            Some(displacement.round() as usize),
+            "frame {}",
+            n
+        );
+    }
+}
+
+#[test]
+fn color_strings() {
+    init();
+
+    let src = gst::ElementFactory::make("rstestpattern", None).unwrap();
+
+    for &(string, color) in &[
+        ("#ff8000", 0xffff8000u32),
+        ("#80ff8000", 0x80ff8000),
+        ("blue", 0xff0000ff),
+    ] {
+        src.set_property("foreground-color-string", string);
+        assert_eq!(src.property::<u32>("foreground-color"), color);
+        src.set_property("background-color-string", string);
+        assert_eq!(src.property::<u32>("background-color"), color);
+    }
+
+    src.set_property("foreground-color", 0x12345678u32);
+    assert_eq!(
+        src.property::<Option<String>>("foreground-color-string")
+            .as_deref(),
+        Some("#12345678")
+    );
+
+    // Invalid strings keep the previous color
+    src.set_property("foreground-color-string", "#12345");
+    assert_eq!(src.property::<u32>("foreground-color"), 0x12345678);
+}
+
+#[test]
+fn speed_notified_once_per_change() {
+    init();
+
+    let src = gst::ElementFactory::make("rstestpattern", None).unwrap();
+    let notifications = Arc::new(Mutex::new(Vec::new()));
+    let handler_notifications = notifications.clone();
+    src.connect_notify(Some("speed"), move |src, _| {
+        let speed = src.property::<i32>("speed");
+        handler_notifications.lock().unwrap().push(speed);
+    });
+
+    src.set_property("speed", 7i32);
+    src.set_property("speed", 7i32);
+    src.set_property("speed", -3i32);
+    // Other properties don't notify about speed
+    src.set_property("size", 20u32);
+
+    assert_eq!(*notifications.lock().unwrap(), vec![7, -3]);
+}
+
+#[test]
+fn text_blended_or_attached_as_meta() {
+    init();
+
+    let first_frame = |text: &str, features: &str| {
+        let pipeline = gst::parse_launch(&format!(
+            "rstestpattern speed=0 size=10 text={} text-x=8 text-y=24 text-scale=2 num-buffers=1 \
+             ! video/x-raw{},format=BGRx,width=64,height=48,framerate=30/1 ! fakesink name=sink",
+            text, features
+        ))
+        .unwrap();
+
+        buffers(&pipeline).remove(0)
+    };
+
+    let plain = first_frame("\"\"", "");
+    let plain = plain.map_readable().unwrap();
+    for &features in &["", "(meta:GstVideoOverlayComposition)"] {
+        let buffer = first_frame("A", features);
+        let meta = buffer.meta::<gst_video::VideoOverlayCompositionMeta>();
+        let blended = buffer.map_readable().unwrap().as_slice() != plain.as_slice();
+        assert!(meta.is_some() != blended, "{:?}", features);
+        assert_eq!(meta.is_some(), !features.is_empty());
+
+        if let Some(meta) = meta {
+            let composition = meta.overlay();
+            assert_eq!(composition.n_rectangles(), 1);
+            let (x, y, _, _) = composition.rectangle(0).unwrap().render_rectangle();
+            assert!(x >= 8 && y >= 24, "{}x{}", x, y);
+        }
+    }
+}
+
+#[test]
+fn jitter_seed_independent_of_seed() {
+    init();
+
+    let bar_tops = |seed: u64, jitter_seed: u64| {
+        let pipeline = gst::parse_launch(&format!(
+            "rstestpattern speed=0 size=4 offset=20 jitter=8 seed={} jitter-seed={} \
+             num-buffers=20 ! video/x-raw,format=BGRx,width=2,height=48,framerate=30/1 \
+             ! fakesink name=sink",
+            seed, jitter_seed
+        ))
+        .unwrap();
+
+        buffers(&pipeline)
+            .iter()
+            .map(|buffer| bar_top(buffer, 2))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(bar_tops(1, 5), bar_tops(2, 5));
+    assert_ne!(bar_tops(1, 5), bar_tops(1, 6));
+    // Without one the jitter follows the seed
+    assert_ne!(bar_tops(1, 0), bar_tops(2, 0));
+    assert_eq!(bar_tops(1, 0), bar_tops(1, 0));
+}
+
+#[test]
+fn digests_verify_recorded_run() {
+    init();
+
+    let launch = |properties: &str| {
+        gst::parse_launch(&format!(
+            "rstestpattern verification=true num-buffers=10 {} \
+             ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 ! fakesink",
+            properties
+        ))
+        .unwrap()
+    };
+
+    // Record
+    let pipeline = launch("");
+    pipeline.set_state(gst::State::Playing).unwrap();
+    let mut digests = Vec::new();
+    loop {
+        let msg = wait_for(
+            &pipeline,
+            &[gst::MessageType::Element, gst::MessageType::Eos],
+        );
+        match msg.view() {
+            gst::MessageView::Element(element) => {
+                let s = element.structure().unwrap();
+                if s.name() == "rstestpattern-digest" {
+                    assert_eq!(s.get::<u64>("frame-index").unwrap(), digests.len() as u64);
+                    digests.push(s.get::<String>("digest").unwrap());
+                }
+            }
+            _ => break,
+        }
+    }
+    pipeline.set_state(gst::State::Null).unwrap();
+    assert_eq!(digests.len(), 10);
+
+    let location =
+        std::env::temp_dir().join(format!("rstestpattern-digests-{}", std::process::id()));
+    std::fs::write(&location, digests.join("\n")).unwrap();
+    let expected = format!(
+        "expected-digests={} mismatch-action=error",
+        location.display()
+    );
+
+    // A run of the same pipeline matches
+    run(&launch(&expected));
+
+    // One changed digest fails at that frame
+    let mut tampered = digests.clone();
+    tampered[3] = format!("{:016x}", u64::from_str_radix(&digests[3], 16).unwrap() ^ 1);
+    std::fs::write(&location, tampered.join("\n")).unwrap();
+    let pipeline = launch(&expected);
+    pipeline.set_state(gst::State::Playing).unwrap();
+    let msg = pipeline
+        .bus()
+        .unwrap()
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(10),
+            &[gst::MessageType::Error, gst::MessageType::Eos],
+        )
+        .expect("timed out");
+    pipeline.set_state(gst::State::Null).unwrap();
+    std::fs::remove_file(&location).unwrap();
+    match msg.view() {
+        gst::MessageView::Error(err) => {
+            let debug = err.debug().unwrap_or_default();
+            assert!(debug.contains("Frame 3 "), "{}", debug);
+        }
+        _ => panic!("tampered run passed"),
+    }
+}
+
+#[test]
+fn meta_stream_matches_video() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src meta-pad=true size=10 speed=3 num-buffers=20 \
+         ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 ! queue ! funnel name=mux \
+         ! fakesink name=sink  src.meta ! queue ! mux.",
+    )
+    .unwrap();
+
+    let (mut video, mut meta): (Vec<_>, Vec<_>) = buffers(&pipeline)
+        .into_iter()
+        .partition(|buffer| buffer.size() == 64 * 48 * 4);
+    assert_eq!(video.len(), 20);
+    assert_eq!(meta.len(), 20);
+
+    // The order the two streams are interleaved in is up to the queues
+    video.sort_by_key(|buffer| buffer.pts());
+    meta.sort_by_key(|buffer| buffer.pts());
+    for (n, (video, meta)) in video.iter().zip(meta.iter()).enumerate() {
+        assert_eq!(meta.pts(), video.pts(), "frame {}", n);
+        assert_eq!(meta.duration(), video.duration(), "frame {}", n);
+
+        let map = meta.map_readable().unwrap();
+        let state = std::str::from_utf8(&map)
+            .unwrap()
+            .parse::<gst::Structure>()
+            .unwrap();
+        assert_eq!(state.name(), "pattern-state");
+        assert_eq!(state.get::<u64>("frame-index").unwrap(), n as u64);
+        assert_eq!(
+            state.get::<u32>("offset").unwrap(),
+            bar_top(video, 64).unwrap() as u32
+        );
+    }
+}
+
+#[test]
+fn timeline_keyframes_land_on_frames() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src speed=0 size=10 num-buffers=16 \
+         ! video/x-raw,format=BGRx,width=4,height=48,framerate=30/1 ! fakesink name=sink",
+    )
+    .unwrap();
+    let keyframe = |time: u64, name: &str, value: u32| {
+        gst::Structure::builder("keyframe")
+            .field("time", time)
+            .field(name, value)
+            .build()
+            .to_send_value()
+    };
+    // Sorted by time when set
+    let timeline = gst::Array::from_values(vec![
+        keyframe(250_000_000, "size", 20),
+        keyframe(100_000_000, "foreground-color", 0xffff0000),
+        keyframe(400_000_000, "background-color", 0xff0000ff),
+    ]);
+    element(&pipeline, "src").set_property("timeline", timeline);
+
+    let pixel = |buffer: &gst::Buffer, line: usize| {
+        let map = buffer.map_readable().unwrap();
+        [map[line * 16], map[line * 16 + 1], map[line * 16 + 2]]
+    };
+    let buffers = buffers(&pipeline);
+    assert_eq!(buffers.len(), 16);
+    for (n, buffer) in buffers.iter().enumerate() {
+        // 100 ms is frame 3, 250 ms between frames 7 and 8, 400 ms frame 12
+        let foreground = if n >= 3 { [0, 0, 0xff] } else { [0xff; 3] };
+        let background = if n >= 12 { [0xff, 0, 0] } else { [0; 3] };
+        let line_15 = if n >= 8 { foreground } else { background };
+        assert_eq!(pixel(buffer, 0), foreground, "frame {}", n);
+        assert_eq!(pixel(buffer, 15), line_15, "frame {}", n);
+        assert_eq!(pixel(buffer, 47), background, "frame {}", n);
+    }
+}
+
+#[test]
+fn color_balance_interface() {
+    use gst::glib::translate::ToGlibPtr;
+    use gst_video::prelude::*;
+
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src foreground-color=0xffff0000 speed=0 size=10 num-buffers=1 \
+         ! video/x-raw,format=BGRx,width=4,height=20,framerate=30/1 ! fakesink name=sink",
+    )
+    .unwrap();
+    let src = element(&pipeline, "src");
+    let balance = src.dynamic_cast_ref::<gst_video::ColorBalance>().unwrap();
+
+    let channels = balance.list_channels();
+    let labels = channels
+        .iter()
+        .map(|channel| unsafe {
+            let channel: *mut gst_video::ffi::GstColorBalanceChannel = channel.to_glib_none().0;
+            std::ffi::CStr::from_ptr((*channel).label)
+                .to_str()
+                .unwrap()
+                .to_owned()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(labels, ["BRIGHTNESS", "CONTRAST", "HUE", "SATURATION"]);
+    assert_eq!(
+        balance.balance_type(),
+        gst_video::ColorBalanceType::Software
+    );
+
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let handler_changes = changes.clone();
+    balance.connect_value_changed(move |_, _, value| {
+        handler_changes.lock().unwrap().push(value);
+    });
+
+    // Fully desaturated and brighter
+    balance.set_value(&channels[0], 500);
+    balance.set_value(&channels[3], -1000);
+    balance.set_value(&channels[3], -1000);
+    assert_eq!(*changes.lock().unwrap(), vec![500, -1000]);
+    assert_eq!(balance.value(&channels[0]), 500);
+    assert_eq!(balance.value(&channels[1]), 0);
+    assert_eq!(balance.value(&channels[3]), -1000);
+
+    let buffers = buffers(&pipeline);
+    let map = buffers[0].map_readable().unwrap();
+    let (bar, background) = (&map[..3], &map[15 * 16..15 * 16 + 3]);
+    assert!(bar[0] == bar[1] && bar[1] == bar[2], "{:?}", bar);
+    assert!(bar[0] > 127, "{:?}", bar);
+    assert_eq!(background, [127; 3]);
+}
+
+#[test]
+fn orientation_interface_flips_like_properties() {
+    use gst_video::prelude::*;
+
+    init();
+
+    let first_frame = |configure: &dyn Fn(&gst::Element)| {
+        let pipeline = gst::parse_launch(
+            "rstestpattern name=src speed=0 size=10 text=AB num-buffers=1 \
+             ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 ! fakesink name=sink",
+        )
+        .unwrap();
+        configure(&element(&pipeline, "src"));
+        let buffers = buffers(&pipeline);
+        let map = buffers[0].map_readable().unwrap();
+        map.to_vec()
+    };
+
+    let plain = first_frame(&|_| ());
+    for &(hflip, vflip) in &[(true, false), (false, true), (true, true)] {
+        let by_property = first_frame(&|src| {
+            src.set_property("mirror-x", hflip);
+            src.set_property("mirror-y", vflip);
+        });
+        let by_interface = first_frame(&|src| {
+            let notified = Arc::new(Mutex::new(Vec::new()));
+            let handler_notified = notified.clone();
+            src.connect_notify(None, move |_, pspec| {
+                handler_notified
+                    .lock()
+                    .unwrap()
+                    .push(pspec.name().to_owned());
+            });
+
+            let orientation = src
+                .dynamic_cast_ref::<gst_video::VideoOrientation>()
+                .unwrap();
+            orientation.set_hflip(hflip).unwrap();
+            orientation.set_vflip(vflip).unwrap();
+            assert_eq!(orientation.hflip().unwrap(), hflip);
+            assert_eq!(orientation.vflip().unwrap(), vflip);
+            assert_eq!(src.property::<bool>("mirror-x"), hflip);
+            assert_eq!(src.property::<bool>("mirror-y"), vflip);
+
+            let expected = [(hflip, "mirror-x"), (vflip, "mirror-y")]
+                .iter()
+                .filter(|(flip, _)| *flip)
+                .map(|(_, name)| name.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(*notified.lock().unwrap(), expected);
+        });
+
+        assert!(by_interface == by_property, "{} {}", hflip, vflip);
+        assert!(by_interface != plain, "{} {}", hflip, vflip);
+    }
+}
+
+#[test]
+fn seeks_and_restarts_carry_seqnums_and_groups() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 \
+         ! fakesink name=sink",
+    )
+    .unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let probe_events = events.clone();
+    element(&pipeline, "sink")
+        .static_pad("sink")
+        .unwrap()
+        .add_probe(
+            gst::PadProbeType::EVENT_DOWNSTREAM | gst::PadProbeType::EVENT_FLUSH,
+            move |_, info| {
+                if let Some(gst::PadProbeData::Event(ref event)) = info.data {
+                    let group_id = match event.view() {
+                        gst::EventView::StreamStart(stream_start) => stream_start.group_id(),
+                        _ => None,
+                    };
+                    let event = (event.type_(), event.seqnum(), group_id);
+                    probe_events.lock().unwrap().push(event);
+                }
+                gst::PadProbeReturn::Ok
+            },
+        )
+        .unwrap();
+    let take_events = || std::mem::take(&mut *events.lock().unwrap());
+    let stream_start = |events: &[(gst::EventType, gst::Seqnum, Option<gst::GroupId>)]| {
+        let starts = events
+            .iter()
+            .filter(|(type_, _, _)| *type_ == gst::EventType::StreamStart)
+            .collect::<Vec<_>>();
+        assert_eq!(starts.len(), 1, "{:?}", events);
+        starts[0].2.expect("no group id")
+    };
+
+    pipeline.set_state(gst::State::Paused).unwrap();
+    wait_for(&pipeline, &[gst::MessageType::AsyncDone]);
+    let first_group = stream_start(&take_events());
+
+    let mut seqnums = Vec::new();
+    for &position in &[gst::ClockTime::SECOND, 3 * gst::ClockTime::SECOND] {
+        let seek = gst::event::Seek::new(
+            1.0,
+            gst::SeekFlags::FLUSH,
+            gst::SeekType::Set,
+            position,
+            gst::SeekType::None,
+            gst::ClockTime::NONE,
+        );
+        let seqnum = seek.seqnum();
+        assert!(pipeline.send_event(seek));
+        wait_for(&pipeline, &[gst::MessageType::AsyncDone]);
+
+        // Flushing and the new segment carry the seqnum of the seek
+        let events = take_events();
+        for &type_ in &[
+            gst::EventType::FlushStart,
+            gst::EventType::FlushStop,
+            gst::EventType::Segment,
+        ] {
+            let event = events
+                .iter()
+                .find(|(event_type, _, _)| *event_type == type_)
+                .unwrap_or_else(|| panic!("no {:?} in {:?}", type_, events));
+            assert_eq!(event.1, seqnum, "{:?}", type_);
+        }
+        // Still the same stream
+        assert!(events
+            .iter()
+            .all(|(type_, _, _)| *type_ != gst::EventType::StreamStart));
+        seqnums.push(seqnum);
+    }
+    assert_ne!(seqnums[0], seqnums[1]);
+
+    // A new start is a new group
+    pipeline.set_state(gst::State::Ready).unwrap();
+    take_events();
+    pipeline.set_state(gst::State::Paused).unwrap();
+    wait_for(&pipeline, &[gst::MessageType::AsyncDone]);
+    let events = take_events();
+    let second_group = stream_start(&events);
+    assert_ne!(first_group, second_group);
+    let segment = events
+        .iter()
+        .find(|(type_, _, _)| *type_ == gst::EventType::Segment)
+        .unwrap();
+    assert!(!seqnums.contains(&segment.1));
+
+    pipeline.set_state(gst::State::Null).unwrap();
+}
+
+#[test]
+fn restored_state_continues_sequence() {
+    init();
+
+    for pattern in &["snow", "bar", "bouncing-ball"] {
+        let launch = |num_buffers: u32| {
+            gst::parse_launch(&format!(
+                "rstestpattern name=src pattern={} seed=3 speed=3 size=10 num-buffers={} \
+                 ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 ! fakesink name=sink",
+                pattern, num_buffers
+            ))
+            .unwrap()
+        };
+
+        let uninterrupted = buffers(&launch(101));
+        assert_eq!(uninterrupted.len(), 101);
+
+        // 100 frames, then the state is taken over by a new pipeline
+        let first = launch(100);
+        let first_buffers = handoffs(&first);
+        first.set_state(gst::State::Playing).unwrap();
+        wait_for(&first, &[gst::MessageType::Eos]);
+        let state = element(&first, "src").property::<gst::Structure>("state");
+        first.set_state(gst::State::Null).unwrap();
+        assert_eq!(first_buffers.lock().unwrap().len(), 100);
+        assert_eq!(state.get::<u64>("frames").unwrap(), 100);
+
+        let second = launch(1);
+        element(&second, "src").set_property("state", &state);
+        let continued = buffers(&second);
+
+        let expected = uninterrupted[100].map_readable().unwrap();
+        let continued = continued[0].map_readable().unwrap();
+        assert!(expected.as_slice() == continued.as_slice(), "{}", pattern);
+    }
+}
+
+#[test]
+fn three_objects_at_known_frame() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src num-buffers=11 \
+         ! video/x-raw,format=BGRx,width=160,height=120,framerate=30/1 ! fakesink name=sink",
+    )
+    .unwrap();
+    let object =
+        |shape: &str, size: u32, color: u32, position: (i32, i32), velocity: (i32, i32)| {
+            gst::Structure::builder("object")
+                .field("shape", shape)
+                .field("size", size)
+                .field("color", color)
+                .field("x", position.0)
+                .field("y", position.1)
+                .field("velocity-x", velocity.0)
+                .field("velocity-y", velocity.1)
+                .field("motion", if shape == "ball" { "bounce" } else { "wrap" })
+                .build()
+                .to_send_value()
+        };
+    let objects = gst::Array::from_values(vec![
+        object("rect", 10, 0xffff0000, (0, 0), (3, 2)),
+        object("ball", 20, 0xff00ff00, (100, 10), (-4, 5)),
+        // Drawn over the ball where they overlap
+        object("bar", 6, 0xff0000ff, (0, 100), (0, -3)),
+    ]);
+    element(&pipeline, "src").set_property("objects", objects);
+
+    let buffers = buffers(&pipeline);
+    let map = buffers[10].map_readable().unwrap();
+    let pixel = |x: usize, y: usize| {
+        [
+            map[(y * 160 + x) * 4],
+            map[(y * 160 + x) * 4 + 1],
+            map[(y * 160 + x) * 4 + 2],
+        ]
+    };
+    let (red, green, blue, black) = ([0, 0, 0xff], [0, 0xff, 0], [0xff, 0, 0], [0; 3]);
+
+    // Rect moved by 10 × (3, 2) to (30, 20)
+    assert_eq!(pixel(30, 20), red);
+    assert_eq!(pixel(39, 29), red);
+    assert_eq!(pixel(29, 20), black);
+    assert_eq!(pixel(40, 29), black);
+    // Ball bounced to (60, 60), its corners are outside the circle
+    assert_eq!(pixel(70, 62), green);
+    assert_eq!(pixel(61, 61), black);
+    assert_eq!(pixel(70, 79), green);
+    // Bar moved up to line 70
+    assert_eq!(pixel(0, 70), blue);
+    assert_eq!(pixel(70, 72), blue);
+    assert_eq!(pixel(159, 75), blue);
+    assert_eq!(pixel(0, 76), black);
+    assert_eq!(pixel(70, 76), green);
+}
+
+#[test]
+fn corruption_rate_and_damage() {
+    init();
+
+    let launch = |properties: &str| {
+        gst::parse_launch(&format!(
+            "rstestpattern name=src seed=11 size=10 num-buffers=400 {} \
+             ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 ! fakesink name=sink",
+            properties
+        ))
+        .unwrap()
+    };
+    let clean = buffers(&launch(""));
+
+    for mode in &[
+        "bit-flips",
+        "zeroed-lines",
+        "truncated-buffer",
+        "wrong-stride",
+    ] {
+        let pipeline = launch(&format!(
+            "corruption-probability=0.25 corruption-mode={}",
+            mode
+        ));
+        let collected = handoffs(&pipeline);
+        pipeline.set_state(gst::State::Playing).unwrap();
+        wait_for(&pipeline, &[gst::MessageType::Eos]);
+        let counted = element(&pipeline, "src").property::<u64>("corrupted-frames");
+        pipeline.set_state(gst::State::Null).unwrap();
+        let corrupted = std::mem::take(&mut *collected.lock().unwrap());
+        assert_eq!(corrupted.len(), 400);
+
+        // 100 expected, more than 4 standard deviations off is a failure
+        let flagged = corrupted
+            .iter()
+            .filter(|buffer| buffer.flags().contains(gst::BufferFlags::CORRUPTED))
+            .count();
+        assert_eq!(flagged as u64, counted, "{}", mode);
+        assert!((65..=135).contains(&flagged), "{}: {}", mode, flagged);
+
+        for (n, (clean, buffer)) in clean.iter().zip(corrupted.iter()).enumerate() {
+            let clean = clean.map_readable().unwrap();
+            let map = buffer.map_readable().unwrap();
+            if !buffer.flags().contains(gst::BufferFlags::CORRUPTED) {
+                assert!(clean.as_slice() == map.as_slice(), "{} frame {}", mode, n);
+                continue;
+            }
+
+            match *mode {
+                "bit-flips" => {
+                    let bits = clean
+                        .iter()
+                        .zip(map.iter())
+                        .map(|(a, b)| (a ^ b).count_ones())
+                        .sum::<u32>();
+                    assert!((1..=16).contains(&bits), "frame {}: {} bits", n, bits);
+                }
+                "zeroed-lines" => {
+                    let damaged = clean
+                        .chunks(64 * 4)
+                        .zip(map.chunks(64 * 4))
+                        .enumerate()
+                        .filter(|(_, (a, b))| a != b)
+                        .map(|(line, (_, b))| {
+                            assert!(b.iter().all(|&byte| byte == 0), "frame {}", n);
+                            line
+                        })
+                        .collect::<Vec<_>>();
+                    // One band of at most height / 8 lines, lines already
+                    // black may not show up
+                    if let (Some(first), Some(last)) = (damaged.first(), damaged.last()) {
+                        assert!(last - first < 6, "frame {}: {:?}", n, damaged);
+                    }
+                }
+                "truncated-buffer" => {
+                    assert_eq!(map.len(), clean.len() / 2, "frame {}", n);
+                    assert!(map.as_slice() == &clean[..map.len()], "frame {}", n);
+                }
+                _ => {
+                    let meta = buffer.meta::<gst_video::VideoMeta>().unwrap();
+                    assert_eq!(meta.stride()[0], 64 * 4 - 4, "frame {}", n);
+                    assert!(clean.as_slice() == map.as_slice(), "frame {}", n);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn ab_labels_match_pixels() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src speed=0 size=10 ab-interval=4 num-buffers=40 \
+         ! video/x-raw,format=BGRx,width=4,height=20,framerate=30/1 ! fakesink name=sink",
+    )
+    .unwrap();
+    let src = element(&pipeline, "src");
+    let config = |color: u32| {
+        gst::Structure::builder("config")
+            .field("foreground-color", color)
+            .build()
+    };
+    src.set_property("config-a", config(0xffff0000));
+    src.set_property("config-b", config(0xff00ff00));
+
+    let buffers = buffers(&pipeline);
+    assert_eq!(buffers.len(), 40);
+    for (n, buffer) in buffers.iter().enumerate() {
+        let label = buffer
+            .meta::<gst::ReferenceTimestampMeta>()
+            .expect("no configuration label");
+        let reference = label.reference().structure(0).unwrap();
+        assert_eq!(reference.name(), "timestamp/x-rstestpattern-config");
+        let config = reference.get::<String>("config").unwrap();
+
+        let map = buffer.map_readable().unwrap();
+        let color = match map[..3] {
+            [0, 0, 0xff] => "a",
+            [0, 0xff, 0] => "b",
+            _ => panic!("frame {} has bar color {:?}", n, &map[..3]),
+        };
+        // Both sides of every boundary are labelled like they look
+        assert_eq!(config, color, "frame {}", n);
+        assert_eq!(
+            config,
+            if (n / 4) % 2 == 0 { "a" } else { "b" },
+            "frame {}",
+            n
+        );
+    }
+}
+
+#[test]
+fn cycled_resolutions_soak() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src cycle-interval=50 num-buffers=280 \
+         ! videoconvert ! fakesink name=sink",
+    )
+    .unwrap();
+    let resolutions = ["64x48@30/1", "32x24@15/1", "80x60@25/1"];
+    element(&pipeline, "src").set_property(
+        "cycle-resolutions",
+        gst::Array::from_values(resolutions.iter().map(|s| s.to_send_value())),
+    );
+
+    // Caps events with the number of buffers that came before them
+    let caps = Arc::new(Mutex::new((0usize, Vec::new())));
+    let probe_caps = caps.clone();
+    element(&pipeline, "sink")
+        .static_pad("sink")
+        .unwrap()
+        .add_probe(
+            gst::PadProbeType::BUFFER | gst::PadProbeType::EVENT_DOWNSTREAM,
+            move |_, info| {
+                let mut guard = probe_caps.lock().unwrap();
+                let (n_buffers, caps) = &mut *guard;
+                match info.data {
+                    Some(gst::PadProbeData::Buffer(_)) => *n_buffers += 1,
+                    Some(gst::PadProbeData::Event(ref event)) => {
+                        if let gst::EventView::Caps(c) = event.view() {
+                            caps.push((*n_buffers, c.caps_owned()));
+                        }
+                    }
+                    _ => (),
+                }
+                gst::PadProbeReturn::Ok
+            },
+        )
+        .unwrap();
+
+    let buffers = buffers(&pipeline);
+    assert_eq!(buffers.len(), 280);
+    for pair in buffers.windows(2) {
+        let end = pair[0].pts().unwrap() + pair[0].duration().unwrap();
+        assert!(pair[1].pts().unwrap() > pair[0].pts().unwrap());
+        assert_eq!(pair[1].pts(), Some(end));
+    }
+
+    // The default size, then a switch every 50 frames
+    let caps = &caps.lock().unwrap().1;
+    let expected = [
+        (0, (320, 240, gst::Fraction::new(30, 1))),
+        (50, (64, 48, gst::Fraction::new(30, 1))),
+        (100, (32, 24, gst::Fraction::new(15, 1))),
+        (150, (80, 60, gst::Fraction::new(25, 1))),
+        (200, (64, 48, gst::Fraction::new(30, 1))),
+        (250, (32, 24, gst::Fraction::new(15, 1))),
+    ];
+    assert_eq!(caps.len(), expected.len(), "{:?}", caps);
+    for (n, ((start, caps), (expected_start, (width, height, fps)))) in
+        caps.iter().zip(expected.iter()).enumerate()
+    {
+        let info = gst_video::VideoInfo::from_caps(caps).unwrap();
+        assert_eq!(start, expected_start, "caps {}", n);
+        assert_eq!(
+            (info.width(), info.height(), info.fps()),
+            (*width, *height, *fps)
+        );
+
+        let end = expected
+            .get(n + 1)
+            .map_or(buffers.len(), |(start, _)| *start);
+        for buffer in &buffers[*start..end] {
+            assert_eq!(buffer.size(), info.size(), "caps {}", n);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use gst::prelude::*;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstrstestpattern::plugin_register_static().unwrap();
+    });
+}
+
+// Plays `pipeline` until EOS and returns the summary the analyzer posted
+// at EOS
+fn summary(pipeline: &gst::Element) -> gst::Structure {
+    pipeline.set_state(gst::State::Playing).unwrap();
+
+    let bus = pipeline.bus().unwrap();
+    let mut summary = None;
+    loop {
+        let msg = bus
+            .timed_pop_filtered(
+                gst::ClockTime::from_seconds(10),
+                &[
+                    gst::MessageType::Element,
+                    gst::MessageType::Eos,
+                    gst::MessageType::Error,
+                ],
+            )
+            .expect("timed out");
+        match msg.view() {
+            gst::MessageView::Element(element) => {
+                let s = element.structure().unwrap();
+                if s.name() == "rstestpatternanalyze-summary" && s.get::<bool>("eos").unwrap() {
+                    summary = Some(s.to_owned());
+                }
+            }
+            gst::MessageView::Error(err) => panic!("{} ({:?})", err.error(), err.debug()),
+            _ => break,
+        }
+    }
+
+    summary.expect("no summary at EOS")
+}
+
+fn counts(summary: &gst::Structure) -> (u64, u64, u64, u64) {
+    (
+        summary.get("frames").unwrap(),
+        summary.get("mismatches").unwrap(),
+        summary.get("dropped").unwrap(),
+        summary.get("duplicated").unwrap(),
+    )
+}
+
+#[test]
+fn clean_stream_passes() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern size=10 speed=3 num-buffers=30 \
+         ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 \
+         ! rstestpatternanalyze size=10 speed=3 tolerance=0 ! fakesink",
+    )
+    .unwrap();
+
+    let summary = summary(&pipeline);
+    pipeline.set_state(gst::State::Null).unwrap();
+    assert_eq!(counts(&summary), (30, 0, 0, 0));
+    assert_eq!(summary.get::<u32>("max-difference").unwrap(), 0);
+}
+
+#[test]
+fn corrupted_frames_counted_as_mismatches() {
+    init();
+
+    // Zeroed lines always differ from the gray background
+    let pipeline = gst::parse_launch(
+        "rstestpattern name=src size=10 background-color=0xff808080 seed=7 \
+         corruption-probability=0.3 corruption-mode=zeroed-lines num-buffers=50 \
+         ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 \
+         ! rstestpatternanalyze size=10 background-color=0xff808080 ! fakesink",
+    )
+    .unwrap();
+
+    let summary = summary(&pipeline);
+    let src = pipeline
+        .downcast_ref::<gst::Bin>()
+        .unwrap()
+        .by_name("src")
+        .unwrap();
+    let corrupted = src.property::<u64>("corrupted-frames");
+    pipeline.set_state(gst::State::Null).unwrap();
+
+    assert!(corrupted > 0);
+    assert_eq!(counts(&summary), (50, corrupted, 0, 0));
+}
+
+#[test]
+fn dropped_frames_counted() {
+    init();
+
+    let pipeline = gst::parse_launch(
+        "rstestpattern size=10 num-buffers=30 \
+         ! video/x-raw,format=BGRx,width=64,height=48,framerate=30/1 \
+         ! identity name=drop ! rstestpatternanalyze size=10 ! fakesink",
+    )
+    .unwrap();
+    let drop = pipeline
+        .downcast_ref::<gst::Bin>()
+        .unwrap()
+        .by_name("drop")
+        .unwrap();
+    drop.static_pad("src")
+        .unwrap()
+        .add_probe(gst::PadProbeType::BUFFER, |_, info| match info.data {
+            Some(gst::PadProbeData::Buffer(ref buffer)) if (5..8).contains(&buffer.offset()) => {
+                gst::PadProbeReturn::Drop
+            }
+            _ => gst::PadProbeReturn::Ok,
+        })
+        .unwrap();
+
+    let summary = summary(&pipeline);
+    pipeline.set_state(gst::State::Null).unwrap();
+    assert_eq!(counts(&summary), (27, 0, 3, 0));
+}
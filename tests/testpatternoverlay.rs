@@ -0,0 +1,105 @@
+// Copyright (C) 2022 Alexander Stein <alexander.stein@mailbox.org>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use gst::prelude::*;
+use std::sync::{Arc, Mutex};
+
+const WIDTH: usize = 16;
+const HEIGHT: usize = 48;
+
+fn init() {
+    use std::sync::Once;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        gst::init().unwrap();
+        gstrstestpattern::plugin_register_static().unwrap();
+    });
+}
+
+// Lines of the BGRx frames of a solid blue videotestsrc stream that went
+// through `filter`
+fn lines(filter: &str) -> Vec<Vec<Vec<u8>>> {
+    let pipeline = gst::parse_launch(&format!(
+        "videotestsrc pattern=solid-color foreground-color=0xff0000ff num-buffers=5 \
+         ! video/x-raw,format=BGRx,width={},height={},framerate=30/1 ! {} \
+         ! video/x-raw,format=BGRx ! fakesink name=sink signal-handoffs=true",
+        WIDTH, HEIGHT, filter
+    ))
+    .unwrap();
+    let sink = pipeline
+        .downcast_ref::<gst::Bin>()
+        .unwrap()
+        .by_name("sink")
+        .unwrap();
+
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    let handoff_frames = frames.clone();
+    sink.connect("handoff", false, move |args| {
+        let buffer = args[1].get::<gst::Buffer>().unwrap();
+        let map = buffer.map_readable().unwrap();
+        let lines: Vec<_> = map.chunks(WIDTH * 4).map(<[u8]>::to_vec).collect();
+        handoff_frames.lock().unwrap().push(lines);
+        None
+    });
+
+    pipeline.set_state(gst::State::Playing).unwrap();
+    let msg = pipeline
+        .bus()
+        .unwrap()
+        .timed_pop_filtered(
+            gst::ClockTime::from_seconds(10),
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        )
+        .expect("timed out");
+    if let gst::MessageView::Error(err) = msg.view() {
+        panic!("{} ({:?})", err.error(), err.debug());
+    }
+    pipeline.set_state(gst::State::Null).unwrap();
+
+    let frames = std::mem::take(&mut *frames.lock().unwrap());
+    frames
+}
+
+#[test]
+fn bar_blended_over_video() {
+    init();
+
+    let original = lines("identity");
+    let overlaid = lines("rstestpatternoverlay size=10 speed=5");
+    assert_eq!(original.len(), 5);
+    assert_eq!(overlaid.len(), 5);
+
+    for (n, (original, overlaid)) in original.iter().zip(overlaid.iter()).enumerate() {
+        let bar = n * 5..n * 5 + 10;
+        for (y, (original, overlaid)) in original.iter().zip(overlaid.iter()).enumerate() {
+            if bar.contains(&y) {
+                assert!(
+                    overlaid.chunks(4).all(|pixel| pixel[..3] == [0xff; 3]),
+                    "frame {} line {}",
+                    n,
+                    y
+                );
+            } else {
+                assert_eq!(original, overlaid, "frame {} line {}", n, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn disabled_passes_through() {
+    init();
+
+    assert_eq!(
+        lines("identity"),
+        lines("rstestpatternoverlay enabled=false size=10")
+    );
+}